@@ -0,0 +1,321 @@
+use std::io::Cursor;
+
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitmap::{format::RawBitmapType, image::BitmapImage},
+    database::{SilverResource, SilverResourceID, SilverSection},
+    section_content::SectionContent,
+    silver_error::SilverError,
+};
+
+/// The amount of transparent pixels separating sprites within a page.
+const GUTTER: u32 = 1;
+
+/// The placement of a single bitmap within an atlas.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AtlasSprite {
+    /// The resource ID of this bitmap.
+    pub id: SilverResourceID,
+    /// The index of the page this bitmap resides within.
+    pub page: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// The format this bitmap is encoded in.
+    pub format: RawBitmapType,
+    /// The row stride of this bitmap. See `RawBitmapHeader::rendered_width`.
+    pub rendered_width: u16,
+}
+
+/// Bitmaps within a section, packed into one or a few larger images.
+///
+/// Only bitmaps with pixel data are packed: those lacking data, or of unknown
+/// or malformed types, have no representation within an atlas.
+pub struct Atlas {
+    /// Every page of our atlas.
+    pub pages: Vec<RgbaImage>,
+    /// The placement of every bitmap, in section order.
+    pub sprites: Vec<AtlasSprite>,
+}
+
+impl Atlas {
+    /// Packs bitmaps within the given section into pages.
+    ///
+    /// Pages are at most `max_size` pixels in either dimension,
+    /// unless a single bitmap is larger.
+    pub fn pack(section: &SilverSection, max_size: u32) -> Result<Self, SilverError> {
+        let mut bitmaps: Vec<(SilverResourceID, &BitmapImage, RgbaImage)> = Vec::new();
+        for resource in &section.resources {
+            let SectionContent::Bitmap(Some(bitmap)) = &resource.contents else {
+                continue;
+            };
+            if bitmap.contents.is_empty() {
+                continue;
+            }
+            bitmaps.push((resource.id, bitmap, bitmap.pixels()?));
+        }
+
+        // Place bitmaps from tallest to shortest on shelves, left to right.
+        let mut placement_order: Vec<usize> = (0..bitmaps.len()).collect();
+        placement_order.sort_by_key(|index| {
+            let (id, _, pixels) = &bitmaps[*index];
+            (u32::MAX - pixels.height(), u32::MAX - pixels.width(), *id)
+        });
+
+        let widest = bitmaps
+            .iter()
+            .map(|(_, _, pixels)| pixels.width())
+            .max()
+            .unwrap_or_default();
+        let page_width = max_size.max(widest);
+
+        let mut placements = vec![(0, 0, 0); bitmaps.len()];
+        let mut page_extents: Vec<(u32, u32)> = Vec::new();
+        let (mut page, mut x, mut shelf_y, mut shelf_height) = (0, 0, 0, 0);
+        for index in placement_order {
+            let (width, height) = bitmaps[index].2.dimensions();
+            if x > 0 && x + width > page_width {
+                // Begin a new shelf.
+                shelf_y += shelf_height + GUTTER;
+                x = 0;
+                shelf_height = 0;
+            }
+            if shelf_y > 0 && shelf_y + height > max_size {
+                // Begin a new page.
+                page += 1;
+                shelf_y = 0;
+            }
+
+            placements[index] = (page, x, shelf_y);
+            if page_extents.len() <= page {
+                page_extents.push((0, 0));
+            }
+            let extent = &mut page_extents[page];
+            extent.0 = extent.0.max(x + width);
+            extent.1 = extent.1.max(shelf_y + height);
+
+            x += width + GUTTER;
+            shelf_height = shelf_height.max(height);
+        }
+
+        let mut pages: Vec<RgbaImage> = page_extents
+            .iter()
+            .map(|(width, height)| RgbaImage::new(*width, *height))
+            .collect();
+        let mut sprites = Vec::new();
+        for ((id, bitmap, pixels), (page, x, y)) in bitmaps.iter().zip(placements) {
+            imageops::replace(&mut pages[page], pixels, x as i64, y as i64);
+            sprites.push(AtlasSprite {
+                id: *id,
+                page,
+                x,
+                y,
+                width: pixels.width(),
+                height: pixels.height(),
+                format: bitmap.format_type,
+                rendered_width: bitmap.rendered_width,
+            });
+        }
+
+        Ok(Atlas { pages, sprites })
+    }
+
+    /// Slices our pages back into individual bitmaps within the given section.
+    ///
+    /// Bitmaps already within the section have their pixels, format and stride replaced;
+    /// unmodified sprites are reduced byte-for-byte. Sprites for resources not yet
+    /// present are appended as new bitmaps. Either are re-encoded when written.
+    pub fn apply(&self, section: &mut SilverSection) -> Result<(), SilverError> {
+        for sprite in &self.sprites {
+            let page = self.pages.get(sprite.page).ok_or_else(|| {
+                SilverError::InvalidAtlas(format!(
+                    "{} is on missing page {}",
+                    sprite.id, sprite.page
+                ))
+            })?;
+            let is_within_page = sprite.x.checked_add(sprite.width) <= Some(page.width())
+                && sprite.y.checked_add(sprite.height) <= Some(page.height());
+            if !is_within_page {
+                return Err(SilverError::InvalidAtlas(format!(
+                    "{} extends beyond page {}",
+                    sprite.id, sprite.page
+                )));
+            }
+
+            let pixels = imageops::crop_imm(page, sprite.x, sprite.y, sprite.width, sprite.height)
+                .to_image();
+            let mut png_writer = Cursor::new(Vec::new());
+            pixels.write_to(&mut png_writer, image::ImageFormat::Png)?;
+
+            let contents = png_writer.into_inner();
+            let new_bitmap = || {
+                SectionContent::Bitmap(Some(BitmapImage {
+                    width: sprite.width,
+                    height: sprite.height,
+                    rendered_width: sprite.rendered_width,
+                    format_type: sprite.format,
+                    resource_id: sprite.id.0,
+                    contents: contents.clone(),
+                    raw: None,
+                }))
+            };
+
+            let existing = section
+                .resources
+                .iter_mut()
+                .find(|resource| resource.id == sprite.id)
+                .map(|resource| &mut resource.contents);
+            match existing {
+                Some(SectionContent::Bitmap(Some(bitmap))) => {
+                    bitmap.width = sprite.width;
+                    bitmap.height = sprite.height;
+                    bitmap.format_type = sprite.format;
+                    bitmap.rendered_width = sprite.rendered_width;
+                    bitmap.contents = contents;
+                }
+                Some(existing @ SectionContent::Bitmap(None)) => *existing = new_bitmap(),
+                Some(_) => {
+                    return Err(SilverError::InvalidAtlas(format!(
+                        "{} is not a bitmap",
+                        sprite.id
+                    )))
+                }
+                None => section.resources.push(SilverResource {
+                    id: sprite.id,
+                    contents: new_bitmap(),
+                }),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitmap::format::tests::NON_SQUARE_RGB565, section_types::SectionType};
+
+    /// A bitmap of the given format, parsed from its reduced form as if read from a database.
+    fn bitmap(id: u32, width: u32, height: u32, format_type: RawBitmapType) -> SilverResource {
+        let pixels = RgbaImage::from_fn(width, height, |x, y| {
+            image::Rgba([(x * 40) as u8, (y * 40) as u8, (id * 60) as u8, 255])
+        });
+        let mut png_writer = Cursor::new(Vec::new());
+        pixels
+            .write_to(&mut png_writer, image::ImageFormat::Png)
+            .unwrap();
+        let image = BitmapImage {
+            width,
+            height,
+            rendered_width: 0,
+            format_type,
+            resource_id: 0x0dad0000 + id,
+            contents: png_writer.into_inner(),
+            raw: None,
+        };
+        let reduced = image.reduce().unwrap();
+        SilverResource {
+            id: SilverResourceID(0x0dad0000 + id),
+            contents: SectionContent::Bitmap(BitmapImage::parse(reduced).unwrap()),
+        }
+    }
+
+    fn section(resources: Vec<SilverResource>) -> SilverSection {
+        SilverSection {
+            section_type: SectionType::from_name("BMap".to_string()).unwrap(),
+            is_sequential: 0,
+            resources,
+        }
+    }
+
+    fn reduce_all(section: &SilverSection) -> Vec<Option<Vec<u8>>> {
+        section
+            .resources
+            .iter()
+            .map(|resource| match &resource.contents {
+                SectionContent::Bitmap(Some(bitmap)) => Some(bitmap.clone().reduce().unwrap()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_verbatim() {
+        let mut original = section(vec![
+            bitmap(1, 5, 3, RawBitmapType::Argb8888),
+            bitmap(2, 4, 4, RawBitmapType::RgbEight),
+            bitmap(3, 7, 2, RawBitmapType::GrayscaleFour),
+            SilverResource {
+                id: SilverResourceID(0x0dad0004),
+                contents: SectionContent::Bitmap(None),
+            },
+        ]);
+        original.resources.push(SilverResource {
+            id: SilverResourceID(0x0dad0005),
+            contents: SectionContent::Bitmap(
+                BitmapImage::parse(NON_SQUARE_RGB565.to_vec()).unwrap(),
+            ),
+        });
+
+        let atlas = Atlas::pack(&original, 64).unwrap();
+        assert_eq!(atlas.pages.len(), 1);
+        assert_eq!(atlas.sprites.len(), 4);
+
+        let mut applied = original.clone();
+        atlas.apply(&mut applied).unwrap();
+        assert_eq!(reduce_all(&applied), reduce_all(&original));
+        assert_eq!(reduce_all(&original)[4].as_deref(), Some(NON_SQUARE_RGB565));
+    }
+
+    #[test]
+    fn packs_across_pages() {
+        let original = section(
+            (1..=5)
+                .map(|id| bitmap(id, 8, 8, RawBitmapType::Rgb565))
+                .collect(),
+        );
+
+        // Two shelves of two bitmaps fit within a page, alongside their gutters.
+        let atlas = Atlas::pack(&original, 20).unwrap();
+        assert_eq!(atlas.pages.len(), 2);
+        for page in &atlas.pages {
+            assert!(page.width() <= 20 && page.height() <= 20);
+        }
+        let pages: Vec<usize> = atlas.sprites.iter().map(|sprite| sprite.page).collect();
+        assert_eq!(pages, vec![0, 0, 0, 0, 1]);
+
+        // No sprites overlap within a page.
+        for (index, a) in atlas.sprites.iter().enumerate() {
+            for b in &atlas.sprites[index + 1..] {
+                let is_disjoint = a.page != b.page
+                    || a.x + a.width <= b.x
+                    || b.x + b.width <= a.x
+                    || a.y + a.height <= b.y
+                    || b.y + b.height <= a.y;
+                assert!(is_disjoint, "{} overlaps {}", a.id, b.id);
+            }
+        }
+
+        let mut applied = original.clone();
+        atlas.apply(&mut applied).unwrap();
+        assert_eq!(reduce_all(&applied), reduce_all(&original));
+    }
+
+    #[test]
+    fn rejects_sprites_beyond_their_page() {
+        let original = section(vec![bitmap(1, 4, 4, RawBitmapType::Rgb565)]);
+        let mut atlas = Atlas::pack(&original, 16).unwrap();
+
+        atlas.sprites[0].x = 1;
+        let result = atlas.apply(&mut original.clone());
+        assert!(matches!(result, Err(SilverError::InvalidAtlas(_))));
+
+        atlas.sprites[0].x = 0;
+        atlas.sprites[0].page = 1;
+        let result = atlas.apply(&mut original.clone());
+        assert!(matches!(result, Err(SilverError::InvalidAtlas(_))));
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashSet, fmt, io::Cursor};
+
+use image::RgbaImage;
+
+use crate::{
+    bitmap::{
+        format::RawBitmapType, image::BitmapImage, pixel::decode_pixels, quantize::QuantizeOptions,
+    },
+    device::DeviceProfile,
+    silver_error::SilverError,
+};
+
+/// The loss incurred by converting a bitmap to another format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversionReport {
+    /// The format converted from.
+    pub original_format: RawBitmapType,
+    /// The format converted to.
+    pub converted_format: RawBitmapType,
+    /// The size of the bitmap's raw contents before conversion, in bytes.
+    pub original_size: usize,
+    /// The size of the bitmap's raw contents after conversion, in bytes.
+    pub converted_size: usize,
+    /// The largest difference within any channel of any pixel.
+    pub max_error: u8,
+    /// The average difference across all channels of all pixels.
+    ///
+    /// Color channels of pixels fully transparent before and after are not compared,
+    /// as they are never visible.
+    pub mean_error: f64,
+    /// Whether translucent pixels were made opaque, as the new format lacks alpha.
+    pub alpha_dropped: bool,
+    /// The amount of distinct colors within the bitmap,
+    /// if more than the new format's palette can hold.
+    pub palette_overflow: Option<usize>,
+}
+
+impl ConversionReport {
+    /// Whether the converted bitmap looks identical to the original.
+    pub fn is_lossless(&self) -> bool {
+        self.max_error == 0
+    }
+
+    /// Compares pixels before and after conversion.
+    fn compare(
+        original: &RgbaImage,
+        converted: &RgbaImage,
+        converted_format: RawBitmapType,
+    ) -> (u8, f64, bool, Option<usize>) {
+        let mut max_error = 0;
+        let mut total_error = 0u64;
+        let mut compared = 0u64;
+        for (before, after) in original.pixels().zip(converted.pixels()) {
+            let is_invisible = before.0[3] == 0 && after.0[3] == 0;
+            let channels = if is_invisible { 3..4 } else { 0..4 };
+            for channel in channels {
+                let error = before.0[channel].abs_diff(after.0[channel]);
+                max_error = max_error.max(error);
+                total_error += error as u64;
+                compared += 1;
+            }
+        }
+        let mean_error = total_error as f64 / compared.max(1) as f64;
+
+        let alpha_dropped =
+            !converted_format.has_alpha() && original.pixels().any(|pixel| pixel.0[3] != 255);
+        let palette_overflow = converted_format.max_colors().and_then(|max_colors| {
+            let colors: HashSet<[u8; 4]> = original.pixels().map(|pixel| pixel.0).collect();
+            (colors.len() > max_colors).then_some(colors.len())
+        });
+        (max_error, mean_error, alpha_dropped, palette_overflow)
+    }
+}
+
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} to {:?}: {} to {} bytes, max error {}, mean error {:.2}",
+            self.original_format,
+            self.converted_format,
+            self.original_size,
+            self.converted_size,
+            self.max_error,
+            self.mean_error
+        )?;
+        if self.alpha_dropped {
+            write!(f, ", alpha dropped")?;
+        }
+        if let Some(colors) = self.palette_overflow {
+            let max_colors = self.converted_format.max_colors().unwrap_or_default();
+            write!(f, ", {colors} colors exceed palette of {max_colors}")?;
+        }
+        Ok(())
+    }
+}
+
+impl BitmapImage {
+    /// Re-encodes this bitmap to the given format, reporting any loss incurred.
+    /// Paletted and low-bit-depth formats are quantized with the given options.
+    ///
+    /// The converted bitmap holds its new raw representation, and is reduced as-is.
+    pub fn convert(
+        &self,
+        format_type: RawBitmapType,
+        profile: &DeviceProfile,
+        quantize_options: &QuantizeOptions,
+    ) -> Result<(BitmapImage, ConversionReport), SilverError> {
+        if !format_type.is_known() {
+            return Err(SilverError::BitmapEncodingFailure(format!(
+                "cannot encode to unknown format {format_type:?}"
+            )));
+        }
+
+        let original = self.pixels()?;
+        let original_size = self.clone().reduce_with_profile(profile)?.len();
+
+        let mut converted = self.clone();
+        converted.format_type = format_type;
+        let raw = converted.encode(profile, quantize_options)?;
+        let converted_pixels = decode_pixels(&raw.header, &raw.contents)?.to_rgba8();
+        let mut png_writer = Cursor::new(Vec::new());
+        converted_pixels.write_to(&mut png_writer, image::ImageFormat::Png)?;
+
+        let (max_error, mean_error, alpha_dropped, palette_overflow) =
+            ConversionReport::compare(&original, &converted_pixels, format_type);
+        let report = ConversionReport {
+            original_format: self.format_type,
+            converted_format: format_type,
+            original_size,
+            converted_size: raw.reduce()?.len(),
+            max_error,
+            mean_error,
+            alpha_dropped,
+            palette_overflow,
+        };
+
+        converted.rendered_width = raw.header.rendered_width;
+        converted.contents = png_writer.into_inner();
+        converted.raw = Some(raw);
+        Ok((converted, report))
+    }
+
+    /// Finds the smallest of the given formats that represents this bitmap identically,
+    /// returning the converted bitmap if smaller than at present.
+    ///
+    /// Only conversions without loss are considered, so no quantization options are needed.
+    pub fn optimize(
+        &self,
+        formats: &[RawBitmapType],
+        profile: &DeviceProfile,
+    ) -> Result<Option<(BitmapImage, ConversionReport)>, SilverError> {
+        let colors: HashSet<[u8; 4]> = self.pixels()?.pixels().map(|pixel| pixel.0).collect();
+
+        let mut smallest: Option<(BitmapImage, ConversionReport)> = None;
+        for format_type in formats {
+            // Palettes too small to hold every color can never be lossless.
+            let is_overflowing = format_type
+                .max_colors()
+                .is_some_and(|max_colors| colors.len() > max_colors);
+            if *format_type == self.format_type || !format_type.is_known() || is_overflowing {
+                continue;
+            }
+
+            let (converted, report) =
+                self.convert(*format_type, profile, &QuantizeOptions::default())?;
+            let smallest_size = match &smallest {
+                Some((_, smallest)) => smallest.converted_size,
+                None => report.original_size,
+            };
+            if report.is_lossless() && report.converted_size < smallest_size {
+                smallest = Some((converted, report));
+            }
+        }
+        Ok(smallest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitmap::expand_channel;
+
+    fn bitmap(pixels: &RgbaImage, format_type: RawBitmapType) -> BitmapImage {
+        let mut png_writer = Cursor::new(Vec::new());
+        pixels
+            .write_to(&mut png_writer, image::ImageFormat::Png)
+            .unwrap();
+        BitmapImage {
+            width: pixels.width(),
+            height: pixels.height(),
+            rendered_width: 0,
+            format_type,
+            resource_id: 1,
+            contents: png_writer.into_inner(),
+            raw: None,
+        }
+    }
+
+    #[test]
+    fn reports_dropped_alpha() {
+        let pixels = RgbaImage::from_fn(4, 4, |x, y| image::Rgba([255, 0, 0, (x * y * 16) as u8]));
+        let profile = DeviceProfile::generic();
+        let (_, report) = bitmap(&pixels, RawBitmapType::Argb8888)
+            .convert(RawBitmapType::Rgb565, &profile, &QuantizeOptions::default())
+            .unwrap();
+        assert!(report.alpha_dropped);
+        assert!(!report.is_lossless());
+        assert_eq!(report.palette_overflow, None);
+    }
+
+    #[test]
+    fn optimizes_to_smaller_lossless_format() {
+        // Every channel is representable within five or six bits.
+        let pixels = RgbaImage::from_fn(8, 8, |x, y| {
+            image::Rgba([
+                expand_channel((x * 4) as u8, 5),
+                expand_channel((x * 8 + y) as u8, 6),
+                expand_channel((y * 4) as u8, 5),
+                255,
+            ])
+        });
+
+        let profile = DeviceProfile::generic();
+        let original = bitmap(&pixels, RawBitmapType::Argb8888);
+        let formats = [RawBitmapType::Argb4444, RawBitmapType::Rgb565];
+        let (converted, report) = original.optimize(&formats, &profile).unwrap().unwrap();
+        assert_eq!(converted.format_type, RawBitmapType::Rgb565);
+        assert!(report.is_lossless());
+        assert!(report.converted_size < report.original_size);
+        assert_eq!(converted.pixels().unwrap(), pixels);
+    }
+}
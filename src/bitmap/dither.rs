@@ -0,0 +1,209 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::silver_error::SilverError;
+
+/// Methods of reducing pixels to the few levels or colors a format permits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Dither {
+    /// Rounds every pixel to its nearest level, without dithering.
+    #[default]
+    Threshold,
+    /// Offsets pixels by an 8x8 Bayer matrix before rounding.
+    /// This produces a regular crosshatch, which suits flat imagery.
+    Bayer,
+    /// Diffuses all rounding error to neighbouring pixels (Floyd-Steinberg).
+    /// This best preserves gradients and photographic imagery.
+    FloydSteinberg,
+    /// Diffuses three quarters of rounding error to neighbouring pixels (Atkinson).
+    /// This preserves contrast within highlights and shadows.
+    Atkinson,
+}
+
+impl Dither {
+    /// All available dithering methods.
+    pub const ALL: [Dither; 4] = [
+        Dither::Threshold,
+        Dither::Bayer,
+        Dither::FloydSteinberg,
+        Dither::Atkinson,
+    ];
+
+    /// The name of this method, as accepted by `Dither::from_str`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dither::Threshold => "threshold",
+            Dither::Bayer => "bayer",
+            Dither::FloydSteinberg => "floyd-steinberg",
+            Dither::Atkinson => "atkinson",
+        }
+    }
+
+    /// The neighbours rounding error is diffused to, as offsets to the right
+    /// and below alongside their share of error.
+    fn diffusion(&self) -> &'static [(isize, usize, f32)] {
+        match self {
+            Dither::Threshold | Dither::Bayer => &[],
+            Dither::FloydSteinberg => &[
+                (1, 0, 7.0 / 16.0),
+                (-1, 1, 3.0 / 16.0),
+                (0, 1, 5.0 / 16.0),
+                (1, 1, 1.0 / 16.0),
+            ],
+            Dither::Atkinson => &[
+                (1, 0, 1.0 / 8.0),
+                (2, 0, 1.0 / 8.0),
+                (-1, 1, 1.0 / 8.0),
+                (0, 1, 1.0 / 8.0),
+                (1, 1, 1.0 / 8.0),
+                (0, 2, 1.0 / 8.0),
+            ],
+        }
+    }
+}
+
+impl FromStr for Dither {
+    type Err = SilverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Dither::ALL
+            .into_iter()
+            .find(|dither| dither.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| SilverError::UnknownDither(s.to_string()))
+    }
+}
+
+impl fmt::Display for Dither {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The largest offset any diffusion reaches, to the left or right.
+const DIFFUSION_REACH: usize = 2;
+
+/// The threshold of the given position within an 8x8 Bayer matrix, from 0 to 1 exclusive.
+fn bayer_threshold(x: usize, y: usize) -> f32 {
+    // Interleaving the bits of x ^ y and y, in reverse, forms the matrix's index.
+    let (x, y) = (x & 7, y & 7);
+    let mut index = 0;
+    for bit in 0..3 {
+        index |= (((x ^ y) >> bit) & 1) << (5 - bit * 2);
+        index |= ((y >> bit) & 1) << (4 - bit * 2);
+    }
+    (index as f32 + 0.5) / 64.0
+}
+
+/// Dithers rows of pixels with `N` channels, returning the chosen output of every pixel.
+///
+/// `choose` is given a pixel's value, alongside a threshold between 0 and 1 for
+/// rounding. It returns its output, and the channel values that output represents.
+/// Without ordered dithering, the threshold is always 0.5: that is, nearest.
+pub(super) fn dither<const N: usize, T>(
+    rows: &[Vec<[f32; N]>],
+    method: Dither,
+    mut choose: impl FnMut([f32; N], f32) -> (T, [f32; N]),
+) -> Vec<T> {
+    let width = rows.first().map(Vec::len).unwrap_or_default();
+    let diffusion = method.diffusion();
+
+    // Errors for the current row and the two following. They are offset,
+    // so that diffusion to the left of the first pixel remains in bounds.
+    let mut errors = vec![vec![[0f32; N]; width + DIFFUSION_REACH * 2]; 3];
+    let mut outputs = Vec::with_capacity(width * rows.len());
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            let error = errors[0][x + DIFFUSION_REACH];
+            let mut value = [0f32; N];
+            for channel in 0..N {
+                value[channel] = (pixel[channel] + error[channel]).clamp(0.0, 255.0);
+            }
+
+            let threshold = match method {
+                Dither::Bayer => bayer_threshold(x, y),
+                _ => 0.5,
+            };
+            let (output, chosen) = choose(value, threshold);
+            outputs.push(output);
+
+            for (dx, dy, share) in diffusion {
+                let target = &mut errors[*dy][(x + DIFFUSION_REACH).wrapping_add_signed(*dx)];
+                for channel in 0..N {
+                    target[channel] += (value[channel] - chosen[channel]) * share;
+                }
+            }
+        }
+
+        errors.rotate_left(1);
+        errors[2].fill([0.0; N]);
+    }
+    outputs
+}
+
+/// Chooses between the closest level below and above the given value,
+/// returning the index of the chosen level and its value.
+///
+/// Levels must be sorted in ascending order.
+pub(super) fn choose_level(levels: &[f32], value: f32, threshold: f32) -> (usize, f32) {
+    let upper = levels
+        .iter()
+        .position(|level| *level >= value)
+        .unwrap_or(levels.len() - 1);
+    if upper == 0 || levels[upper] == value {
+        return (upper, levels[upper]);
+    }
+
+    let lower = upper - 1;
+    let fraction = (value - levels[lower]) / (levels[upper] - levels[lower]);
+    match fraction >= threshold {
+        true => (upper, levels[upper]),
+        false => (lower, levels[lower]),
+    }
+}
+
+/// Converts an eight-bit value to linear light with the given gamma, if any.
+pub(super) fn linearize(value: u8, gamma: Option<f32>) -> f32 {
+    match gamma {
+        Some(gamma) => (value as f32 / 255.0).powf(gamma) * 255.0,
+        None => value as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bayer_thresholds_are_distinct() {
+        let mut thresholds: Vec<u32> = (0..64)
+            .map(|index| (bayer_threshold(index % 8, index / 8) * 64.0) as u32)
+            .collect();
+        assert_eq!(bayer_threshold(0, 0), 0.5 / 64.0);
+        thresholds.sort();
+        assert_eq!(thresholds, (0..64).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn dithering_preserves_average_intensity() {
+        // Two levels, black and white, with an image of a uniform quarter intensity.
+        let levels = [0.0, 255.0];
+        let rows = vec![vec![[63.75f32]; 16]; 16];
+        for method in [Dither::Bayer, Dither::FloydSteinberg] {
+            let outputs = dither(&rows, method, |value, threshold| {
+                let (index, level) = choose_level(&levels, value[0], threshold);
+                (index, [level])
+            });
+            let white = outputs.iter().filter(|index| **index == 1).count();
+            assert!((60..=68).contains(&white), "{method}: {white} of 256");
+        }
+
+        let outputs = dither(&rows, Dither::Threshold, |value, threshold| {
+            let (index, level) = choose_level(&levels, value[0], threshold);
+            (index, [level])
+        });
+        assert!(outputs.iter().all(|index| *index == 0));
+    }
+}
@@ -1,118 +1,419 @@
-use std::io::Cursor;
+use std::{fmt, io::Cursor, str::FromStr};
 
-use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{little_helper::LittleHelper, SilverError};
+use crate::{
+    bitmap::pixel::pixel_issues, device::DeviceProfile, little_helper::LittleHelper, SilverError,
+};
 
 /// Possible representations of bitmap data.
-#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug)]
-#[repr(u16)]
+///
+/// Within raw contents and YAML representations, types are represented by their code.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum RawBitmapType {
-    GrayscaleTwo = 0x0002,
-    GrayscaleFour = 0x0004,
-    GrayscaleEight = 0x0008,
-    Rgb565 = 0x0565,
-    Argb4444 = 0x1444,
-    Argb8888 = 0x1888,
-    RgbEight = 0x0064,
-    RgbSixteen = 0x0065,
+    GrayscaleTwo,
+    GrayscaleFour,
+    GrayscaleEight,
+    Rgb565,
+    Argb4444,
+    Argb8888,
+    RgbEight,
+    RgbSixteen,
+    /// A type we do not yet understand.
+    /// Its pixel data is preserved as-is, but cannot be decoded.
+    Unknown(u16),
 }
 
-impl TryFrom<u16> for RawBitmapType {
-    type Error = SilverError;
-
-    fn try_from(v: u16) -> Result<Self, Self::Error> {
+impl From<u16> for RawBitmapType {
+    fn from(v: u16) -> Self {
         match v {
-            x if x == RawBitmapType::GrayscaleTwo as u16 => Ok(RawBitmapType::GrayscaleTwo),
-            x if x == RawBitmapType::GrayscaleFour as u16 => Ok(RawBitmapType::GrayscaleFour),
-            x if x == RawBitmapType::GrayscaleEight as u16 => Ok(RawBitmapType::GrayscaleEight),
-            x if x == RawBitmapType::Rgb565 as u16 => Ok(RawBitmapType::Rgb565),
-            x if x == RawBitmapType::Argb4444 as u16 => Ok(RawBitmapType::Argb4444),
-            x if x == RawBitmapType::Argb8888 as u16 => Ok(RawBitmapType::Argb8888),
-            x if x == RawBitmapType::RgbEight as u16 => Ok(RawBitmapType::RgbEight),
-            x if x == RawBitmapType::RgbSixteen as u16 => Ok(RawBitmapType::RgbSixteen),
-            _ => Err(SilverError::UnknownBitmap),
+            0x0002 => RawBitmapType::GrayscaleTwo,
+            0x0004 => RawBitmapType::GrayscaleFour,
+            0x0008 => RawBitmapType::GrayscaleEight,
+            0x0565 => RawBitmapType::Rgb565,
+            0x1444 => RawBitmapType::Argb4444,
+            0x1888 => RawBitmapType::Argb8888,
+            0x0064 => RawBitmapType::RgbEight,
+            0x0065 => RawBitmapType::RgbSixteen,
+            code => RawBitmapType::Unknown(code),
         }
     }
 }
 
-#[derive(Deserialize, Serialize)]
-/// The raw representation of bitmap data within a resource entry's raw contents.
-pub struct RawBitmapData {
+/// Parses a known type by name, i.e. `Argb4444`, ignoring case.
+impl FromStr for RawBitmapType {
+    type Err = SilverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RawBitmapType::KNOWN
+            .into_iter()
+            .find(|image_type| format!("{image_type:?}").eq_ignore_ascii_case(s))
+            .ok_or_else(|| SilverError::UnknownBitmapFormat(s.to_string()))
+    }
+}
+
+impl Serialize for RawBitmapType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for RawBitmapType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(u16::deserialize(deserializer)?.into())
+    }
+}
+
+impl RawBitmapType {
+    /// Every type we are able to decode and encode.
+    pub const KNOWN: [RawBitmapType; 8] = [
+        RawBitmapType::GrayscaleTwo,
+        RawBitmapType::GrayscaleFour,
+        RawBitmapType::GrayscaleEight,
+        RawBitmapType::Rgb565,
+        RawBitmapType::Argb4444,
+        RawBitmapType::Argb8888,
+        RawBitmapType::RgbEight,
+        RawBitmapType::RgbSixteen,
+    ];
+
+    /// The code representing this type within raw contents.
+    pub fn code(&self) -> u16 {
+        match self {
+            RawBitmapType::GrayscaleTwo => 0x0002,
+            RawBitmapType::GrayscaleFour => 0x0004,
+            RawBitmapType::GrayscaleEight => 0x0008,
+            RawBitmapType::Rgb565 => 0x0565,
+            RawBitmapType::Argb4444 => 0x1444,
+            RawBitmapType::Argb8888 => 0x1888,
+            RawBitmapType::RgbEight => 0x0064,
+            RawBitmapType::RgbSixteen => 0x0065,
+            RawBitmapType::Unknown(code) => *code,
+        }
+    }
+
+    /// Whether we are able to decode and encode pixel data of this type.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, RawBitmapType::Unknown(_))
+    }
+
+    /// The color depth expected within headers of this type.
+    /// This is also the amount of bits per pixel, or per palette index.
+    pub fn color_depth(&self) -> u16 {
+        match self {
+            RawBitmapType::GrayscaleTwo => 2,
+            RawBitmapType::GrayscaleFour => 4,
+            RawBitmapType::GrayscaleEight => 8,
+            RawBitmapType::Rgb565 => 16,
+            RawBitmapType::Argb4444 => 16,
+            RawBitmapType::Argb8888 => 32,
+            RawBitmapType::RgbEight => 8,
+            RawBitmapType::RgbSixteen => 16,
+            // As we don't understand this type, we have no expectations.
+            RawBitmapType::Unknown(_) => 0,
+        }
+    }
+
+    /// Whether pixels of this type can be translucent.
+    /// Palette entries hold alpha, too.
+    pub fn has_alpha(&self) -> bool {
+        matches!(
+            self,
+            RawBitmapType::Argb4444
+                | RawBitmapType::Argb8888
+                | RawBitmapType::RgbEight
+                | RawBitmapType::RgbSixteen
+        )
+    }
+
+    /// The maximum amount of palette entries, if this type is paletted.
+    pub fn max_colors(&self) -> Option<usize> {
+        match self {
+            RawBitmapType::RgbEight => Some(1 << 8),
+            RawBitmapType::RgbSixteen => Some(1 << 16),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// The header preceding bitmap data within a resource entry's raw contents.
+pub struct RawBitmapHeader {
     /// The type of this bitmap image.
     pub image_type: RawBitmapType,
     /// This field's exact usage is unknown: it was observed to be one
     /// within external SilverImages, and zero within internal bitmaps.
     // TODO(spotlightishere): Determine exact usage
     pub is_external: u16,
-    /// The width this bitmap image will be rendered at.
+    /// The length of every row of pixels in bytes, including any padding.
+    ///
+    /// Some images record a value smaller than their row's length here
+    /// (such as their width in pixels), in which case rows are unpadded.
+    /// See `RawBitmapHeader::row_stride`.
     pub rendered_width: u16,
     /// The color depth associated with this image.
     /// (This is correlated to the image's bitmap type.)
     pub color_depth: u16,
     pub padding_one: u32,
     pub padding_two: u32,
+    /// The width of this bitmap image in pixels.
     pub width: u32,
+    /// The height of this bitmap image in pixels, i.e. its amount of rows.
     pub height: u32,
-    pub resource_id: u32,
+    /// The resource ID this bitmap is associated with.
+    /// Older firmware (e.g. for iPod classics) lacks this field entirely.
+    pub resource_id: Option<u32>,
     pub contents_length: u32,
+}
+
+impl RawBitmapHeader {
+    /// The amount of bytes holding a single row of pixels, excluding padding.
+    /// For paletted types, this covers their indices.
+    pub fn row_length(&self) -> u32 {
+        (self.width * self.image_type.color_depth() as u32).div_ceil(8)
+    }
+
+    /// The amount of bytes from the start of one row to the start of the next.
+    pub fn row_stride(&self) -> u32 {
+        self.row_length().max(self.rendered_width as u32)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// The raw representation of bitmap data within a resource entry's raw contents.
+pub struct RawBitmapData {
+    pub header: RawBitmapHeader,
+    /// The raw pixel payload (including any palette) following the header.
     pub contents: Vec<u8>,
 }
 
 impl RawBitmapData {
     /// Parses a resource entry's raw contents to our representation.
-    pub fn parse(raw_data: Vec<u8>) -> Result<Self, SilverError> {
+    pub fn parse(raw_data: Vec<u8>, profile: &DeviceProfile) -> Result<Self, SilverError> {
         // Read the internal representation.
         let resource_length = raw_data.len() as u32;
-        let cursor = Cursor::new(raw_data.clone());
+        let cursor = Cursor::new(raw_data);
         let mut helper = LittleHelper(cursor);
 
-        let mut representation = RawBitmapData {
-            image_type: helper.read_u16_le()?.try_into()?,
+        let mut header = RawBitmapHeader {
+            image_type: helper.read_u16_le()?.into(),
             is_external: helper.read_u16_le()?,
             rendered_width: helper.read_u16_le()?,
             color_depth: helper.read_u16_le()?,
             padding_one: helper.read_u32_le()?,
             padding_two: helper.read_u32_le()?,
+            // Height precedes width on disk, the reverse of our field order.
             height: helper.read_u32_le()?,
             width: helper.read_u32_le()?,
             // These will be filled in below.
-            resource_id: 0,
+            resource_id: None,
             contents_length: 0,
-            // We'll read once we can access the content length.
-            contents: vec![],
         };
 
         // Some older firmware (e.g. for iPod classics) lacks the resource ID
         // field within the bitmap-specific resource header, and skip
         // directly to the bitmap `contents_length` field.
         //
-        // Thankfully, we can logically infer what this value is intended to be:
+        // Our device profile tells us whether it's present. If we don't know
+        // our device, we can logically infer what this value is intended to be:
         // On iPods which do have resource IDs present (e.g. iPod nanos),
         // we should expect to see a resource ID of 0xDAD0_0000 or greater.
         // Alternatively, we may see a resource ID of 0 for some `StBm` images.
         let next_u32 = helper.read_u32_le()?;
-        if next_u32 >= 0x0DAD_0000 || next_u32 == 0 {
+        let has_resource_id = profile
+            .bitmap_resource_id
+            .unwrap_or(next_u32 >= 0x0DAD_0000 || next_u32 == 0);
+        if has_resource_id {
             // We'll fill in the resource ID, and read the actual content length.
-            representation.resource_id = next_u32;
-            representation.contents_length = helper.read_u32_le()?;
+            header.resource_id = Some(next_u32);
+            header.contents_length = helper.read_u32_le()?;
         } else {
-            representation.contents_length = next_u32;
+            header.contents_length = next_u32;
         }
 
         // Ensure that we have exactly enough data available to read the content's length.
         let remaining_data = resource_length - helper.pos_as_u32();
-        if representation.contents_length != remaining_data {
+        if header.contents_length != remaining_data {
             return Err(SilverError::InvalidBitmap);
         }
 
-        representation.contents = helper.read_length(representation.contents_length)?;
-        Ok(representation)
+        let contents = helper.read_length(header.contents_length)?;
+        Ok(RawBitmapData { header, contents })
+    }
+
+    /// Checks that our header is consistent with our type, dimensions and pixel data.
+    pub fn validate(&self) -> Vec<BitmapIssue> {
+        let header = &self.header;
+        let mut issues = Vec::new();
+        if header.contents_length as usize != self.contents.len() {
+            issues.push(BitmapIssue::ContentsLengthMismatch {
+                header: header.contents_length,
+                actual: self.contents.len(),
+            });
+        }
+
+        // We have no expectations of types we do not understand.
+        if !header.image_type.is_known() {
+            return issues;
+        }
+        let expected_depth = header.image_type.color_depth();
+        if header.color_depth != expected_depth {
+            issues.push(BitmapIssue::ColorDepthMismatch {
+                expected: expected_depth,
+                actual: header.color_depth,
+            });
+        }
+
+        // Bitmaps lacking pixel data entirely are permitted.
+        if !self.contents.is_empty() {
+            issues.extend(pixel_issues(header, &self.contents));
+        }
+        issues
+    }
+
+    /// Whether our pixel data can be decoded to an image.
+    pub fn is_decodable(&self) -> bool {
+        self.header.image_type.is_known()
+            && !self.contents.is_empty()
+            && !pixel_issues(&self.header, &self.contents)
+                .iter()
+                .any(BitmapIssue::prevents_decoding)
+    }
+
+    /// Reduces our representation to a resource entry's raw contents.
+    pub fn reduce(&self) -> Result<Vec<u8>, SilverError> {
+        let header = &self.header;
+        let mut writer = LittleHelper::new();
+        writer.write_u16_le(header.image_type.code())?;
+        writer.write_u16_le(header.is_external)?;
+        writer.write_u16_le(header.rendered_width)?;
+        writer.write_u16_le(header.color_depth)?;
+        writer.write_u32_le(header.padding_one)?;
+        writer.write_u32_le(header.padding_two)?;
+        writer.write_u32_le(header.height)?;
+        writer.write_u32_le(header.width)?;
+        if let Some(resource_id) = header.resource_id {
+            writer.write_u32_le(resource_id)?;
+        }
+        writer.write_u32_le(self.contents.len() as u32)?;
+        writer.write_length(&self.contents)?;
+        Ok(writer.contents())
+    }
+}
+
+/// Inconsistencies within raw bitmap data.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BitmapIssue {
+    /// The header's `contents_length` differs from the length of the data present.
+    ContentsLengthMismatch { header: u32, actual: usize },
+    /// The header's `color_depth` differs from that of its type.
+    ColorDepthMismatch { expected: u16, actual: u16 },
+    /// The palette is longer than the data holding it.
+    TruncatedPalette {
+        palette_length: u32,
+        available: usize,
+    },
+    /// There is less pixel data than required by the image's type and dimensions.
+    TruncatedPixels { expected: usize, actual: usize },
+    /// There is more pixel data than required by the image's type and dimensions,
+    /// or its final row is only partially padded.
+    UnexpectedLength { expected: usize, actual: usize },
+    /// A pixel references a color beyond the end of the palette.
+    /// Only the first such pixel is reported.
+    PaletteIndexOutOfRange {
+        x: u32,
+        y: u32,
+        index: u32,
+        palette_length: u32,
+    },
+}
+
+impl BitmapIssue {
+    /// Whether this issue prevents pixel data from being decoded.
+    pub fn prevents_decoding(&self) -> bool {
+        matches!(
+            self,
+            BitmapIssue::TruncatedPalette { .. }
+                | BitmapIssue::TruncatedPixels { .. }
+                | BitmapIssue::PaletteIndexOutOfRange { .. }
+        )
+    }
+}
+
+impl fmt::Display for BitmapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitmapIssue::ContentsLengthMismatch { header, actual } => write!(
+                f,
+                "header specifies {header} bytes of contents, but {actual} are present"
+            ),
+            BitmapIssue::ColorDepthMismatch { expected, actual } => write!(
+                f,
+                "color depth is {actual}, but its type has a depth of {expected}"
+            ),
+            BitmapIssue::TruncatedPalette {
+                palette_length,
+                available,
+            } => write!(
+                f,
+                "palette of {palette_length} colors exceeds the {available} bytes present"
+            ),
+            BitmapIssue::TruncatedPixels { expected, actual } => write!(
+                f,
+                "expected {expected} bytes of pixel data, but only {actual} are present"
+            ),
+            BitmapIssue::UnexpectedLength { expected, actual } => write!(
+                f,
+                "expected {expected} bytes of pixel data, but {actual} are present"
+            ),
+            BitmapIssue::PaletteIndexOutOfRange {
+                x,
+                y,
+                index,
+                palette_length,
+            } => write!(
+                f,
+                "pixel at ({x}, {y}) references color {index} of a {palette_length} color palette"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// A 3x2 RGB565 bitmap with resource ID 0x0dad0001, as laid out within a database.
+    pub(in crate::bitmap) const NON_SQUARE_RGB565: &[u8] = &[
+        0x65, 0x05, // Type: RGB565
+        0x00, 0x00, // Unknown
+        0x06, 0x00, // Row stride: 6 bytes
+        0x10, 0x00, // Color depth: 16
+        0x00, 0x00, 0x00, 0x00, // Padding
+        0x00, 0x00, 0x00, 0x00, // Padding
+        0x02, 0x00, 0x00, 0x00, // Height: 2
+        0x03, 0x00, 0x00, 0x00, // Width: 3
+        0x01, 0x00, 0xad, 0x0d, // Resource ID
+        0x0c, 0x00, 0x00, 0x00, // Contents length: 12
+        0x00, 0xf8, 0xe0, 0x07, 0x1f, 0x00, // Red, green, blue
+        0xff, 0xff, 0x00, 0x00, 0xff, 0xff, // White, black, white
+    ];
+
+    #[test]
+    fn parses_height_before_width() {
+        let raw = RawBitmapData::parse(NON_SQUARE_RGB565.to_vec(), &DeviceProfile::generic())
+            .expect("should parse bitmap");
+        assert_eq!(raw.header.width, 3);
+        assert_eq!(raw.header.height, 2);
+        assert_eq!(raw.header.row_length(), 6);
+        assert_eq!(raw.header.resource_id, Some(0x0dad0001));
+        assert!(raw.validate().is_empty());
     }
 
-    // Reduces our representation to a resource entry's raw contents.
-    pub fn _reduce(self) -> Result<Vec<u8>, SilverError> {
-        todo!()
+    #[test]
+    fn reduces_to_identical_header() {
+        let raw = RawBitmapData::parse(NON_SQUARE_RGB565.to_vec(), &DeviceProfile::generic())
+            .expect("should parse bitmap");
+        assert_eq!(raw.reduce().unwrap(), NON_SQUARE_RGB565);
     }
 }
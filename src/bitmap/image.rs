@@ -1,267 +1,273 @@
 use std::io::Cursor;
 
 use crate::{
-    bitmap::format::{RawBitmapData, RawBitmapType},
+    bitmap::{
+        format::{RawBitmapData, RawBitmapHeader, RawBitmapType},
+        pixel::{decode_pixels, encode_pixels, separate_palette},
+        quantize::QuantizeOptions,
+    },
+    device::DeviceProfile,
     SilverError,
 };
-use image::{GrayImage, RgbImage, RgbaImage};
+use image::RgbaImage;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 /// A higher-level representation of bitmap imagery.
 /// It holds minimal information to lower to an internal representation,
 /// alongside re-encoding the bitmap image to a PNG.
 ///
 /// It's assumed that `contents` is a PNG.
 pub struct BitmapImage {
-    /// The width of our bitmap data.
+    /// The width of our bitmap data in pixels.
     pub width: u32,
-    /// The height of our bitmap data.
+    /// The height of our bitmap data in pixels.
     pub height: u32,
-    /// The height this bitmap image is rendered at.
+    /// The length of every row within our raw bitmap data in bytes.
+    /// See `RawBitmapHeader::rendered_width` for specifics.
     pub rendered_width: u16,
     /// The format this bitmap image is encoded in.
     pub format_type: RawBitmapType,
     /// The resource ID this bitmap data is associated with.
     pub resource_id: u32,
     /// A PNG-encoded version of our bitmap image.
+    /// Bitmaps with a header but no pixel data have empty contents.
     pub contents: Vec<u8>,
+    /// The raw representation this image was parsed from, if any.
+    ///
+    /// Quantized formats do not survive a PNG round trip exactly, so
+    /// this is reused when reducing so long as our pixels are unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<RawBitmapData>,
 }
 
 impl BitmapImage {
     /// Parses a resource entry's raw contents to our representation.
     pub fn parse(raw_data: Vec<u8>) -> Result<Option<Self>, SilverError> {
+        BitmapImage::parse_with_profile(raw_data, &DeviceProfile::generic())
+    }
+
+    /// Parses a resource entry's raw contents using the rules of the given device profile.
+    pub fn parse_with_profile(
+        raw_data: Vec<u8>,
+        profile: &DeviceProfile,
+    ) -> Result<Option<Self>, SilverError> {
         // Some bitmap images have no data. It's unclear why this is.
         if raw_data.is_empty() {
             return Ok(None);
         }
 
-        let raw_format = RawBitmapData::parse(raw_data.clone())?;
+        let raw_format = RawBitmapData::parse(raw_data, profile)?;
+        let header = &raw_format.header;
 
-        // TODO(spotlightishere): Remove
-        let resource_id = raw_format.resource_id;
-        println!("{} is {:?}", resource_id, raw_format.image_type);
-        println!("\tColor depth: {:?}", raw_format.color_depth);
-        println!(
-            "\tDimensions: {}x{} (rendered at width {})",
-            raw_format.width, raw_format.height, raw_format.rendered_width
-        );
+        let mut png_writer = Cursor::new(Vec::new());
 
         // Additionally, some bitmap images have dimensions, but lack any substance.
+        // We retain their header, but they have no PNG representation.
+        // The same applies for types we cannot decode: their pixel data is retained as-is.
+        // Malformed bitmaps are retained likewise. See `RawBitmapData::validate`.
         // TODO(spotlightishere): Is this correct?
-        if raw_format.contents_length == 0 || raw_format.contents.is_empty() {
-            return Ok(None);
+        if raw_format.is_decodable() {
+            let image = decode_pixels(header, &raw_format.contents)?;
+            image.write_to(&mut png_writer, image::ImageFormat::Png)?;
         }
 
-        // TODO(spotlightishere): Is this really how the rendered_width field is used,
-        // or are there fields indicating orientation (landscape/portrait)?
-        let height = raw_format.height;
-        let width: u32 = match raw_format.image_type {
-            RawBitmapType::GrayscaleTwo => raw_format.rendered_width as u32 * 4,
-            RawBitmapType::GrayscaleFour => raw_format.rendered_width as u32 * 2,
-            RawBitmapType::GrayscaleEight => raw_format.rendered_width as u32,
-            _ => raw_format.width,
+        let result = BitmapImage {
+            width: header.width,
+            height: header.height,
+            format_type: header.image_type,
+            rendered_width: header.rendered_width,
+            resource_id: header.resource_id.unwrap_or_default(),
+            contents: png_writer.into_inner(),
+            raw: Some(raw_format),
         };
+        Ok(Some(result))
+    }
 
-        // Now, convert our bitmap data to a PNG representation.
-        let mut png_writer = Cursor::new(Vec::new());
-        match raw_format.image_type {
-            RawBitmapType::GrayscaleTwo => {
-                // We have four pixels in every byte.
-                let gray_contents: Vec<u8> = raw_format
-                    .contents
-                    .into_iter()
-                    .flat_map(|pixel| {
-                        let one: u8 = (pixel & 0x3) * 32;
-                        let two: u8 = ((pixel >> 2) & 0x3) * 32;
-                        let three: u8 = ((pixel >> 4) & 0x3) * 32;
-                        let four: u8 = ((pixel >> 6) & 0x3) * 32;
-
-                        [four, three, two, one]
-                    })
-                    .collect();
-
-                let gray_image = GrayImage::from_raw(width, height, gray_contents)
-                    .expect("should be able to create grayscale image");
-                gray_image.write_to(&mut png_writer, image::ImageFormat::Png)?;
-            }
-            RawBitmapType::GrayscaleFour => {
-                // We have two pixels in every byte.
-                let gray_contents: Vec<u8> = raw_format
-                    .contents
-                    .into_iter()
-                    .flat_map(|pixel| {
-                        let lower: u8 = ((pixel >> 4) & 0xf) * 16;
-                        let upper: u8 = (pixel & 0xf) * 16;
-
-                        [lower, upper]
-                    })
-                    .collect();
-
-                let gray_image = GrayImage::from_raw(width, height, gray_contents)
-                    .expect("should be able to create grayscale image");
-                gray_image.write_to(&mut png_writer, image::ImageFormat::Png)?;
-            }
-            RawBitmapType::GrayscaleEight => {
-                let gray_image = GrayImage::from_raw(width, height, raw_format.contents)
-                    .expect("should be able to create grayscale image");
-                gray_image.write_to(&mut png_writer, image::ImageFormat::Png)?;
-            }
-            RawBitmapType::Rgb565 => {
-                // Parse our raw RGB565 contents by chunking every two bytes.
-                // We can then manipulate each u16.
-                let rgb_contents: Vec<u8> = raw_format
-                    .contents
-                    .chunks_exact(2)
-                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
-                    .flat_map(|pixel| {
-                        let r = ((pixel >> 11 & 0b11111) * (255 / 0b11111)) as u8;
-                        let g = ((pixel >> 5 & 0b111111) * (255 / 0b111111)) as u8;
-                        let b = ((pixel & 0b11111) * (255 / 0b11111)) as u8;
-
-                        [r, g, b]
-                    })
-                    .collect();
-
-                let rgb_image = RgbImage::from_raw(width, height, rgb_contents.clone())
-                    .expect("should be able to create an RGB image");
-
-                rgb_image.write_to(&mut png_writer, image::ImageFormat::Png)?;
-            }
-            RawBitmapType::Argb4444 => {
-                let rgba_contents: Vec<u8> = raw_format
-                    .contents
-                    .chunks_exact(2)
-                    .flat_map(|pixels| {
-                        // This is little-endian, so ARGB is actually the reverse (BGRA).
-                        let b: u8 = (pixels[0] & 0xf) * 16;
-                        let g: u8 = ((pixels[0] >> 4) & 0xf) * 16;
-                        let r: u8 = (pixels[1] & 0xf) * 16;
-                        let a: u8 = ((pixels[1] >> 4) & 0xf) * 16;
-
-                        [r, g, b, a]
-                    })
-                    .collect();
-
-                let rgba_image = RgbaImage::from_raw(width, height, rgba_contents.clone())
-                    .expect("should be able to create an RGBA image");
-
-                rgba_image.write_to(&mut png_writer, image::ImageFormat::Png)?;
-            }
-            RawBitmapType::Argb8888 => {
-                let rgba_contents: Vec<u8> = raw_format
-                    .contents
-                    .chunks_exact(4)
-                    .flat_map(|pixels| {
-                        // This is little-endian, so ARGB is actually the reverse (BGRA).
-                        let b = pixels[0];
-                        let g = pixels[1];
-                        let r = pixels[2];
-                        let a = pixels[3];
+    /// Decodes our PNG contents to RGBA pixels.
+    pub fn pixels(&self) -> Result<RgbaImage, SilverError> {
+        Ok(image::load_from_memory(&self.contents)?.to_rgba8())
+    }
 
-                        [r, g, b, a]
-                    })
-                    .collect();
+    /// Reduces our representation to a resource entry's raw contents.
+    ///
+    /// If our pixels and header fields are unchanged from the raw representation
+    /// we were parsed from, it is returned byte-for-byte. Otherwise, we re-encode.
+    pub fn reduce(self) -> Result<Vec<u8>, SilverError> {
+        self.reduce_with_profile(&DeviceProfile::generic())
+    }
 
-                let rgba_image = RgbaImage::from_raw(width, height, rgba_contents.clone())
-                    .expect("should be able to create an RGBA image");
+    /// Reduces our representation using the rules of the given device profile.
+    pub fn reduce_with_profile(self, profile: &DeviceProfile) -> Result<Vec<u8>, SilverError> {
+        self.reduce_with_options(profile, &QuantizeOptions::default())
+    }
 
-                rgba_image.write_to(&mut png_writer, image::ImageFormat::Png)?;
+    /// Reduces our representation using the rules of the given device profile,
+    /// quantizing changed pixels with the given options if our format requires.
+    pub fn reduce_with_options(
+        self,
+        profile: &DeviceProfile,
+        quantize_options: &QuantizeOptions,
+    ) -> Result<Vec<u8>, SilverError> {
+        if let Some(raw) = &self.raw {
+            if self.is_unchanged(raw)? {
+                return raw.reduce();
             }
-            RawBitmapType::RgbEight => {
-                // Obtain our palette and raw, indexed contents.
-                let (palette, indexed_contents) = separate_palette(raw_format.contents);
+        }
+        self.encode(profile, quantize_options)?.reduce()
+    }
 
-                // Iterate through each chunk and resolve RGBA colors from our palette.
-                let rgba_contents = indexed_contents
-                    .iter()
-                    .flat_map(|index| {
-                        // Our palette is ARGB.
-                        let (r, g, b, a) = palette[*index as usize];
-                        [r, g, b, a]
-                    })
-                    .collect();
+    /// Whether this image still matches the given raw representation.
+    fn is_unchanged(&self, raw: &RawBitmapData) -> Result<bool, SilverError> {
+        let header = &raw.header;
+        if header.image_type != self.format_type
+            || header.rendered_width != self.rendered_width
+            || header.resource_id.unwrap_or_default() != self.resource_id
+        {
+            return Ok(false);
+        }
 
-                // Finally, create our image.
-                let rgba_image = RgbaImage::from_raw(width, height, rgba_contents)
-                    .expect("should be able to create an RGBA image");
+        // Images without a PNG representation are unchanged if they remain without.
+        // This includes unknown types and malformed data, which we can only reuse verbatim.
+        if self.contents.is_empty() || !raw.is_decodable() {
+            return Ok(self.contents.is_empty() && !raw.is_decodable());
+        }
 
-                rgba_image.write_to(&mut png_writer, image::ImageFormat::Png)?;
-            }
-            RawBitmapType::RgbSixteen => {
-                // Obtain our palette and raw, indexed contents.
-                let (palette, indexed_contents) = separate_palette(raw_format.contents);
+        let original = decode_pixels(header, &raw.contents)?.to_rgba8();
+        Ok(original == self.pixels()?)
+    }
 
-                // Iterate through each chunk and resolve RGBA colors from our palette.
-                // As we're 16-bit, map our index from two u8 to one u16.
-                let rgba_contents = indexed_contents
-                    .chunks_exact(2)
-                    .map(|x| u16::from_le_bytes([x[0], x[1]]))
-                    .flat_map(|index| {
-                        // Our palette is ARGB.
-                        let (r, g, b, a) = palette[index as usize];
-                        [r, g, b, a]
-                    })
-                    .collect();
+    /// Encodes our pixels to a new raw representation,
+    /// retaining fields from our original header where possible.
+    pub(super) fn encode(
+        &self,
+        profile: &DeviceProfile,
+        quantize_options: &QuantizeOptions,
+    ) -> Result<RawBitmapData, SilverError> {
+        if self.contents.is_empty() {
+            return Err(SilverError::BitmapEncodingFailure(
+                "bitmap has no pixel data to encode".to_string(),
+            ));
+        }
 
-                // Finally, create our image.
-                let rgba_image = RgbaImage::from_raw(width, height, rgba_contents)
-                    .expect("should be able to create an RGBA image");
+        let pixels = self.pixels()?;
+        let original = self.raw.as_ref();
+
+        // Paletted images can reuse their original palette, if of the same type.
+        // Malformed originals have no palette worth reusing.
+        let is_paletted = self.format_type.max_colors().is_some();
+        let palette = original
+            .filter(|raw| is_paletted && raw.header.image_type == self.format_type)
+            .filter(|raw| raw.is_decodable())
+            .map(|raw| separate_palette(raw.contents.clone()))
+            .transpose()?
+            .map(|(palette, _)| palette);
+        let mut header = match original {
+            Some(raw) => raw.header.clone(),
+            None => RawBitmapHeader {
+                image_type: self.format_type,
+                is_external: 0,
+                rendered_width: self.rendered_width,
+                color_depth: self.format_type.color_depth(),
+                padding_one: 0,
+                padding_two: 0,
+                width: 0,
+                height: 0,
+                // Only some devices have a resource ID within bitmap headers.
+                resource_id: match profile.bitmap_resource_id {
+                    Some(false) => None,
+                    _ => Some(self.resource_id),
+                },
+                contents_length: 0,
+            },
+        };
 
-                rgba_image.write_to(&mut png_writer, image::ImageFormat::Png)?;
-            }
+        // Our row stride is retained, unless our rows have changed length.
+        let is_resized = header.image_type != self.format_type || header.width != pixels.width();
+        if header.image_type != self.format_type {
+            header.image_type = self.format_type;
+            header.color_depth = self.format_type.color_depth();
         }
-
-        let result = BitmapImage {
-            width: raw_format.width,
-            height: raw_format.width,
-            format_type: raw_format.image_type,
-            rendered_width: raw_format.rendered_width,
-            resource_id: raw_format.resource_id,
-            contents: png_writer.into_inner(),
+        header.width = pixels.width();
+        header.height = pixels.height();
+        header.rendered_width = match is_resized {
+            true => header.row_length() as u16,
+            false => self.rendered_width,
         };
-        Ok(Some(result))
-    }
+        header.resource_id = header.resource_id.map(|_| self.resource_id);
 
-    // Reduces our representation to a resource entry's raw contents.
-    pub fn reduce(self) -> Result<Vec<u8>, SilverError> {
-        todo!("reducing is not yet supported")
+        let contents = encode_pixels(&pixels, &header, palette.as_deref(), quantize_options)?;
+        header.contents_length = contents.len() as u32;
+
+        Ok(RawBitmapData { header, contents })
     }
 }
 
-/// An ARGB pixel.
-type ArgbPixel = (u8, u8, u8, u8);
-
-/// Reads the four first bytes of our raw contents
-/// in order to separate the palette from our contents.
-/// It then returns the palette as a tuple of four u8s,
-/// and the raw contents as a simple Vec<u8>.
-fn separate_palette(raw_contents: Vec<u8>) -> (Vec<ArgbPixel>, Vec<u8>) {
-    let palette_length = u32::from_le_bytes([
-        raw_contents[0],
-        raw_contents[1],
-        raw_contents[2],
-        raw_contents[3],
-    ]);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitmap::format::tests::NON_SQUARE_RGB565;
+
+    #[test]
+    fn parses_dimensions() {
+        let bitmap = BitmapImage::parse(NON_SQUARE_RGB565.to_vec())
+            .expect("should parse bitmap")
+            .expect("bitmap should have contents");
+        assert_eq!((bitmap.width, bitmap.height), (3, 2));
+        assert_eq!(bitmap.rendered_width, 6);
+
+        let pixels = bitmap.pixels().unwrap();
+        assert_eq!(pixels.dimensions(), (3, 2));
+        assert_eq!(pixels.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(pixels.get_pixel(2, 0).0, [0, 0, 255, 255]);
+        assert_eq!(pixels.get_pixel(1, 1).0, [0, 0, 0, 255]);
+    }
 
-    // The palette begins immediately after our length, a u32.
-    // It's an array of RGBA8888, so we operate over clusters of four bytes.
-    // However, because this is little endian, we read it as the inverse, ABGR8888.
-    let palette_start = 4;
-    let palette_end = palette_start + (palette_length as usize * 4);
-    let palette = raw_contents[palette_start..palette_end]
-        .chunks_exact(4)
-        .flat_map(|pixels| {
-            let b = pixels[0];
-            let g = pixels[1];
-            let r = pixels[2];
-            let a = pixels[3];
+    #[test]
+    fn reduces_unchanged_bitmap_verbatim() {
+        let bitmap = BitmapImage::parse(NON_SQUARE_RGB565.to_vec())
+            .unwrap()
+            .unwrap();
+        assert_eq!(bitmap.reduce().unwrap(), NON_SQUARE_RGB565);
+    }
 
-            [(r, g, b, a)]
-        })
-        .collect();
+    #[test]
+    fn encodes_over_truncated_palette() {
+        let pixels = RgbaImage::from_pixel(2, 1, image::Rgba([10, 20, 30, 255]));
+        let mut png_writer = Cursor::new(Vec::new());
+        pixels
+            .write_to(&mut png_writer, image::ImageFormat::Png)
+            .unwrap();
+
+        // This palette claims 255 entries, but holds none.
+        let truncated = vec![0xff, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let header = RawBitmapHeader {
+            image_type: RawBitmapType::RgbEight,
+            is_external: 0,
+            rendered_width: 2,
+            color_depth: 8,
+            padding_one: 0,
+            padding_two: 0,
+            width: 2,
+            height: 1,
+            resource_id: Some(0x0dad0001),
+            contents_length: truncated.len() as u32,
+        };
+        let bitmap = BitmapImage {
+            width: 2,
+            height: 1,
+            rendered_width: 2,
+            format_type: RawBitmapType::RgbEight,
+            resource_id: 0x0dad0001,
+            contents: png_writer.into_inner(),
+            raw: Some(RawBitmapData {
+                header,
+                contents: truncated,
+            }),
+        };
 
-    // Finally, separate our raw, indexed contents.
-    let indexed_contents = &raw_contents[palette_end..];
-    (palette, indexed_contents.to_vec())
+        let reduced = bitmap.reduce().expect("should re-encode pixels");
+        let parsed = BitmapImage::parse(reduced).unwrap().unwrap();
+        assert_eq!(parsed.pixels().unwrap(), pixels);
+    }
 }
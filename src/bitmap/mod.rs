@@ -1,5 +1,17 @@
+mod atlas;
+mod convert;
+mod dither;
 mod format;
 mod image;
+mod pixel;
+mod quantize;
+mod theme;
 
-pub use format::RawBitmapType;
+pub use atlas::{Atlas, AtlasSprite};
+pub use convert::ConversionReport;
+pub use dither::Dither;
+pub use format::{BitmapIssue, RawBitmapData, RawBitmapHeader, RawBitmapType};
 pub use image::BitmapImage;
+pub use pixel::{expand_channel, reduce_channel};
+pub use quantize::{QuantizeOptions, Quantizer};
+pub use theme::{Theme, ThemeImage, ThemeReport, ThemeSkip};
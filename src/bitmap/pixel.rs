@@ -0,0 +1,668 @@
+use image::{DynamicImage, GrayImage, RgbImage, RgbaImage};
+
+use crate::{
+    bitmap::{
+        dither::{choose_level, dither, linearize},
+        format::{BitmapIssue, RawBitmapHeader, RawBitmapType},
+        quantize::{quantize, QuantizeOptions},
+    },
+    silver_error::SilverError,
+};
+
+/// An ARGB pixel.
+pub(super) type ArgbPixel = (u8, u8, u8, u8);
+
+/// Expands a channel of the given bit depth to eight bits.
+///
+/// Bits are replicated into the lower bits (i.e. `0b10110` becomes `0b10110101`),
+/// so that the minimum and maximum values map to 0 and 255 respectively.
+///
+/// # Panics
+///
+/// Panics if `bits` is not between 1 and 8.
+pub fn expand_channel(value: u8, bits: u32) -> u8 {
+    assert!((1..=8).contains(&bits), "channel depth of {bits} bits");
+    let value = value as u32 & ((1 << bits) - 1);
+    let mut expanded = 0u32;
+    let mut filled = 0;
+    while filled < 8 {
+        expanded = (expanded << bits) | value;
+        filled += bits;
+    }
+    (expanded >> (filled - 8)) as u8
+}
+
+/// Reduces an eight-bit channel to the given bit depth, rounding to the nearest value.
+///
+/// This is the exact inverse of `expand_channel`.
+///
+/// # Panics
+///
+/// Panics if `bits` is not between 1 and 8.
+pub fn reduce_channel(value: u8, bits: u32) -> u8 {
+    assert!((1..=8).contains(&bits), "channel depth of {bits} bits");
+    let maximum = (1u32 << bits) - 1;
+    ((value as u32 * maximum + 127) / 255) as u8
+}
+
+/// Whether the given type holds a palette preceding its pixel data.
+fn is_paletted(image_type: RawBitmapType) -> bool {
+    matches!(
+        image_type,
+        RawBitmapType::RgbEight | RawBitmapType::RgbSixteen
+    )
+}
+
+/// Checks that the given contents are of the size expected by the given header,
+/// and that any palette indices are in range.
+pub(super) fn pixel_issues(header: &RawBitmapHeader, contents: &[u8]) -> Vec<BitmapIssue> {
+    let mut issues = Vec::new();
+
+    // Paletted types have their palette preceding their rows.
+    let mut palette_size = 0;
+    let mut palette_length = 0;
+    if is_paletted(header.image_type) {
+        palette_length = match contents.get(..4) {
+            Some(length) => u32::from_le_bytes([length[0], length[1], length[2], length[3]]),
+            None => u32::MAX,
+        };
+        palette_size = 4 + palette_length as usize * 4;
+        if palette_size > contents.len() {
+            issues.push(BitmapIssue::TruncatedPalette {
+                palette_length,
+                available: contents.len(),
+            });
+            return issues;
+        }
+    }
+
+    // The final row may lack its padding.
+    let pixel_data = &contents[palette_size..];
+    let height = header.height as usize;
+    let row_stride = header.row_stride() as usize;
+    let full_length = row_stride * height;
+    let minimum_length = match height {
+        0 => 0,
+        _ => row_stride * (height - 1) + header.row_length() as usize,
+    };
+    if pixel_data.len() < minimum_length {
+        issues.push(BitmapIssue::TruncatedPixels {
+            expected: full_length,
+            actual: pixel_data.len(),
+        });
+        return issues;
+    }
+    if pixel_data.len() != minimum_length && pixel_data.len() != full_length {
+        issues.push(BitmapIssue::UnexpectedLength {
+            expected: full_length,
+            actual: pixel_data.len(),
+        });
+    }
+
+    if is_paletted(header.image_type) {
+        let index_size = header.image_type.color_depth() as usize / 8;
+        let out_of_range = split_rows(header, pixel_data)
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chunks_exact(index_size)
+                    .enumerate()
+                    .map(move |(x, index)| (x, y, index))
+            })
+            .find_map(|(x, y, index)| {
+                let index = match index_size {
+                    1 => index[0] as u32,
+                    _ => u16::from_le_bytes([index[0], index[1]]) as u32,
+                };
+                (index >= palette_length).then_some(BitmapIssue::PaletteIndexOutOfRange {
+                    x: x as u32,
+                    y: y as u32,
+                    index,
+                    palette_length,
+                })
+            });
+        issues.extend(out_of_range);
+    }
+    issues
+}
+
+/// Splits pixel data into its rows, per the given header's stride.
+/// Row padding is excluded.
+fn split_rows<'a>(header: &RawBitmapHeader, pixel_data: &'a [u8]) -> Vec<&'a [u8]> {
+    let row_length = header.row_length() as usize;
+    let row_stride = header.row_stride() as usize;
+    (0..header.height as usize)
+        .map(|row| {
+            let start = (row * row_stride).min(pixel_data.len());
+            let end = (start + row_length).min(pixel_data.len());
+            &pixel_data[start..end]
+        })
+        .collect()
+}
+
+/// Joins rows of pixel data, padding each with zeroes to the given header's stride.
+fn join_rows(header: &RawBitmapHeader, rows: impl Iterator<Item = Vec<u8>>) -> Vec<u8> {
+    let row_stride = header.row_stride() as usize;
+    let mut pixel_data = Vec::with_capacity(row_stride * header.height as usize);
+    for mut row in rows {
+        row.resize(row_stride, 0);
+        pixel_data.extend(row);
+    }
+    pixel_data
+}
+
+/// Decodes raw pixel data to an image, per the given header.
+/// Unknown types cannot be decoded.
+pub(super) fn decode_pixels(
+    header: &RawBitmapHeader,
+    contents: &[u8],
+) -> Result<DynamicImage, SilverError> {
+    let width = header.width;
+    let height = header.height;
+
+    // Ensure we have enough data for every row, and that palette indices are in range.
+    // Afterwards, every row holds exactly `row_length` bytes, which
+    // is a multiple of our pixel size: `chunks_exact` has no remainders.
+    if let Some(issue) = pixel_issues(header, contents)
+        .into_iter()
+        .find(BitmapIssue::prevents_decoding)
+    {
+        return Err(SilverError::MalformedBitmap(issue));
+    }
+
+    // Paletted types have their palette preceding their rows.
+    let (palette, pixel_data) = match is_paletted(header.image_type) {
+        true => separate_palette(contents.to_vec())?,
+        false => (Vec::new(), contents.to_vec()),
+    };
+    let rows = split_rows(header, &pixel_data);
+
+    let image = match header.image_type {
+        RawBitmapType::GrayscaleTwo | RawBitmapType::GrayscaleFour => {
+            // We have several pixels in every byte.
+            // The first pixel resides within the most significant bits.
+            let bits = header.image_type.color_depth() as u32;
+            let pixels_per_byte = 8 / bits;
+            let gray_contents: Vec<u8> = rows
+                .iter()
+                .flat_map(|row| {
+                    row.iter()
+                        .flat_map(|pixel| {
+                            (0..pixels_per_byte).map(move |index| {
+                                let shift = 8 - bits * (index + 1);
+                                expand_channel(pixel >> shift, bits)
+                            })
+                        })
+                        .take(width as usize)
+                })
+                .collect();
+
+            let gray_image = GrayImage::from_raw(width, height, gray_contents)
+                .ok_or(SilverError::InvalidBitmap)?;
+            DynamicImage::ImageLuma8(gray_image)
+        }
+        RawBitmapType::GrayscaleEight => {
+            let gray_image = GrayImage::from_raw(width, height, rows.concat())
+                .ok_or(SilverError::InvalidBitmap)?;
+            DynamicImage::ImageLuma8(gray_image)
+        }
+        RawBitmapType::Rgb565 => {
+            // Parse our raw RGB565 contents by chunking every two bytes.
+            // We can then manipulate each u16.
+            let rgb_contents: Vec<u8> = rows
+                .concat()
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .flat_map(|pixel| {
+                    let r = expand_channel((pixel >> 11) as u8, 5);
+                    let g = expand_channel((pixel >> 5) as u8, 6);
+                    let b = expand_channel(pixel as u8, 5);
+
+                    [r, g, b]
+                })
+                .collect();
+
+            let rgb_image = RgbImage::from_raw(width, height, rgb_contents)
+                .ok_or(SilverError::InvalidBitmap)?;
+            DynamicImage::ImageRgb8(rgb_image)
+        }
+        RawBitmapType::Argb4444 => {
+            let rgba_contents: Vec<u8> = rows
+                .concat()
+                .chunks_exact(2)
+                .flat_map(|pixels| {
+                    // This is little-endian, so ARGB is actually the reverse (BGRA).
+                    let b = expand_channel(pixels[0], 4);
+                    let g = expand_channel(pixels[0] >> 4, 4);
+                    let r = expand_channel(pixels[1], 4);
+                    let a = expand_channel(pixels[1] >> 4, 4);
+
+                    [r, g, b, a]
+                })
+                .collect();
+
+            let rgba_image = RgbaImage::from_raw(width, height, rgba_contents)
+                .ok_or(SilverError::InvalidBitmap)?;
+            DynamicImage::ImageRgba8(rgba_image)
+        }
+        RawBitmapType::Argb8888 => {
+            let rgba_contents: Vec<u8> = rows
+                .concat()
+                .chunks_exact(4)
+                .flat_map(|pixels| {
+                    // This is little-endian, so ARGB is actually the reverse (BGRA).
+                    let b = pixels[0];
+                    let g = pixels[1];
+                    let r = pixels[2];
+                    let a = pixels[3];
+
+                    [r, g, b, a]
+                })
+                .collect();
+
+            let rgba_image = RgbaImage::from_raw(width, height, rgba_contents)
+                .ok_or(SilverError::InvalidBitmap)?;
+            DynamicImage::ImageRgba8(rgba_image)
+        }
+        RawBitmapType::RgbEight => {
+            // Iterate through each index and resolve RGBA colors from our palette.
+            let rgba_contents = rows
+                .concat()
+                .iter()
+                .flat_map(|index| {
+                    // Our palette is ARGB.
+                    let (r, g, b, a) = palette[*index as usize];
+                    [r, g, b, a]
+                })
+                .collect();
+
+            // Finally, create our image.
+            let rgba_image = RgbaImage::from_raw(width, height, rgba_contents)
+                .ok_or(SilverError::InvalidBitmap)?;
+            DynamicImage::ImageRgba8(rgba_image)
+        }
+        RawBitmapType::RgbSixteen => {
+            // Iterate through each chunk and resolve RGBA colors from our palette.
+            // As we're 16-bit, map our index from two u8 to one u16.
+            let rgba_contents = rows
+                .concat()
+                .chunks_exact(2)
+                .map(|x| u16::from_le_bytes([x[0], x[1]]))
+                .flat_map(|index| {
+                    // Our palette is ARGB.
+                    let (r, g, b, a) = palette[index as usize];
+                    [r, g, b, a]
+                })
+                .collect();
+
+            // Finally, create our image.
+            let rgba_image = RgbaImage::from_raw(width, height, rgba_contents)
+                .ok_or(SilverError::InvalidBitmap)?;
+            DynamicImage::ImageRgba8(rgba_image)
+        }
+        RawBitmapType::Unknown(_) => return Err(SilverError::UnknownBitmap),
+    };
+    Ok(image)
+}
+
+/// Encodes an image to raw pixel data per the given header.
+/// This is the inverse of `decode_pixels`.
+///
+/// The header's dimensions must match that of the image.
+/// Paletted types are quantized, possibly reusing the given original palette.
+pub(super) fn encode_pixels(
+    pixels: &RgbaImage,
+    header: &RawBitmapHeader,
+    original_palette: Option<&[ArgbPixel]>,
+    quantize_options: &QuantizeOptions,
+) -> Result<Vec<u8>, SilverError> {
+    quantize_options.validate()?;
+    let image_type = header.image_type;
+    let contents = match image_type {
+        RawBitmapType::GrayscaleTwo | RawBitmapType::GrayscaleFour => {
+            let gray_image = DynamicImage::ImageRgba8(pixels.clone()).to_luma8();
+            let bits = image_type.color_depth() as u32;
+            let pixels_per_byte = 8 / bits as usize;
+
+            // Values are dithered to levels in linear light, if a gamma is given.
+            let gamma = quantize_options.gamma;
+            let levels: Vec<f32> = (0..1u8 << bits)
+                .map(|level| linearize(expand_channel(level, bits), gamma))
+                .collect();
+            let values: Vec<Vec<[f32; 1]>> = gray_image
+                .rows()
+                .map(|row| row.map(|luma| [linearize(luma.0[0], gamma)]).collect())
+                .collect();
+            let levels = dither(&values, quantize_options.dither, |value, threshold| {
+                let (level, chosen) = choose_level(&levels, value[0], threshold);
+                (level as u8, [chosen])
+            });
+
+            // The first pixel resides within the most significant bits.
+            let width = gray_image.width() as usize;
+            let rows = (0..gray_image.height() as usize).map(|y| {
+                levels[y * width..(y + 1) * width]
+                    .chunks(pixels_per_byte)
+                    .map(|chunk| {
+                        let mut current_byte = 0u8;
+                        for (index, level) in chunk.iter().enumerate() {
+                            let shift = 8 - bits * (index as u32 + 1);
+                            current_byte |= level << shift;
+                        }
+                        current_byte
+                    })
+                    .collect()
+            });
+            join_rows(header, rows)
+        }
+        RawBitmapType::GrayscaleEight => {
+            let gray_image = DynamicImage::ImageRgba8(pixels.clone()).to_luma8();
+            let rows = gray_image
+                .rows()
+                .map(|row| row.map(|luma| luma.0[0]).collect());
+            join_rows(header, rows)
+        }
+        RawBitmapType::Rgb565 => {
+            let rows = pixels.rows().map(|row| {
+                row.flat_map(|pixel| {
+                    let [r, g, b, _] = pixel.0;
+                    let r = reduce_channel(r, 5) as u16;
+                    let g = reduce_channel(g, 6) as u16;
+                    let b = reduce_channel(b, 5) as u16;
+                    ((r << 11) | (g << 5) | b).to_le_bytes()
+                })
+                .collect()
+            });
+            join_rows(header, rows)
+        }
+        RawBitmapType::Argb4444 => {
+            let rows = pixels.rows().map(|row| {
+                row.flat_map(|pixel| {
+                    let [r, g, b, a] = pixel.0.map(|channel| reduce_channel(channel, 4));
+                    // This is little-endian, so ARGB is actually the reverse (BGRA).
+                    [(g << 4) | b, (a << 4) | r]
+                })
+                .collect()
+            });
+            join_rows(header, rows)
+        }
+        RawBitmapType::Argb8888 => {
+            let rows = pixels.rows().map(|row| {
+                row.flat_map(|pixel| {
+                    let [r, g, b, a] = pixel.0;
+                    [b, g, r, a]
+                })
+                .collect()
+            });
+            join_rows(header, rows)
+        }
+        RawBitmapType::RgbEight | RawBitmapType::RgbSixteen => {
+            let max_colors = image_type.max_colors().unwrap_or_default();
+            let (palette, indices) =
+                quantize(pixels, max_colors, original_palette, quantize_options);
+
+            let rows = indices.chunks(pixels.width().max(1) as usize).map(|row| {
+                row.iter()
+                    .flat_map(|index| match image_type {
+                        RawBitmapType::RgbEight => vec![*index as u8],
+                        _ => (*index as u16).to_le_bytes().to_vec(),
+                    })
+                    .collect()
+            });
+
+            let mut contents = join_palette(&palette);
+            contents.extend(join_rows(header, rows));
+            contents
+        }
+        RawBitmapType::Unknown(_) => return Err(SilverError::UnknownBitmap),
+    };
+    Ok(contents)
+}
+
+/// Reads the four first bytes of our raw contents
+/// in order to separate the palette from our contents.
+/// It then returns the palette as a tuple of four u8s,
+/// and the raw contents as a simple Vec<u8>.
+///
+/// Contents too short to hold their palette are malformed.
+pub(super) fn separate_palette(
+    raw_contents: Vec<u8>,
+) -> Result<(Vec<ArgbPixel>, Vec<u8>), SilverError> {
+    let palette_length = match raw_contents.get(..4) {
+        Some(length) => u32::from_le_bytes([length[0], length[1], length[2], length[3]]),
+        None => u32::MAX,
+    };
+
+    // The palette begins immediately after our length, a u32.
+    // It's an array of RGBA8888, so we operate over clusters of four bytes.
+    // However, because this is little endian, we read it as the inverse, ABGR8888.
+    let palette_start = 4;
+    let palette_end = (palette_length as usize)
+        .checked_mul(4)
+        .and_then(|palette_size| palette_size.checked_add(palette_start))
+        .filter(|palette_end| *palette_end <= raw_contents.len())
+        .ok_or(SilverError::MalformedBitmap(
+            BitmapIssue::TruncatedPalette {
+                palette_length,
+                available: raw_contents.len(),
+            },
+        ))?;
+    let palette = raw_contents[palette_start..palette_end]
+        .chunks_exact(4)
+        .flat_map(|pixels| {
+            let b = pixels[0];
+            let g = pixels[1];
+            let r = pixels[2];
+            let a = pixels[3];
+
+            [(r, g, b, a)]
+        })
+        .collect();
+
+    // Finally, separate our raw, indexed contents.
+    let indexed_contents = &raw_contents[palette_end..];
+    Ok((palette, indexed_contents.to_vec()))
+}
+
+/// Writes a palette in the layout `separate_palette` expects:
+/// its length as a u32, followed by every color as BGRA.
+fn join_palette(palette: &[ArgbPixel]) -> Vec<u8> {
+    let mut contents = (palette.len() as u32).to_le_bytes().to_vec();
+    for (r, g, b, a) in palette {
+        contents.extend([*b, *g, *r, *a]);
+    }
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use proptest::{collection::vec, prelude::*};
+
+    use super::*;
+
+    const WIDTH: u32 = 8;
+    const HEIGHT: u32 = 4;
+
+    /// A header for an unpadded `WIDTH` by `HEIGHT` image of the given type.
+    fn header(image_type: RawBitmapType) -> RawBitmapHeader {
+        RawBitmapHeader {
+            image_type,
+            is_external: 0,
+            rendered_width: 0,
+            color_depth: image_type.color_depth(),
+            padding_one: 0,
+            padding_two: 0,
+            width: WIDTH,
+            height: HEIGHT,
+            resource_id: None,
+            contents_length: 0,
+        }
+    }
+
+    /// Decodes and re-encodes raw contents, as if a PNG were extracted and recreated.
+    fn round_trip(image_type: RawBitmapType, contents: &[u8]) -> Vec<u8> {
+        let header = header(image_type);
+        let pixels = decode_pixels(&header, contents)
+            .expect("should be able to decode pixels")
+            .to_rgba8();
+        let original_palette = match image_type {
+            RawBitmapType::RgbEight | RawBitmapType::RgbSixteen => {
+                Some(separate_palette(contents.to_vec()).unwrap().0)
+            }
+            _ => None,
+        };
+        encode_pixels(
+            &pixels,
+            &header,
+            original_palette.as_deref(),
+            &QuantizeOptions::default(),
+        )
+        .expect("should be able to encode pixels")
+    }
+
+    /// Raw contents of a paletted image with unique palette entries.
+    fn paletted_contents(palette: HashSet<u32>, indices: Vec<usize>, index_size: usize) -> Vec<u8> {
+        let palette: Vec<u32> = palette.into_iter().collect();
+        let mut contents = (palette.len() as u32).to_le_bytes().to_vec();
+        for color in &palette {
+            contents.extend(color.to_le_bytes());
+        }
+        for index in indices {
+            let index = (index % palette.len()) as u16;
+            contents.extend(&index.to_le_bytes()[..index_size]);
+        }
+        contents
+    }
+
+    #[test]
+    fn channels_expand_to_full_range() {
+        for bits in [2, 4, 5, 6] {
+            let maximum = (1u8 << bits) - 1;
+            assert_eq!(expand_channel(0, bits), 0);
+            assert_eq!(expand_channel(maximum, bits), 255);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn channels_require_bits() {
+        expand_channel(1, 0);
+    }
+
+    #[test]
+    fn channels_reduce_inversely() {
+        for bits in 1..=8 {
+            for value in 0..(1u16 << bits) {
+                let value = value as u8;
+                assert_eq!(reduce_channel(expand_channel(value, bits), bits), value);
+            }
+        }
+    }
+
+    #[test]
+    fn padded_rows_are_skipped() {
+        // Three 2-bit pixels per row, padded to a stride of two bytes.
+        let header = RawBitmapHeader {
+            width: 3,
+            height: 2,
+            rendered_width: 2,
+            ..header(RawBitmapType::GrayscaleTwo)
+        };
+        let contents = [0b11_01_10_00, 0x00, 0b00_10_01_00, 0x00];
+
+        let pixels = decode_pixels(&header, &contents).unwrap().to_luma8();
+        assert_eq!(pixels.dimensions(), (3, 2));
+        assert_eq!(pixels.into_raw(), [255, 85, 170, 0, 170, 85]);
+
+        let pixels = decode_pixels(&header, &contents).unwrap().to_rgba8();
+        let encoded = encode_pixels(&pixels, &header, None, &QuantizeOptions::default())
+            .expect("should be able to encode pixels");
+        assert_eq!(encoded, contents);
+    }
+
+    #[test]
+    fn malformed_pixels_are_diagnosed() {
+        // One row short of our height.
+        let header = header(RawBitmapType::Rgb565);
+        let contents = vec![0; (WIDTH * (HEIGHT - 1) * 2) as usize];
+        let issues = pixel_issues(&header, &contents);
+        assert_eq!(
+            issues,
+            [BitmapIssue::TruncatedPixels {
+                expected: (WIDTH * HEIGHT * 2) as usize,
+                actual: contents.len(),
+            }]
+        );
+        assert!(matches!(
+            decode_pixels(&header, &contents),
+            Err(SilverError::MalformedBitmap(_))
+        ));
+
+        // A single-color palette, with our final pixel referencing a second color.
+        let header = self::header(RawBitmapType::RgbEight);
+        let mut contents = paletted_contents(HashSet::from([0xff00ff00]), vec![0; 32], 1);
+        *contents.last_mut().unwrap() = 1;
+        assert_eq!(
+            pixel_issues(&header, &contents),
+            [BitmapIssue::PaletteIndexOutOfRange {
+                x: WIDTH - 1,
+                y: HEIGHT - 1,
+                index: 1,
+                palette_length: 1,
+            }]
+        );
+        assert!(decode_pixels(&header, &contents).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn grayscale_two_round_trips(contents in vec(any::<u8>(), (WIDTH * HEIGHT / 4) as usize)) {
+            prop_assert_eq!(round_trip(RawBitmapType::GrayscaleTwo, &contents), contents);
+        }
+
+        #[test]
+        fn grayscale_four_round_trips(contents in vec(any::<u8>(), (WIDTH * HEIGHT / 2) as usize)) {
+            prop_assert_eq!(round_trip(RawBitmapType::GrayscaleFour, &contents), contents);
+        }
+
+        #[test]
+        fn grayscale_eight_round_trips(contents in vec(any::<u8>(), (WIDTH * HEIGHT) as usize)) {
+            prop_assert_eq!(round_trip(RawBitmapType::GrayscaleEight, &contents), contents);
+        }
+
+        #[test]
+        fn rgb565_round_trips(contents in vec(any::<u8>(), (WIDTH * HEIGHT * 2) as usize)) {
+            prop_assert_eq!(round_trip(RawBitmapType::Rgb565, &contents), contents);
+        }
+
+        #[test]
+        fn argb4444_round_trips(contents in vec(any::<u8>(), (WIDTH * HEIGHT * 2) as usize)) {
+            prop_assert_eq!(round_trip(RawBitmapType::Argb4444, &contents), contents);
+        }
+
+        #[test]
+        fn argb8888_round_trips(contents in vec(any::<u8>(), (WIDTH * HEIGHT * 4) as usize)) {
+            prop_assert_eq!(round_trip(RawBitmapType::Argb8888, &contents), contents);
+        }
+
+        #[test]
+        fn rgb_eight_round_trips(
+            palette in prop::collection::hash_set(any::<u32>(), 1..=256),
+            indices in vec(any::<usize>(), (WIDTH * HEIGHT) as usize),
+        ) {
+            let contents = paletted_contents(palette, indices, 1);
+            prop_assert_eq!(round_trip(RawBitmapType::RgbEight, &contents), contents);
+        }
+
+        #[test]
+        fn rgb_sixteen_round_trips(
+            palette in prop::collection::hash_set(any::<u32>(), 1..=512),
+            indices in vec(any::<usize>(), (WIDTH * HEIGHT) as usize),
+        ) {
+            let contents = paletted_contents(palette, indices, 2);
+            prop_assert_eq!(round_trip(RawBitmapType::RgbSixteen, &contents), contents);
+        }
+    }
+}
@@ -0,0 +1,442 @@
+use std::{
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitmap::{
+        dither::{dither, Dither},
+        pixel::ArgbPixel,
+    },
+    silver_error::SilverError,
+};
+
+/// Algorithms for choosing a palette when an image has more colors than permitted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Quantizer {
+    /// Recursively splits the color space at the median of its widest channel,
+    /// averaging every resulting box. Best for photographic imagery.
+    #[default]
+    MedianCut,
+    /// Keeps the most frequently used colors as-is.
+    /// Best for flat imagery with a handful of dominant colors.
+    Popularity,
+}
+
+impl Quantizer {
+    /// All available quantizers.
+    pub const ALL: [Quantizer; 2] = [Quantizer::MedianCut, Quantizer::Popularity];
+
+    /// The name of this quantizer, as accepted by `Quantizer::from_str`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quantizer::MedianCut => "median-cut",
+            Quantizer::Popularity => "popularity",
+        }
+    }
+}
+
+impl FromStr for Quantizer {
+    type Err = SilverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Quantizer::ALL
+            .into_iter()
+            .find(|quantizer| quantizer.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| SilverError::UnknownQuantizer(s.to_string()))
+    }
+}
+
+impl fmt::Display for Quantizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Options for encoding true-color images to paletted and low-bit-depth grayscale formats.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct QuantizeOptions {
+    /// The algorithm used to choose a palette.
+    pub quantizer: Quantizer,
+    /// The method used to reduce pixels to palette colors or grayscale levels.
+    pub dither: Dither,
+    /// The gamma grayscale values are encoded with, if any.
+    ///
+    /// When present, grayscale images are dithered in linear light, so that
+    /// dithered areas retain their perceived brightness. sRGB imagery is roughly 2.2.
+    pub gamma: Option<f32>,
+    /// Whether to map colors to the original resource's palette,
+    /// even if it lacks some colors present within the image.
+    ///
+    /// Original palettes holding every color are always reused, regardless.
+    pub reuse_palette: bool,
+}
+
+impl QuantizeOptions {
+    /// Ensures these options are usable, i.e. that any gamma is finite and positive.
+    pub fn validate(&self) -> Result<(), SilverError> {
+        match self.gamma {
+            Some(gamma) if !gamma.is_finite() || gamma <= 0.0 => {
+                Err(SilverError::InvalidQuantizeOptions(format!(
+                    "gamma {gamma} must be finite and positive"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Quantizes an image to a palette of at most `max_colors` entries,
+/// returning the palette and the palette index of every pixel.
+///
+/// Images with few enough colors are never lossy.
+pub(crate) fn quantize(
+    pixels: &RgbaImage,
+    max_colors: usize,
+    original_palette: Option<&[ArgbPixel]>,
+    options: &QuantizeOptions,
+) -> (Vec<ArgbPixel>, Vec<usize>) {
+    let mut histogram: HashMap<ArgbPixel, u32> = HashMap::new();
+    for pixel in pixels.pixels() {
+        let [r, g, b, a] = pixel.0;
+        *histogram.entry((r, g, b, a)).or_default() += 1;
+    }
+
+    if let Some(original_palette) = original_palette {
+        let original_colors: HashSet<&ArgbPixel> = original_palette.iter().collect();
+        let is_complete = histogram
+            .keys()
+            .all(|color| original_colors.contains(color));
+        if is_complete || options.reuse_palette {
+            // Complete palettes need no dithering, which would only introduce noise.
+            let method = match is_complete {
+                true => Dither::Threshold,
+                false => options.dither,
+            };
+            let indices = map_pixels(pixels, original_palette, method);
+            return (original_palette.to_vec(), indices);
+        }
+    }
+
+    // Order colors by prevalence, so that common colors receive low indices.
+    let mut colors: Vec<(ArgbPixel, u32)> = histogram.into_iter().collect();
+    colors.sort_by(|first, second| second.1.cmp(&first.1).then(first.0.cmp(&second.0)));
+
+    let is_complete = colors.len() <= max_colors;
+    let palette: Vec<ArgbPixel> = if is_complete {
+        colors.iter().map(|(color, _)| *color).collect()
+    } else {
+        match options.quantizer {
+            Quantizer::MedianCut => median_cut(colors, max_colors),
+            Quantizer::Popularity => colors
+                .iter()
+                .take(max_colors)
+                .map(|(color, _)| *color)
+                .collect(),
+        }
+    };
+
+    let method = match is_complete {
+        true => Dither::Threshold,
+        false => options.dither,
+    };
+    let indices = map_pixels(pixels, &palette, method);
+    (palette, indices)
+}
+
+fn channels(color: ArgbPixel) -> [u8; 4] {
+    let (r, g, b, a) = color;
+    [r, g, b, a]
+}
+
+/// A box of colors within median cut, alongside their pixel counts.
+type ColorBox = Vec<(ArgbPixel, u32)>;
+
+/// Determines the channel with the widest range of values,
+/// and a priority for splitting this box weighing that range by its pixel count.
+fn split_priority(color_box: &ColorBox) -> (usize, u64) {
+    let mut minimum = [u8::MAX; 4];
+    let mut maximum = [u8::MIN; 4];
+    for (color, _) in color_box {
+        for (channel, value) in channels(*color).into_iter().enumerate() {
+            minimum[channel] = minimum[channel].min(value);
+            maximum[channel] = maximum[channel].max(value);
+        }
+    }
+
+    let (channel, range) = (0..4)
+        .map(|channel| (channel, (maximum[channel] - minimum[channel]) as u64))
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or_default();
+    let population: u64 = color_box.iter().map(|(_, count)| *count as u64).sum();
+    (channel, range * population)
+}
+
+/// Chooses a palette via median cut.
+fn median_cut(colors: ColorBox, max_colors: usize) -> Vec<ArgbPixel> {
+    let mut boxes: Vec<ColorBox> = Vec::new();
+    let mut queue: BinaryHeap<(u64, usize)> = BinaryHeap::new();
+
+    // Boxes with a single color cannot be split further.
+    fn add_box(
+        boxes: &mut Vec<ColorBox>,
+        queue: &mut BinaryHeap<(u64, usize)>,
+        index: usize,
+        color_box: ColorBox,
+    ) {
+        if color_box.len() > 1 {
+            queue.push((split_priority(&color_box).1, index));
+        }
+        if index == boxes.len() {
+            boxes.push(color_box);
+        } else {
+            boxes[index] = color_box;
+        }
+    }
+    add_box(&mut boxes, &mut queue, 0, colors);
+
+    while boxes.len() < max_colors {
+        let Some((_, index)) = queue.pop() else {
+            break;
+        };
+
+        let mut lower = std::mem::take(&mut boxes[index]);
+        let (channel, _) = split_priority(&lower);
+        lower.sort_by_key(|(color, _)| channels(*color)[channel]);
+
+        // Split at the median pixel, ensuring both halves hold at least one color.
+        let population: u64 = lower.iter().map(|(_, count)| *count as u64).sum();
+        let mut seen = 0;
+        let mut split = lower.len() / 2;
+        for (position, (_, count)) in lower.iter().enumerate() {
+            seen += *count as u64;
+            if seen * 2 >= population {
+                split = position + 1;
+                break;
+            }
+        }
+        let upper = lower.split_off(split.clamp(1, lower.len() - 1));
+
+        add_box(&mut boxes, &mut queue, index, lower);
+        let upper_index = boxes.len();
+        add_box(&mut boxes, &mut queue, upper_index, upper);
+    }
+
+    boxes.iter().map(average_color).collect()
+}
+
+/// The average color within a box, weighted by pixel count.
+fn average_color(color_box: &ColorBox) -> ArgbPixel {
+    let mut totals = [0u64; 4];
+    let mut population = 0u64;
+    for (color, count) in color_box {
+        for (channel, value) in channels(*color).into_iter().enumerate() {
+            totals[channel] += value as u64 * *count as u64;
+        }
+        population += *count as u64;
+    }
+
+    let [r, g, b, a] = totals.map(|total| ((total + population / 2) / population.max(1)) as u8);
+    (r, g, b, a)
+}
+
+/// The squared distance between two colors.
+fn distance(first: [u8; 4], second: [u8; 4]) -> u32 {
+    first
+        .into_iter()
+        .zip(second)
+        .map(|(first, second)| (first as i32 - second as i32).pow(2) as u32)
+        .sum()
+}
+
+/// A k-d tree over palette colors, so that the nearest color within
+/// palettes of up to 65536 entries is found without scanning each.
+struct PaletteTree<'a> {
+    palette: &'a [ArgbPixel],
+    /// Pairs of palette index and split channel. Every subtree occupies a slice,
+    /// with its root at the middle and lesser colors before it.
+    nodes: Vec<(usize, usize)>,
+}
+
+impl<'a> PaletteTree<'a> {
+    fn new(palette: &'a [ArgbPixel]) -> Self {
+        let mut nodes: Vec<(usize, usize)> = (0..palette.len()).map(|index| (index, 0)).collect();
+        Self::build(palette, &mut nodes);
+        PaletteTree { palette, nodes }
+    }
+
+    fn build(palette: &[ArgbPixel], nodes: &mut [(usize, usize)]) {
+        if nodes.is_empty() {
+            return;
+        }
+
+        // Split along the channel with the widest range.
+        let channel = (0..4)
+            .max_by_key(|channel| {
+                let values = nodes
+                    .iter()
+                    .map(|(index, _)| channels(palette[*index])[*channel]);
+                values.clone().max().unwrap_or(0) - values.min().unwrap_or(0)
+            })
+            .unwrap_or(0);
+        let middle = nodes.len() / 2;
+        nodes.select_nth_unstable_by_key(middle, |(index, _)| channels(palette[*index])[channel]);
+        nodes[middle].1 = channel;
+
+        let (lower, upper) = nodes.split_at_mut(middle);
+        Self::build(palette, lower);
+        Self::build(palette, &mut upper[1..]);
+    }
+
+    /// The index of the closest color within the palette.
+    /// Ties resolve to the lowest index.
+    fn nearest(&self, color: [u8; 4]) -> usize {
+        let mut nearest = (0, u32::MAX);
+        self.search(&self.nodes, color, &mut nearest);
+        nearest.0
+    }
+
+    fn search(&self, nodes: &[(usize, usize)], color: [u8; 4], nearest: &mut (usize, u32)) {
+        if nodes.is_empty() {
+            return;
+        }
+
+        let middle = nodes.len() / 2;
+        let (index, channel) = nodes[middle];
+        let entry = channels(self.palette[index]);
+        let entry_distance = distance(entry, color);
+        if (entry_distance, index) < (nearest.1, nearest.0) {
+            *nearest = (index, entry_distance);
+        }
+
+        let offset = color[channel] as i32 - entry[channel] as i32;
+        let (lower, upper) = (&nodes[..middle], &nodes[middle + 1..]);
+        let (near, far) = match offset < 0 {
+            true => (lower, upper),
+            false => (upper, lower),
+        };
+        self.search(near, color, nearest);
+        // Colors across the split are at least as distant as the split itself.
+        if offset.pow(2) as u32 <= nearest.1 {
+            self.search(far, color, nearest);
+        }
+    }
+}
+
+/// Maps every pixel to the index of its closest palette color.
+fn map_pixels(pixels: &RgbaImage, palette: &[ArgbPixel], method: Dither) -> Vec<usize> {
+    // Colors present within the palette need not be searched for.
+    let mut cache: HashMap<[u8; 4], usize> = HashMap::new();
+    for (index, color) in palette.iter().enumerate().rev() {
+        cache.insert(channels(*color), index);
+    }
+    let tree = PaletteTree::new(palette);
+    let mut lookup = |color: [u8; 4]| *cache.entry(color).or_insert_with(|| tree.nearest(color));
+
+    if method == Dither::Threshold {
+        return pixels.pixels().map(|pixel| lookup(pixel.0)).collect();
+    }
+
+    // Only color channels are dithered. Alpha is carried through as-is, so that edges
+    // remain crisp and translucency never diffuses into neighbouring pixels.
+    // Ordered dithering offsets color by up to the typical distance between palette colors.
+    let spread = 255.0 / (palette.len() as f32).cbrt();
+    let rows: Vec<Vec<[f32; 3]>> = pixels
+        .rows()
+        .map(|row| {
+            row.map(|pixel| [pixel.0[0], pixel.0[1], pixel.0[2]].map(|value| value as f32))
+                .collect()
+        })
+        .collect();
+    // Pixels are chosen in order, row by row.
+    let mut alphas = pixels.pixels().map(|pixel| pixel.0[3]);
+    dither(&rows, method, |value, threshold| {
+        let offset = (threshold - 0.5) * spread;
+        let alpha = alphas.next().unwrap_or(u8::MAX);
+        let target: [u8; 4] = std::array::from_fn(|channel| match channel {
+            3 => alpha,
+            _ => (value[channel] + offset).round().clamp(0.0, 255.0) as u8,
+        });
+
+        let index = lookup(target);
+        let (r, g, b, _) = palette[index];
+        (index, [r, g, b].map(|value| value as f32))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The index of the closest color within the palette, found by scanning every entry.
+    fn scan_nearest(palette: &[ArgbPixel], color: [u8; 4]) -> usize {
+        (0..palette.len())
+            .min_by_key(|index| distance(channels(palette[*index]), color))
+            .unwrap()
+    }
+
+    #[test]
+    fn palette_tree_matches_scanning_large_palettes() {
+        // A palette as large as RgbSixteen permits, of pseudo-random colors with duplicates.
+        let mut state = 0x2545_f491_u32;
+        let mut next_color = || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let [r, g, b, a] = state.to_be_bytes();
+            (r, g, b, a | 0x80)
+        };
+        let mut palette: Vec<ArgbPixel> = (0..65536 - 256).map(|_| next_color()).collect();
+        palette.extend_from_within(..256);
+
+        let tree = PaletteTree::new(&palette);
+        for _ in 0..64 {
+            let color = channels(next_color());
+            assert_eq!(tree.nearest(color), scan_nearest(&palette, color));
+        }
+        for index in [0, 255, 65535] {
+            let color = channels(palette[index]);
+            assert_eq!(tree.nearest(color), scan_nearest(&palette, color));
+        }
+    }
+
+    #[test]
+    fn validates_gamma() {
+        for gamma in [None, Some(1.0), Some(2.2)] {
+            let options = QuantizeOptions {
+                gamma,
+                ..Default::default()
+            };
+            assert!(options.validate().is_ok(), "{gamma:?}");
+        }
+        for gamma in [0.0, -2.2, f32::NAN, f32::INFINITY] {
+            let options = QuantizeOptions {
+                gamma: Some(gamma),
+                ..Default::default()
+            };
+            assert!(
+                matches!(
+                    options.validate(),
+                    Err(SilverError::InvalidQuantizeOptions(_))
+                ),
+                "{gamma}"
+            );
+        }
+    }
+
+    #[test]
+    fn error_diffusion_carries_alpha_through() {
+        // Mostly opaque red is nearest to opaque red. Diffusing the difference in alpha
+        // would make some of its neighbours transparent.
+        let palette = [(255, 0, 0, 255), (255, 0, 0, 0)];
+        let pixels = RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 160]));
+
+        for method in [Dither::FloydSteinberg, Dither::Atkinson] {
+            let indices = map_pixels(&pixels, &palette, method);
+            assert!(indices.iter().all(|index| *index == 0), "{method}");
+        }
+    }
+}
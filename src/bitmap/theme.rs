@@ -0,0 +1,404 @@
+use std::{collections::HashSet, fmt, fs, io::Cursor, path::Path};
+
+use crate::{
+    bitmap::{image::BitmapImage, pixel::decode_pixels, quantize::QuantizeOptions},
+    database::SilverDB,
+    database::SilverResourceID,
+    device::DeviceProfile,
+    section_content::SectionContent,
+    silver_error::SilverError,
+    symbols::SymbolTable,
+};
+
+/// A replacement image within a theme, named after the resource it replaces.
+pub struct ThemeImage {
+    /// The file name of this image, without its extension.
+    /// This is either a resource ID (i.e. `0x0dad06d8` or `231540440`) or a symbol name.
+    pub name: String,
+    /// PNG contents of this image.
+    pub contents: Vec<u8>,
+}
+
+/// A set of images replacing bitmaps within a database.
+pub struct Theme {
+    pub images: Vec<ThemeImage>,
+}
+
+/// Why an image within a theme was not applied.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ThemeSkip {
+    /// The image's name is neither a resource ID nor a known symbol.
+    UnknownName,
+    /// No bitmap with this ID is present.
+    MissingBitmap(SilverResourceID),
+    /// Another image within the theme already replaced this bitmap.
+    DuplicateImage(SilverResourceID),
+    /// The image could not be encoded to the original bitmap's format, and why.
+    UnencodableBitmap(SilverResourceID, String),
+    /// The image's dimensions differ from those of the original bitmap.
+    DimensionMismatch {
+        id: SilverResourceID,
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    /// The image could not be decoded.
+    InvalidImage(String),
+}
+
+impl fmt::Display for ThemeSkip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeSkip::UnknownName => write!(f, "not a resource ID or known symbol"),
+            ThemeSkip::MissingBitmap(id) => write!(f, "{id}: no such bitmap"),
+            ThemeSkip::DuplicateImage(id) => write!(f, "{id}: replaced by another image"),
+            ThemeSkip::UnencodableBitmap(id, e) => write!(f, "{id}: cannot be encoded: {e}"),
+            ThemeSkip::DimensionMismatch {
+                id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{id}: expected {}x{}, but image is {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            ThemeSkip::InvalidImage(e) => write!(f, "invalid image: {e}"),
+        }
+    }
+}
+
+/// The outcome of applying a theme to a database.
+#[derive(Default)]
+pub struct ThemeReport {
+    /// Names of images applied, and the resource they replaced.
+    pub applied: Vec<(String, SilverResourceID)>,
+    /// Names of images not applied, and why.
+    pub skipped: Vec<(String, ThemeSkip)>,
+}
+
+impl Theme {
+    /// Reads every PNG within the given directory, in order of file name.
+    pub fn read_dir(directory: &Path) -> Result<Self, SilverError> {
+        let mut images = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            let is_png = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+            let Some(name) = path.file_stem().filter(|_| is_png) else {
+                continue;
+            };
+            images.push(ThemeImage {
+                name: name.to_string_lossy().to_string(),
+                contents: fs::read(&path)?,
+            });
+        }
+        images.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Theme { images })
+    }
+
+    /// Replaces bitmaps within the given database with our images.
+    ///
+    /// Images must match the dimensions of the bitmap they replace. They are
+    /// encoded to its original format immediately, so that images which cannot be
+    /// are skipped rather than failing once the database is written.
+    pub fn apply(
+        &self,
+        database: &mut SilverDB,
+        symbols: &SymbolTable,
+        quantize_options: &QuantizeOptions,
+        profile: &DeviceProfile,
+    ) -> ThemeReport {
+        let mut report = ThemeReport::default();
+        let mut applied_ids = HashSet::new();
+
+        for image in &self.images {
+            let name = image.name.clone();
+            let Some(id) = symbols.resolve(&image.name) else {
+                report.skipped.push((name, ThemeSkip::UnknownName));
+                continue;
+            };
+            if applied_ids.contains(&id) {
+                report.skipped.push((name, ThemeSkip::DuplicateImage(id)));
+                continue;
+            }
+            let dimensions = match image::load_from_memory(&image.contents) {
+                Ok(pixels) => (pixels.width(), pixels.height()),
+                Err(e) => {
+                    report
+                        .skipped
+                        .push((name, ThemeSkip::InvalidImage(e.to_string())));
+                    continue;
+                }
+            };
+
+            // The same ID may be present within several bitmap sections,
+            // unless its name is limited to one.
+            let bitmaps: Vec<_> = database
+                .sections
+                .iter_mut()
+                .filter(|section| {
+                    symbols
+                        .resolve_in(section.section_type, &image.name)
+                        .is_some()
+                })
+                .flat_map(|section| section.resources.iter_mut())
+                .filter(|resource| resource.id == id)
+                .filter_map(|resource| match &mut resource.contents {
+                    SectionContent::Bitmap(Some(bitmap)) => Some(bitmap),
+                    _ => None,
+                })
+                .collect();
+            let skip = if bitmaps.is_empty() {
+                Some(ThemeSkip::MissingBitmap(id))
+            } else if let Some(bitmap) =
+                bitmaps.iter().find(|bitmap| !bitmap.format_type.is_known())
+            {
+                Some(ThemeSkip::UnencodableBitmap(
+                    id,
+                    format!("unknown format {:?}", bitmap.format_type),
+                ))
+            } else {
+                bitmaps
+                    .iter()
+                    .find(|bitmap| (bitmap.width, bitmap.height) != dimensions)
+                    .map(|bitmap| ThemeSkip::DimensionMismatch {
+                        id,
+                        expected: (bitmap.width, bitmap.height),
+                        actual: dimensions,
+                    })
+            };
+            if let Some(skip) = skip {
+                report.skipped.push((name, skip));
+                continue;
+            }
+
+            // Every bitmap is encoded before any are replaced, so that failures leave all intact.
+            let replacements: Result<Vec<BitmapImage>, SilverError> = bitmaps
+                .iter()
+                .map(|bitmap| replace(bitmap, &image.contents, quantize_options, profile))
+                .collect();
+            let replacements = match replacements {
+                Ok(replacements) => replacements,
+                Err(e) => {
+                    report
+                        .skipped
+                        .push((name, ThemeSkip::UnencodableBitmap(id, e.to_string())));
+                    continue;
+                }
+            };
+
+            for (bitmap, replacement) in bitmaps.into_iter().zip(replacements) {
+                *bitmap = replacement;
+            }
+            applied_ids.insert(id);
+            report.applied.push((name, id));
+        }
+        report
+    }
+}
+
+/// Encodes the given PNG to the format of the given bitmap, returning its replacement.
+///
+/// The replacement holds its new raw representation, and its contents are the pixels
+/// encoded, so that it is reduced as-is.
+fn replace(
+    bitmap: &BitmapImage,
+    contents: &[u8],
+    quantize_options: &QuantizeOptions,
+    profile: &DeviceProfile,
+) -> Result<BitmapImage, SilverError> {
+    let mut replacement = bitmap.clone();
+    replacement.contents = contents.to_vec();
+
+    let raw = replacement.encode(profile, quantize_options)?;
+    let encoded_pixels = decode_pixels(&raw.header, &raw.contents)?.to_rgba8();
+    let mut png_writer = Cursor::new(Vec::new());
+    encoded_pixels.write_to(&mut png_writer, image::ImageFormat::Png)?;
+
+    replacement.rendered_width = raw.header.rendered_width;
+    replacement.contents = png_writer.into_inner();
+    replacement.raw = Some(raw);
+    Ok(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbaImage;
+
+    use super::*;
+    use crate::{
+        bitmap::{format::RawBitmapType, pixel::expand_channel},
+        database::{SilverResource, SilverSection},
+        section_types::SectionType,
+    };
+
+    fn png(width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+        let mut png_writer = Cursor::new(Vec::new());
+        RgbaImage::from_pixel(width, height, image::Rgba(color))
+            .write_to(&mut png_writer, image::ImageFormat::Png)
+            .unwrap();
+        png_writer.into_inner()
+    }
+
+    fn bitmap(id: u32, width: u32, height: u32, format_type: RawBitmapType) -> SilverResource {
+        SilverResource {
+            id: SilverResourceID(id),
+            contents: SectionContent::Bitmap(Some(BitmapImage {
+                width,
+                height,
+                rendered_width: 0,
+                format_type,
+                resource_id: id,
+                contents: png(width, height, [0, 0, 0, 255]),
+                raw: None,
+            })),
+        }
+    }
+
+    fn database(resources: Vec<SilverResource>) -> SilverDB {
+        SilverDB {
+            sections: vec![SilverSection {
+                section_type: SectionType::from_name("BMap".to_string()).unwrap(),
+                is_sequential: 0,
+                resources,
+            }],
+        }
+    }
+
+    fn theme(images: &[(&str, Vec<u8>)]) -> Theme {
+        Theme {
+            images: images
+                .iter()
+                .map(|(name, contents)| ThemeImage {
+                    name: name.to_string(),
+                    contents: contents.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn symbols() -> SymbolTable {
+        let mut symbols = SymbolTable::new();
+        symbols
+            .insert(SilverResourceID(0x0dad0002), "PlayIcon".to_string())
+            .unwrap();
+        symbols
+    }
+
+    fn apply(theme: &Theme, database: &mut SilverDB) -> ThemeReport {
+        theme.apply(
+            database,
+            &symbols(),
+            &QuantizeOptions::default(),
+            &DeviceProfile::generic(),
+        )
+    }
+
+    #[test]
+    fn resolves_ids_and_symbols() {
+        let mut database = database(vec![
+            bitmap(0x0dad0001, 2, 2, RawBitmapType::Rgb565),
+            bitmap(0x0dad0002, 2, 2, RawBitmapType::RgbEight),
+            bitmap(0x0dad0003, 2, 2, RawBitmapType::Argb8888),
+        ]);
+        // Every channel is representable within five or six bits.
+        let red = [expand_channel(31, 5), 0, 0, 255];
+        let theme = theme(&[
+            ("0x0dad0001", png(2, 2, red)),
+            ("PlayIcon", png(2, 2, red)),
+            ("229441539", png(2, 2, red)),
+            ("NoSuchIcon", png(2, 2, red)),
+            ("0x0dad0009", png(2, 2, red)),
+        ]);
+
+        let report = apply(&theme, &mut database);
+        let applied: Vec<_> = report.applied.iter().map(|(_, id)| id.0).collect();
+        assert_eq!(applied, vec![0x0dad0001, 0x0dad0002, 0x0dad0003]);
+        assert_eq!(
+            report.skipped,
+            vec![
+                ("NoSuchIcon".to_string(), ThemeSkip::UnknownName),
+                (
+                    "0x0dad0009".to_string(),
+                    ThemeSkip::MissingBitmap(SilverResourceID(0x0dad0009))
+                ),
+            ]
+        );
+
+        // Replacements are already encoded to their original format.
+        for resource in &database.sections[0].resources {
+            let SectionContent::Bitmap(Some(bitmap)) = &resource.contents else {
+                panic!("bitmap should remain");
+            };
+            let raw = bitmap.raw.as_ref().expect("bitmap should be encoded");
+            assert_eq!(raw.header.image_type, bitmap.format_type);
+            assert!(bitmap
+                .pixels()
+                .unwrap()
+                .pixels()
+                .all(|pixel| pixel.0 == red));
+        }
+    }
+
+    #[test]
+    fn skips_mismatched_and_duplicate_images() {
+        let mut database = database(vec![
+            bitmap(0x0dad0001, 2, 2, RawBitmapType::Rgb565),
+            bitmap(0x0dad0002, 2, 2, RawBitmapType::Rgb565),
+        ]);
+        let theme = theme(&[
+            ("0x0dad0001", png(3, 2, [255, 255, 255, 255])),
+            ("0x0dad0002", png(2, 2, [255, 255, 255, 255])),
+            ("PlayIcon", png(2, 2, [255, 255, 255, 255])),
+        ]);
+
+        let report = apply(&theme, &mut database);
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(
+            report.skipped,
+            vec![
+                (
+                    "0x0dad0001".to_string(),
+                    ThemeSkip::DimensionMismatch {
+                        id: SilverResourceID(0x0dad0001),
+                        expected: (2, 2),
+                        actual: (3, 2),
+                    }
+                ),
+                (
+                    "PlayIcon".to_string(),
+                    ThemeSkip::DuplicateImage(SilverResourceID(0x0dad0002))
+                ),
+            ]
+        );
+
+        // The mismatched bitmap is left untouched.
+        let SectionContent::Bitmap(Some(bitmap)) = &database.sections[0].resources[0].contents
+        else {
+            panic!("bitmap should remain");
+        };
+        assert!(bitmap.raw.is_none());
+        assert_eq!(bitmap.pixels().unwrap().get_pixel(0, 0).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn skips_unencodable_bitmaps() {
+        let mut database = database(vec![bitmap(
+            0x0dad0001,
+            2,
+            2,
+            RawBitmapType::Unknown(0x1234),
+        )]);
+        let theme = theme(&[("0x0dad0001", png(2, 2, [255, 255, 255, 255]))]);
+
+        let report = apply(&theme, &mut database);
+        assert!(report.applied.is_empty());
+        assert!(matches!(
+            report.skipped.as_slice(),
+            [(
+                _,
+                ThemeSkip::UnencodableBitmap(SilverResourceID(0x0dad0001), _)
+            )]
+        ));
+    }
+}
@@ -0,0 +1,348 @@
+use std::{cmp::Ordering, collections::BTreeMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitmap::RawBitmapType,
+    database::{find_database_files, SilverDB, SilverResourceID},
+    device::{Device, DeviceProfile},
+    section_content::SectionContent,
+    silver_error::SilverError,
+};
+
+/// Identifies a single database within the catalog.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CatalogKey {
+    /// The firmware version this database was taken from, i.e. `1.1.2`.
+    pub firmware: String,
+    /// The device this firmware is for, if known.
+    pub device: Option<Device>,
+    /// The path of this database relative to the ingested directory,
+    /// i.e. `SilverDB.en_US.LE.bin`.
+    pub file: String,
+}
+
+impl CatalogKey {
+    /// Orders keys by device, then firmware version, then file.
+    fn compare(&self, other: &CatalogKey) -> Ordering {
+        self.device
+            .cmp(&other.device)
+            .then_with(|| compare_versions(&self.firmware, &other.firmware))
+            .then_with(|| self.file.cmp(&other.file))
+    }
+}
+
+/// A summary of a single resource's contents.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CatalogResource {
+    /// The four-character name of this resource's section, i.e. `BMap`.
+    pub section: String,
+    /// The ID of this resource.
+    pub id: SilverResourceID,
+    /// A hash of this resource's contents, per `SectionContent::content_hash`.
+    pub hash: String,
+    /// The text of this resource, if it is a string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Details of this resource, if it is a bitmap.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitmap: Option<CatalogBitmap>,
+}
+
+/// Bitmap details retained within the catalog.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CatalogBitmap {
+    pub format: RawBitmapType,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// All resources within a single database.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CatalogEntry {
+    pub key: CatalogKey,
+    pub resources: Vec<CatalogResource>,
+}
+
+/// A persistent record of resources across many databases, firmware versions and devices.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Catalog {
+    /// All ingested databases, ordered by device, firmware version and file.
+    pub entries: Vec<CatalogEntry>,
+}
+
+/// A single occurrence of a resource within the catalog.
+#[derive(Clone, Debug)]
+pub struct CatalogOccurrence<'a> {
+    pub key: &'a CatalogKey,
+    pub resource: &'a CatalogResource,
+}
+
+/// A distinct version of a bitmap, alongside where it was seen.
+#[derive(Clone, Debug)]
+pub struct BitmapVersion<'a> {
+    /// The hash of this version's contents.
+    pub hash: &'a str,
+    /// Details of this version.
+    pub bitmap: Option<CatalogBitmap>,
+    /// Every database this version was seen within, in catalog order.
+    pub seen_in: Vec<&'a CatalogKey>,
+}
+
+/// The outcome of ingesting a directory of databases.
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    /// Relative paths of databases ingested.
+    pub ingested: Vec<String>,
+    /// Relative paths of files that could not be parsed as databases, and why.
+    pub skipped: Vec<(String, String)>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    /// Records the resources of a database under the given key.
+    /// Any database previously recorded under the same key is replaced.
+    pub fn ingest(&mut self, key: CatalogKey, database: &SilverDB) {
+        let mut resources = Vec::new();
+        for section in &database.sections {
+            for resource in &section.resources {
+                let (text, bitmap) = match &resource.contents {
+                    SectionContent::String(string) => (Some(string.text().to_string()), None),
+                    SectionContent::Bitmap(Some(image)) => (
+                        None,
+                        Some(CatalogBitmap {
+                            format: image.format_type,
+                            width: image.width,
+                            height: image.height,
+                        }),
+                    ),
+                    _ => (None, None),
+                };
+
+                resources.push(CatalogResource {
+                    section: section.section_type.to_name(),
+                    id: resource.id,
+                    hash: resource.contents.content_hash(),
+                    text,
+                    bitmap,
+                });
+            }
+        }
+
+        self.entries.retain(|entry| entry.key != key);
+        let position = self
+            .entries
+            .partition_point(|entry| entry.key.compare(&key) == Ordering::Less);
+        self.entries
+            .insert(position, CatalogEntry { key, resources });
+    }
+
+    /// Records every database (`*.bin`) within the given directory and its subdirectories
+    /// as part of the given firmware version. Files are keyed by their relative path.
+    ///
+    /// Files that cannot be parsed as a database (as other `.bin` files may be present
+    /// within firmware extracts) are skipped, and reported as such.
+    pub fn ingest_directory(
+        &mut self,
+        directory: &Path,
+        firmware: &str,
+        profile: &DeviceProfile,
+    ) -> Result<IngestReport, SilverError> {
+        let mut report = IngestReport::default();
+        for database_path in find_database_files(directory)? {
+            let file = database_path
+                .strip_prefix(directory)
+                .unwrap_or(&database_path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let database = match SilverDB::read_with_profile(fs::read(&database_path)?, profile) {
+                Ok(database) => database,
+                Err(e) => {
+                    report.skipped.push((file, e.to_string()));
+                    continue;
+                }
+            };
+
+            let key = CatalogKey {
+                firmware: firmware.to_string(),
+                device: profile.device,
+                file: file.clone(),
+            };
+            self.ingest(key, &database);
+            report.ingested.push(file);
+        }
+        Ok(report)
+    }
+
+    /// Every occurrence of the given resource within the given section, in catalog order.
+    ///
+    /// IDs are only unique within their section (i.e. `Str ` and `StrT` share IDs).
+    pub fn history(&self, section: &str, id: SilverResourceID) -> Vec<CatalogOccurrence<'_>> {
+        self.occurrences()
+            .filter(|occurrence| {
+                occurrence.resource.section == section && occurrence.resource.id == id
+            })
+            .collect()
+    }
+
+    /// Every section the given resource ID is present within, in the order first seen.
+    pub fn sections(&self, id: SilverResourceID) -> Vec<&str> {
+        let mut sections: Vec<&str> = Vec::new();
+        for occurrence in self.occurrences() {
+            let section = occurrence.resource.section.as_str();
+            if occurrence.resource.id == id && !sections.contains(&section) {
+                sections.push(section);
+            }
+        }
+        sections
+    }
+
+    /// The earliest occurrence of a string containing the given text, per device.
+    ///
+    /// As firmware versions are only comparable within the same device,
+    /// we return the first occurrence for every device separately.
+    pub fn first_appearance(&self, text: &str) -> Vec<CatalogOccurrence<'_>> {
+        let mut appearances: BTreeMap<Option<Device>, CatalogOccurrence<'_>> = BTreeMap::new();
+        for occurrence in self.occurrences() {
+            let contains_text = occurrence
+                .resource
+                .text
+                .as_ref()
+                .is_some_and(|resource_text| resource_text.contains(text));
+            if contains_text {
+                appearances
+                    .entry(occurrence.key.device)
+                    .or_insert(occurrence);
+            }
+        }
+        appearances.into_values().collect()
+    }
+
+    /// All distinct versions of the given bitmap within the given section,
+    /// in the order they were first seen.
+    pub fn bitmap_versions(&self, section: &str, id: SilverResourceID) -> Vec<BitmapVersion<'_>> {
+        let mut versions: Vec<BitmapVersion<'_>> = Vec::new();
+        for occurrence in self.history(section, id) {
+            if occurrence.resource.bitmap.is_none() {
+                continue;
+            }
+
+            let hash = occurrence.resource.hash.as_str();
+            match versions.iter_mut().find(|version| version.hash == hash) {
+                Some(version) => version.seen_in.push(occurrence.key),
+                None => versions.push(BitmapVersion {
+                    hash,
+                    bitmap: occurrence.resource.bitmap,
+                    seen_in: vec![occurrence.key],
+                }),
+            }
+        }
+        versions
+    }
+
+    /// Every resource within every database, in catalog order.
+    fn occurrences(&self) -> impl Iterator<Item = CatalogOccurrence<'_>> {
+        self.entries.iter().flat_map(|entry| {
+            entry
+                .resources
+                .iter()
+                .map(move |resource| CatalogOccurrence {
+                    key: &entry.key,
+                    resource,
+                })
+        })
+    }
+}
+
+/// Compares firmware versions such as `1.0.2` and `1.1.2` by their numeric components.
+/// Non-numeric components are compared as text.
+fn compare_versions(first: &str, second: &str) -> Ordering {
+    let mut first_components = first.split('.');
+    let mut second_components = second.split('.');
+    loop {
+        let ordering = match (first_components.next(), second_components.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(first), Some(second)) => match (first.parse::<u32>(), second.parse::<u32>()) {
+                (Ok(first), Ok(second)) => first.cmp(&second),
+                _ => first.cmp(second),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::string_section;
+
+    /// A database holding a string with ID 1 within each given section.
+    fn database(sections: &[(&str, &str)]) -> SilverDB {
+        SilverDB {
+            sections: sections
+                .iter()
+                .map(|(name, text)| string_section(name, &[(1, text)]))
+                .collect(),
+        }
+    }
+
+    fn key(firmware: &str) -> CatalogKey {
+        CatalogKey {
+            firmware: firmware.to_string(),
+            device: None,
+            file: "SilverDB.en_US.LE.bin".to_string(),
+        }
+    }
+
+    #[test]
+    fn history_is_keyed_by_section() {
+        let mut catalog = Catalog::new();
+        catalog.ingest(
+            key("1.0"),
+            &database(&[("Str ", "Music"), ("StrT", "Title")]),
+        );
+        catalog.ingest(key("1.1"), &database(&[("Str ", "Songs")]));
+
+        let id = SilverResourceID(1);
+        assert_eq!(catalog.sections(id), vec!["Str ", "StrT"]);
+
+        let texts = |section| -> Vec<_> {
+            catalog
+                .history(section, id)
+                .iter()
+                .map(|occurrence| occurrence.resource.text.clone().unwrap())
+                .collect()
+        };
+        assert_eq!(texts("Str "), vec!["Music", "Songs"]);
+        assert_eq!(texts("StrT"), vec!["Title"]);
+        assert!(catalog.history("BMap", id).is_empty());
+    }
+
+    #[test]
+    fn ingest_directory_skips_unparsable_files() {
+        let directory =
+            std::env::temp_dir().join(format!("silverlib-catalog-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let valid = SilverDB::write(database(&[("Str ", "Music")]).sections).unwrap();
+        fs::write(directory.join("SilverDB.en_US.LE.bin"), valid).unwrap();
+        fs::write(directory.join("Junk.bin"), b"not a database").unwrap();
+
+        let mut catalog = Catalog::new();
+        let report = catalog
+            .ingest_directory(&directory, "1.0", &DeviceProfile::generic())
+            .unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(report.ingested, vec!["SilverDB.en_US.LE.bin"]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, "Junk.bin");
+        assert_eq!(catalog.entries.len(), 1);
+    }
+}
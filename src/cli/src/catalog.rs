@@ -0,0 +1,161 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use silverlib::{Catalog, CatalogKey, DeviceProfile, SectionType, SilverResourceID, SymbolTable};
+
+type AnyError = Box<dyn std::error::Error>;
+
+/// Loads the catalog at the given path, or an empty catalog if it does not yet exist.
+fn open_catalog(catalog_path: &Path) -> Result<Catalog, AnyError> {
+    if !catalog_path.exists() {
+        return Ok(Catalog::new());
+    }
+    Ok(serde_yaml::from_str(&fs::read_to_string(catalog_path)?)?)
+}
+
+/// Describes a catalog key, i.e. "nano7g 1.1.2 SilverDB.en_US.LE.bin".
+fn describe_key(key: &CatalogKey) -> String {
+    let device = key
+        .device
+        .map(|device| device.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    format!("{} {} {}", device, key.firmware, key.file)
+}
+
+/// Ingests all databases within a directory into the catalog at the given path.
+pub fn add_to_catalog(
+    catalog_path: &Path,
+    input_dir: &Path,
+    firmware: &str,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    let mut catalog = open_catalog(catalog_path)?;
+    let report = catalog.ingest_directory(input_dir, firmware, profile)?;
+    fs::write(catalog_path, serde_yaml::to_string(&catalog)?)?;
+
+    for (file, reason) in &report.skipped {
+        println!("Skipped {file}: {reason}");
+    }
+    println!(
+        "Ingested {} databases for firmware {}, skipped {}.",
+        report.ingested.len(),
+        firmware,
+        report.skipped.len()
+    );
+    Ok(())
+}
+
+/// Describes a resource alongside its name within the given section.
+fn describe_resource(symbols: &SymbolTable, section: &str, id: SilverResourceID) -> String {
+    match SectionType::from_name(section.to_string()) {
+        Ok(section_type) => symbols.describe_in(section_type, id),
+        Err(_) => symbols.describe(id),
+    }
+}
+
+/// Prints every occurrence of a resource within the given section, or within every section
+/// it is present within, noting where its contents changed.
+pub fn print_history(
+    catalog_path: &Path,
+    id: SilverResourceID,
+    section: Option<&str>,
+    symbols: &SymbolTable,
+) -> Result<(), AnyError> {
+    let catalog = open_catalog(catalog_path)?;
+    let sections = match section {
+        Some(section) => vec![section],
+        None => catalog.sections(id),
+    };
+
+    let mut is_present = false;
+    for section in sections {
+        let history = catalog.history(section, id);
+        if history.is_empty() {
+            continue;
+        }
+        is_present = true;
+
+        // Contents are only comparable against prior versions of the same file.
+        println!(
+            "History of {} ({}):",
+            describe_resource(symbols, section, id),
+            section
+        );
+        let mut previous_hashes: HashMap<_, &str> = HashMap::new();
+        for occurrence in history {
+            let lineage = (occurrence.key.device, occurrence.key.file.as_str());
+            let hash = occurrence.resource.hash.as_str();
+            let status = match previous_hashes.insert(lineage, hash) {
+                None => "added",
+                Some(previous_hash) if previous_hash != hash => "changed",
+                Some(_) => "unchanged",
+            };
+            println!("\t- {}: {}", describe_key(occurrence.key), status);
+        }
+    }
+    if !is_present {
+        println!(
+            "{} is not present within the catalog.",
+            symbols.describe(id)
+        );
+    }
+    Ok(())
+}
+
+/// Prints the first version, per device, where a string containing the given text appears.
+pub fn print_first_appearance(
+    catalog_path: &Path,
+    text: &str,
+    symbols: &SymbolTable,
+) -> Result<(), AnyError> {
+    let catalog = open_catalog(catalog_path)?;
+    let appearances = catalog.first_appearance(text);
+    if appearances.is_empty() {
+        println!(
+            "No string containing {:?} is present within the catalog.",
+            text
+        );
+        return Ok(());
+    }
+
+    for occurrence in appearances {
+        println!(
+            "{}: {} ({:?})",
+            describe_key(occurrence.key),
+            describe_resource(
+                symbols,
+                &occurrence.resource.section,
+                occurrence.resource.id
+            ),
+            occurrence.resource.text.as_deref().unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+/// Prints every distinct version of a bitmap.
+pub fn print_bitmap_versions(
+    catalog_path: &Path,
+    id: SilverResourceID,
+    section: &str,
+    symbols: &SymbolTable,
+) -> Result<(), AnyError> {
+    let catalog = open_catalog(catalog_path)?;
+    let versions = catalog.bitmap_versions(section, id);
+    println!(
+        "{} has {} distinct versions.",
+        describe_resource(symbols, section, id),
+        versions.len()
+    );
+
+    for version in versions {
+        println!("-------------------------------------------");
+        println!("Hash: {}", version.hash);
+        if let Some(bitmap) = version.bitmap {
+            println!("\t{}x{}, {:?}", bitmap.width, bitmap.height, bitmap.format);
+        }
+        for key in version.seen_in {
+            println!("\t- {}", describe_key(key));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::{fs, path::Path};
+
+use silverlib::{
+    DeviceProfile, QuantizeOptions, RawBitmapType, SectionContent, SilverDB, SilverResourceID,
+    SymbolTable,
+};
+
+use crate::open_database;
+
+type AnyError = Box<dyn std::error::Error>;
+
+/// Converts a single bitmap to the given format, reporting its loss.
+/// The converted database is only written if an output path is given.
+pub fn convert_bitmap(
+    database_path: &Path,
+    resource_id: SilverResourceID,
+    format_type: RawBitmapType,
+    output_path: Option<&Path>,
+    symbols: &SymbolTable,
+    quantize_options: &QuantizeOptions,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    let mut database = open_database(database_path.to_path_buf(), profile);
+
+    let mut converted_count = 0;
+    for section in &mut database.sections {
+        let section_type = section.section_type;
+        for resource in section
+            .resources
+            .iter_mut()
+            .filter(|resource| resource.id == resource_id)
+        {
+            let SectionContent::Bitmap(Some(bitmap)) = &mut resource.contents else {
+                continue;
+            };
+            let (converted, report) = bitmap.convert(format_type, profile, quantize_options)?;
+            println!(
+                "{}: {report}",
+                symbols.describe_in(section_type, resource_id)
+            );
+            if !report.is_lossless() {
+                println!("\tThis conversion is lossy.");
+            }
+
+            *bitmap = converted;
+            converted_count += 1;
+        }
+    }
+    if converted_count == 0 {
+        return Err(format!(
+            "no bitmap with ID {} is present",
+            symbols.describe(resource_id)
+        )
+        .into());
+    }
+
+    if let Some(output_path) = output_path {
+        let raw_database = SilverDB::write_with_profile(database.sections, profile)?;
+        fs::write(output_path, raw_database)?;
+    }
+    Ok(())
+}
+
+/// Converts every bitmap to the smallest known format that looks identical,
+/// reporting savings per section.
+/// The converted database is only written if an output path is given.
+pub fn optimize_bitmaps(
+    database_path: &Path,
+    output_path: Option<&Path>,
+    symbols: &SymbolTable,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    let mut database = open_database(database_path.to_path_buf(), profile);
+
+    for section in &mut database.sections {
+        let mut optimized_count = 0;
+        let mut saved_size = 0;
+        for resource in &mut section.resources {
+            let SectionContent::Bitmap(Some(bitmap)) = &mut resource.contents else {
+                continue;
+            };
+            // Bitmaps without pixel data have nothing to convert.
+            if bitmap.contents.is_empty() {
+                continue;
+            }
+
+            let Some((converted, report)) = bitmap.optimize(&RawBitmapType::KNOWN, profile)? else {
+                continue;
+            };
+            println!(
+                "{}: {report}",
+                symbols.describe_in(section.section_type, resource.id)
+            );
+            saved_size += report.original_size - report.converted_size;
+            optimized_count += 1;
+            *bitmap = converted;
+        }
+
+        if optimized_count > 0 {
+            println!(
+                "{}: {} bitmaps can be {} bytes smaller.",
+                section.section_type, optimized_count, saved_size
+            );
+        }
+    }
+
+    if let Some(output_path) = output_path {
+        let raw_database = SilverDB::write_with_profile(database.sections, profile)?;
+        fs::write(output_path, raw_database)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use silverlib::{
+    check_placeholders, new_locale, pseudo_localize_database, DeviceProfile, LocaleSet,
+    LocaleStatus, PlaceholderIssueKind, PseudoLocaleOptions, SilverDB, SilverResourceID,
+};
+
+use crate::open_database;
+
+type AnyError = Box<dyn std::error::Error>;
+
+/// Prints placeholder issues for all localized databases, returning whether any were found.
+pub fn print_placeholder_issues(
+    base: &SilverDB,
+    localized_paths: Vec<PathBuf>,
+    profile: &DeviceProfile,
+) -> bool {
+    let mut found_issues = false;
+
+    for localized_path in localized_paths {
+        let localized = open_database(localized_path.clone(), profile);
+        let issues = check_placeholders(base, &localized);
+        if issues.is_empty() {
+            continue;
+        }
+        found_issues = true;
+
+        println!("{}:", localized_path.display());
+        for issue in issues {
+            let description = match issue.kind {
+                PlaceholderIssueKind::Missing(token) => format!("missing placeholder {token}"),
+                PlaceholderIssueKind::Extra(token) => format!("extra placeholder {token}"),
+                PlaceholderIssueKind::Reordered { expected, found } => format!(
+                    "reordered placeholders: expected {}, found {}",
+                    expected.join(" "),
+                    found.join(" ")
+                ),
+            };
+            println!("\t- {}: {}", issue.id, description);
+        }
+    }
+
+    found_issues
+}
+
+/// Prints a single line listing the given resource IDs.
+fn print_ids(description: &str, ids: &[SilverResourceID]) {
+    if ids.is_empty() {
+        return;
+    }
+    let all_ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    println!("\t{} ({}): {}", description, ids.len(), all_ids.join(", "));
+}
+
+/// Prints a coverage report for every locale database within the given directory.
+pub fn print_coverage_report(
+    locale_dir: &Path,
+    reference: &str,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    let locale_set = LocaleSet::load_with_profile(locale_dir, profile)?;
+    let reports = locale_set.coverage_report(reference)?;
+    println!("Compared {} locales against {}.", reports.len(), reference);
+
+    for report in reports {
+        println!("-------------------------------------------");
+        if report.is_complete() {
+            println!("{}: complete", report.locale);
+            continue;
+        }
+        println!("{}:", report.locale);
+        print_ids("Missing", &report.missing);
+        print_ids("Extra", &report.extra);
+        print_ids("Untranslated", &report.untranslated);
+        print_ids("Empty", &report.empty);
+    }
+
+    Ok(())
+}
+
+/// Writes a pseudo-localized copy of the given database.
+pub fn create_pseudo_locale(
+    mut database: SilverDB,
+    output_path: &Path,
+    options: &PseudoLocaleOptions,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    pseudo_localize_database(&mut database, options);
+    let raw_database = SilverDB::write_with_profile(database.sections, profile)?;
+    fs::write(output_path, raw_database)?;
+    Ok(())
+}
+
+/// Creates a new locale database and its status file from the database at the given path.
+pub fn create_new_locale(
+    base_path: &Path,
+    locale_code: &str,
+    output_dir: &Path,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    // The base locale can be determined by name, i.e. SilverDB.en_US.LE.bin.
+    let base_locale = base_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(LocaleSet::locale_code);
+
+    let base = open_database(base_path.to_path_buf(), profile);
+    let locale = new_locale(&base, base_locale, locale_code)?;
+
+    let database_path = output_dir.join(locale.file_name());
+    if database_path.exists() {
+        return Err(format!("{} already exists", database_path.display()).into());
+    }
+
+    // e.g. "SilverDB.ja_JP.status.yaml"
+    let status_path = output_dir.join(LocaleStatus::file_name(locale_code));
+    let status = serde_yaml::to_string(&locale.status)?;
+    fs::write(status_path, status)?;
+
+    let raw_database = SilverDB::write_with_profile(locale.database.sections, profile)?;
+    fs::write(&database_path, raw_database)?;
+    println!("Created {}.", database_path.display());
+    Ok(())
+}
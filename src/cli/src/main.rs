@@ -1,17 +1,37 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
-use std::{fs::File, io::Read, path::PathBuf};
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::PathBuf,
+};
 
-use silverlib::{SectionContent, SectionType, SilverDB};
+use silverlib::{
+    Device, DeviceProfile, Dither, Filter, MatchOptions, PseudoLocaleOptions, QuantizeOptions,
+    Quantizer, RawBitmapType, SectionContent, SilverDB, SymbolTable,
+};
 
+mod catalog;
+mod convert;
+mod localization;
 mod marshal;
+mod matching;
 mod scrape;
+mod strings;
+mod theme;
+mod xref;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Subcommands,
+    /// Symbol file mapping resource IDs to names
+    #[arg(long, global = true)]
+    symbols: Option<PathBuf>,
+    /// Device databases are read for and written for (nano5g, nano6g, nano7g, or classic)
+    #[arg(long, global = true)]
+    device: Option<Device>,
 }
 
 #[derive(Subcommand)]
@@ -22,15 +42,41 @@ enum Subcommands {
         database_path: PathBuf,
         /// Directory to output YAML representations within
         output_dir: PathBuf,
+        /// Only extract resources matching this filter, i.e. `section == "Str"`
+        #[arg(long)]
+        filter: Option<Filter>,
+        /// Pack bitmap sections into atlas pages of at most this many pixels square,
+        /// rather than one PNG per bitmap
+        #[arg(long, value_name = "SIZE", num_args = 0..=1, default_missing_value = "2048")]
+        atlas: Option<u32>,
     },
     /// Displays information about contents present within sections
-    Info { database_path: PathBuf },
+    Info {
+        database_path: PathBuf,
+        /// Only display resources matching this filter, i.e. `section == "Str"`
+        #[arg(long)]
+        filter: Option<Filter>,
+    },
+    /// Displays all resources matching a filter
+    Find {
+        /// Path to Silver database to search within
+        database_path: PathBuf,
+        /// Filter to match resources with, i.e. `section == "Str" && text ~ /Photos/`
+        filter: Filter,
+    },
     /// Creates a database from a YAML representation
     Create {
         /// Directory holding the YAML representations to create from
         input_dir: PathBuf,
         /// Path to write Silver databases to
         database_path: PathBuf,
+        #[command(flatten)]
+        quantize: QuantizeArgs,
+    },
+    /// Checks that bitmaps within a database are well-formed and suitable for the device given via --device
+    Validate {
+        /// Path to Silver database to validate
+        database_path: PathBuf,
     },
     /// Scrapes SilverDBs embedded within a given firmware file
     Scrape {
@@ -39,80 +85,607 @@ enum Subcommands {
         /// Directory to output SilverDBs within
         output_dir: PathBuf,
     },
+    /// Displays resources referencing, or referenced by, the given resource
+    Xref {
+        /// Path to Silver database to search within
+        database_path: PathBuf,
+        /// ID of the resource (i.e. 0x0dad06d8), or its symbol name
+        resource_id: String,
+        /// Section of the resource (i.e. BMap), defaulting to every section it is present within
+        #[arg(long)]
+        section: Option<String>,
+        /// Also match the small IDs of Str resources within UI sections.
+        /// Such references are of low confidence, as small integers are common
+        #[arg(long)]
+        strings: bool,
+    },
+    /// Creates a symbol file naming resources after their StrT placeholder
+    Symbols {
+        /// Path to Silver database holding a StrT section
+        database_path: PathBuf,
+        /// Path to write the symbol file to
+        output_path: PathBuf,
+    },
+    /// Matches resources between two versions of a database, writing a mapping file
+    Match {
+        /// Path to the older Silver database
+        old_path: PathBuf,
+        /// Path to the newer Silver database
+        new_path: PathBuf,
+        /// Path to write the YAML mapping file to
+        output_path: PathBuf,
+        /// Minimum similarity, from 0.0 to 1.0, for strings and images to match
+        #[arg(long, default_value_t = 0.8)]
+        minimum_similarity: f32,
+    },
+    /// Migrates the symbol file given via --symbols through a mapping file
+    MigrateSymbols {
+        /// Path to a mapping file created by `match`
+        mapping_path: PathBuf,
+        /// Path to write the migrated symbol file to
+        output_path: PathBuf,
+    },
+    /// Compares placeholders within localized strings against a base database
+    CheckPlaceholders {
+        /// Path to the base Silver database, i.e. for en_US
+        base_path: PathBuf,
+        /// Paths to localized Silver databases to check
+        #[arg(required = true)]
+        localized_paths: Vec<PathBuf>,
+    },
+    /// Reports translation coverage for every locale database within a directory
+    L10nReport {
+        /// Directory holding SilverDB.xx_XX.LE.bin databases
+        locale_dir: PathBuf,
+        /// Locale code all other locales are compared against
+        #[arg(long, default_value = "en_US")]
+        reference: String,
+    },
+    /// Creates a new locale database from an existing locale's database
+    NewLocale {
+        /// Path to the Silver database to base this locale upon
+        base_path: PathBuf,
+        /// Locale code of the new locale, i.e. ja_JP
+        locale_code: String,
+        /// Directory to create the database within, defaulting to that of the base database
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+    },
+    /// Creates a pseudo-localized database for testing layout and translations
+    PseudoLocale {
+        /// Path to the Silver database to pseudo-localize
+        base_path: PathBuf,
+        /// Path to write the pseudo-localized database to
+        output_path: PathBuf,
+        /// Percentage to lengthen all strings by
+        #[arg(long, default_value_t = 30)]
+        expansion: u32,
+        /// Do not replace letters with accented look-alikes
+        #[arg(long)]
+        no_accents: bool,
+        /// Do not surround strings with brackets
+        #[arg(long)]
+        no_brackets: bool,
+        /// Reverse strings to emulate right-to-left text
+        #[arg(long)]
+        mirror: bool,
+    },
+    /// Exports a database, or a directory of databases, to an SQLite file
+    #[cfg(feature = "sqlite")]
+    ExportSqlite {
+        /// Path to a Silver database, or a directory holding them
+        input_path: PathBuf,
+        /// Path to the SQLite file to export to
+        output_path: PathBuf,
+    },
+    /// Records and queries resources across many firmware versions
+    Catalog {
+        #[command(subcommand)]
+        command: CatalogCommand,
+    },
+    /// Exchanges strings with translation tools
+    Strings {
+        #[command(subcommand)]
+        command: StringsCommand,
+    },
+    /// Re-encodes a bitmap to another format, reporting any loss
+    ConvertBitmap {
+        /// Path to the Silver database holding bitmaps
+        database_path: PathBuf,
+        /// ID of the bitmap (i.e. 0x0dad06d8), or its symbol name
+        #[arg(required_unless_present = "optimize")]
+        resource_id: Option<String>,
+        /// Format to convert to, i.e. Rgb565 or Argb4444
+        #[arg(required_unless_present = "optimize")]
+        format: Option<RawBitmapType>,
+        /// Instead convert every bitmap to the smallest known format that looks identical
+        #[arg(long, conflicts_with_all = ["resource_id", "format"])]
+        optimize: bool,
+        /// Path to write the converted database to; otherwise, only loss is reported
+        #[arg(long)]
+        output_path: Option<PathBuf>,
+        #[command(flatten)]
+        quantize: QuantizeArgs,
+    },
+    /// Replaces bitmaps with custom imagery
+    Theme {
+        #[command(subcommand)]
+        command: ThemeCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum CatalogCommand {
+    /// Ingests every database within a directory, replacing any previously ingested
+    Add {
+        /// Path to the catalog, created if it does not exist
+        catalog_path: PathBuf,
+        /// Directory holding Silver databases, i.e. a scrape output or rsrc extract
+        input_dir: PathBuf,
+        /// Firmware version these databases are from, i.e. 1.1.2
+        #[arg(long)]
+        firmware: String,
+    },
+    /// Displays every version of a resource, and where its contents changed
+    History {
+        /// Path to the catalog
+        catalog_path: PathBuf,
+        /// ID of the resource (i.e. 0x0dad06d8), or its symbol name
+        resource_id: String,
+        /// Section of the resource (i.e. BMap), defaulting to every section it is present within
+        #[arg(long)]
+        section: Option<String>,
+    },
+    /// Displays the first version where a string containing the given text appears
+    FirstString {
+        /// Path to the catalog
+        catalog_path: PathBuf,
+        /// Text to search for
+        text: String,
+    },
+    /// Displays all distinct versions of a bitmap
+    BitmapVersions {
+        /// Path to the catalog
+        catalog_path: PathBuf,
+        /// ID of the resource (i.e. 0x0dad06d8), or its symbol name
+        resource_id: String,
+        /// Section of the bitmap (BMap or StBM)
+        #[arg(long, default_value = "BMap")]
+        section: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum StringsCommand {
+    /// Exports strings of every locale database within a directory, one file per locale
+    Export {
+        /// Directory holding SilverDB.xx_XX.LE.bin databases
+        locale_dir: PathBuf,
+        /// Directory to output exported strings within
+        output_dir: PathBuf,
+        /// Format to export strings as
+        #[arg(long, value_enum, default_value = "po")]
+        format: strings::StringsFormat,
+        /// Locale code to use as source text
+        #[arg(long, default_value = "en_US")]
+        reference: String,
+    },
+    /// Imports translated strings, updating locale databases in place
+    Import {
+        /// Directory holding SilverDB.xx_XX.LE.bin databases
+        locale_dir: PathBuf,
+        /// Directory holding files named by locale code, i.e. ja_JP.po
+        input_dir: PathBuf,
+        /// Format to import strings from
+        #[arg(long, value_enum, default_value = "po")]
+        format: strings::StringsFormat,
+        /// Locale code that spreadsheet resource IDs, and .strings keys lacking a resource ID, are matched against
+        #[arg(long, default_value = "en_US")]
+        reference: String,
+    },
+}
+
+/// Options for encoding edited bitmaps to paletted and low-bit-depth grayscale formats.
+#[derive(Args)]
+struct QuantizeArgs {
+    /// Algorithm used to choose palettes for RgbEight/RgbSixteen bitmaps
+    /// (median-cut or popularity)
+    #[arg(long, default_value_t = Quantizer::MedianCut)]
+    quantizer: Quantizer,
+    /// Dithering used when reducing colors of paletted and 2- or 4-bit grayscale bitmaps
+    /// (threshold, bayer, floyd-steinberg, or atkinson)
+    #[arg(long, default_value_t = Dither::Threshold, default_missing_value = "floyd-steinberg", num_args = 0..=1)]
+    dither: Dither,
+    /// Gamma of grayscale imagery, i.e. 2.2, so that dithering preserves brightness
+    #[arg(long, value_parser = parse_gamma)]
+    gamma: Option<f32>,
+    /// Map paletted bitmaps to their original palette, even if it lacks colors
+    #[arg(long)]
+    reuse_palette: bool,
+}
+
+/// Parses a gamma, which must be finite and positive.
+fn parse_gamma(value: &str) -> Result<f32, String> {
+    let gamma: f32 = value.parse().map_err(|e| format!("{e}"))?;
+    let options = QuantizeOptions {
+        gamma: Some(gamma),
+        ..Default::default()
+    };
+    options.validate().map_err(|e| e.to_string())?;
+    Ok(gamma)
+}
+
+impl From<QuantizeArgs> for QuantizeOptions {
+    fn from(args: QuantizeArgs) -> Self {
+        QuantizeOptions {
+            quantizer: args.quantizer,
+            dither: args.dither,
+            gamma: args.gamma,
+            reuse_palette: args.reuse_palette,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ThemeCommand {
+    /// Replaces bitmaps with PNGs named by resource ID or symbol name, i.e. 0x0dad06d8.png
+    Apply {
+        /// Path to the Silver database holding bitmaps
+        database_path: PathBuf,
+        /// Directory holding replacement PNGs, each matching its original's dimensions
+        theme_dir: PathBuf,
+        /// Path to write the themed database to, leaving the original untouched
+        #[arg(long)]
+        output_path: PathBuf,
+        #[command(flatten)]
+        quantize: QuantizeArgs,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
+    let symbols = open_symbols(cli.symbols);
+    let profile = cli
+        .device
+        .map(DeviceProfile::for_device)
+        .unwrap_or_default();
     match cli.command {
         Subcommands::Extract {
             database_path,
             output_dir,
+            filter,
+            atlas,
         } => {
-            let database = open_database(database_path);
-            marshal::serialize_contents(database, &output_dir)
+            let mut database = open_database(database_path, &profile);
+            if let Some(filter) = filter {
+                database = filter.apply(&database);
+            }
+            marshal::serialize_contents(database, &output_dir, &symbols, atlas)
                 .expect("failed to serialize database to YAML representation");
         }
-        Subcommands::Info { database_path } => {
-            let database = open_database(database_path);
-            print_info(database)
+        Subcommands::Info {
+            database_path,
+            filter,
+        } => {
+            let mut database = open_database(database_path, &profile);
+            if let Some(filter) = filter {
+                database = filter.apply(&database);
+            }
+            print_info(database, &symbols)
+        }
+        Subcommands::Find {
+            database_path,
+            filter,
+        } => {
+            let database = open_database(database_path, &profile);
+            print_matches(&database, &filter, &symbols)
         }
         Subcommands::Create {
             input_dir,
             database_path,
+            quantize,
         } => {
-            marshal::deserialize_contents(&input_dir, &database_path)
-                .expect("failed to deserialize YAML representation");
+            let quantize_options = QuantizeOptions::from(quantize);
+            marshal::deserialize_contents(
+                &input_dir,
+                &database_path,
+                &symbols,
+                &profile,
+                &quantize_options,
+            )
+            .expect("failed to deserialize YAML representation");
+        }
+        Subcommands::Validate { database_path } => {
+            let database = open_database(database_path, &profile);
+            let issues = profile.check(&database);
+            for issue in &issues {
+                println!("{}", issue);
+            }
+            if !issues.is_empty() {
+                std::process::exit(1);
+            }
+            println!("No issues found.");
         }
         Subcommands::Scrape {
             firmware_path,
             output_dir,
-        } => scrape::handle_scrape(&firmware_path, &output_dir)
+        } => scrape::handle_scrape(&firmware_path, &output_dir, &symbols, &profile)
             .expect("failed to scrape resource databases within firmware"),
+        Subcommands::Xref {
+            database_path,
+            resource_id,
+            section,
+            strings,
+        } => {
+            let resource_id = symbols
+                .resolve(&resource_id)
+                .expect("unknown resource ID or symbol");
+            let database = open_database(database_path, &profile);
+            xref::print_xref(database, resource_id, section.as_deref(), strings, &symbols)
+                .expect("failed to find references")
+        }
+        Subcommands::Symbols {
+            database_path,
+            output_path,
+        } => {
+            let database = open_database(database_path, &profile);
+            let derived_symbols = SymbolTable::from_placeholder_names(&database);
+            fs::write(output_path, derived_symbols.to_file_contents())
+                .expect("failed to write symbol file");
+            println!("Wrote {} symbols.", derived_symbols.len());
+        }
+        Subcommands::Match {
+            old_path,
+            new_path,
+            output_path,
+            minimum_similarity,
+        } => {
+            let old_database = open_database(old_path, &profile);
+            let new_database = open_database(new_path, &profile);
+            let options = MatchOptions { minimum_similarity };
+            matching::write_mapping(&old_database, &new_database, &output_path, &options)
+                .expect("failed to match resources")
+        }
+        Subcommands::MigrateSymbols {
+            mapping_path,
+            output_path,
+        } => matching::migrate_symbols(&symbols, &mapping_path, &output_path)
+            .expect("failed to migrate symbols"),
+        Subcommands::CheckPlaceholders {
+            base_path,
+            localized_paths,
+        } => {
+            let base = open_database(base_path, &profile);
+            if localization::print_placeholder_issues(&base, localized_paths, &profile) {
+                std::process::exit(1);
+            }
+        }
+        Subcommands::L10nReport {
+            locale_dir,
+            reference,
+        } => localization::print_coverage_report(&locale_dir, &reference, &profile)
+            .expect("failed to create localization report"),
+        Subcommands::NewLocale {
+            base_path,
+            locale_code,
+            output_dir,
+        } => {
+            let output_dir = output_dir
+                .or_else(|| base_path.parent().map(PathBuf::from))
+                .unwrap_or_default();
+            localization::create_new_locale(&base_path, &locale_code, &output_dir, &profile)
+                .expect("failed to create new locale")
+        }
+        Subcommands::PseudoLocale {
+            base_path,
+            output_path,
+            expansion,
+            no_accents,
+            no_brackets,
+            mirror,
+        } => {
+            let database = open_database(base_path, &profile);
+            let options = PseudoLocaleOptions {
+                accents: !no_accents,
+                expansion,
+                brackets: !no_brackets,
+                mirror,
+            };
+            localization::create_pseudo_locale(database, &output_path, &options, &profile)
+                .expect("failed to create pseudo-localized database")
+        }
+        #[cfg(feature = "sqlite")]
+        Subcommands::ExportSqlite {
+            input_path,
+            output_path,
+        } => {
+            export_sqlite(&input_path, &output_path, &profile).expect("failed to export to SQLite")
+        }
+        Subcommands::Catalog { command } => match command {
+            CatalogCommand::Add {
+                catalog_path,
+                input_dir,
+                firmware,
+            } => catalog::add_to_catalog(&catalog_path, &input_dir, &firmware, &profile)
+                .expect("failed to add databases to catalog"),
+            CatalogCommand::History {
+                catalog_path,
+                resource_id,
+                section,
+            } => {
+                let resource_id = symbols
+                    .resolve(&resource_id)
+                    .expect("unknown resource ID or symbol");
+                catalog::print_history(&catalog_path, resource_id, section.as_deref(), &symbols)
+                    .expect("failed to query catalog")
+            }
+            CatalogCommand::FirstString { catalog_path, text } => {
+                catalog::print_first_appearance(&catalog_path, &text, &symbols)
+                    .expect("failed to query catalog")
+            }
+            CatalogCommand::BitmapVersions {
+                catalog_path,
+                resource_id,
+                section,
+            } => {
+                let resource_id = symbols
+                    .resolve(&resource_id)
+                    .expect("unknown resource ID or symbol");
+                catalog::print_bitmap_versions(&catalog_path, resource_id, &section, &symbols)
+                    .expect("failed to query catalog")
+            }
+        },
+        Subcommands::Strings { command } => match command {
+            StringsCommand::Export {
+                locale_dir,
+                output_dir,
+                format,
+                reference,
+            } => strings::export_strings(&locale_dir, &output_dir, format, &reference, &profile)
+                .expect("failed to export strings"),
+            StringsCommand::Import {
+                locale_dir,
+                input_dir,
+                format,
+                reference,
+            } => strings::import_strings(&locale_dir, &input_dir, format, &reference, &profile)
+                .expect("failed to import strings"),
+        },
+        Subcommands::ConvertBitmap {
+            database_path,
+            resource_id,
+            format,
+            optimize,
+            output_path,
+            quantize,
+        } => {
+            if optimize {
+                convert::optimize_bitmaps(
+                    &database_path,
+                    output_path.as_deref(),
+                    &symbols,
+                    &profile,
+                )
+                .expect("failed to optimize bitmaps")
+            } else {
+                let resource_id = resource_id
+                    .and_then(|resource_id| symbols.resolve(&resource_id))
+                    .expect("unknown resource ID or symbol");
+                let format = format.expect("no format to convert to provided");
+                let quantize_options = QuantizeOptions::from(quantize);
+                convert::convert_bitmap(
+                    &database_path,
+                    resource_id,
+                    format,
+                    output_path.as_deref(),
+                    &symbols,
+                    &quantize_options,
+                    &profile,
+                )
+                .expect("failed to convert bitmap")
+            }
+        }
+        Subcommands::Theme { command } => match command {
+            ThemeCommand::Apply {
+                database_path,
+                theme_dir,
+                output_path,
+                quantize,
+            } => {
+                let quantize_options = QuantizeOptions::from(quantize);
+                theme::apply_theme(
+                    &database_path,
+                    &theme_dir,
+                    &output_path,
+                    &symbols,
+                    &quantize_options,
+                    &profile,
+                )
+                .expect("failed to apply theme")
+            }
+        },
+    };
+}
+
+/// Exports the database, or directory of databases, at the given path to SQLite.
+#[cfg(feature = "sqlite")]
+fn export_sqlite(
+    input_path: &std::path::Path,
+    output_path: &std::path::Path,
+    profile: &DeviceProfile,
+) -> Result<(), silverlib::SilverError> {
+    let mut exporter = silverlib::SqliteExporter::open(output_path)?;
+    if input_path.is_dir() {
+        let count = exporter.add_directory(input_path, profile)?;
+        println!("Exported {} databases.", count);
+    } else {
+        let name = input_path.file_name().unwrap_or_default().to_string_lossy();
+        exporter.add_database(&name, fs::read(input_path)?, profile)?;
+        println!("Exported {}.", name);
+    }
+    Ok(())
+}
+
+/// Parses the symbol file at the given path, if any.
+fn open_symbols(symbols_path: Option<PathBuf>) -> SymbolTable {
+    let Some(symbols_path) = symbols_path else {
+        return SymbolTable::new();
     };
+    let contents = fs::read_to_string(symbols_path).expect("unable to read symbol file");
+    SymbolTable::parse(&contents).expect("unable to parse symbol file")
 }
 
-/// Parses the given path.
-fn open_database(database_path: PathBuf) -> SilverDB {
+/// Parses the given path, using the rules of the given device profile.
+fn open_database(database_path: PathBuf, profile: &DeviceProfile) -> SilverDB {
     let mut database_file = File::open(database_path).expect("unable to open SilverDB database");
     let mut file_contents: Vec<u8> = Vec::new();
     database_file
         .read_to_end(&mut file_contents)
         .expect("unable to read contents of SilverDB database");
 
-    SilverDB::read(file_contents).expect("unable to parse SilverDB database")
+    SilverDB::read_with_profile(file_contents, profile).expect("unable to parse SilverDB database")
 }
 
-fn print_info(database: SilverDB) {
+fn print_info(database: SilverDB, symbols: &SymbolTable) {
     println!("There are {} sections.", database.sections.len());
     println!("Sections:");
-    let sections = database.sections;
-    for section in &sections {
+    for section in &database.sections {
         println!("-------------------------------------------");
         println!("Section: {}", section.section_type);
         println!("\tResource count: {}", section.resources.len());
         println!("\tResources:");
         for resource in &section.resources {
-            println!("\t\t- Resource ID: {}", resource.id);
+            println!(
+                "\t\t- Resource ID: {}",
+                symbols.describe_in(section.section_type, resource.id)
+            );
         }
         println!("-------------------------------------------");
     }
+}
 
-    // Test: Print all 'Str ' types
-    let string_section = sections
-        .as_slice()
-        .iter()
-        .find(|&section| section.section_type == SectionType::String);
-    let Some(string_section) = string_section else {
-        println!("No string section found in this database.");
-        return;
-    };
-
-    for string_resource in string_section.resources.as_slice() {
-        match &string_resource.contents {
-            SectionContent::String(value) => {
-                println!("String resource ({}): {}", &string_resource.id, value)
+/// Prints a summary of every resource matching the given filter.
+fn print_matches(database: &SilverDB, filter: &Filter, symbols: &SymbolTable) {
+    let matches = filter.find(database);
+    for (section, resource) in &matches {
+        let summary = match &resource.contents {
+            SectionContent::String(value) => format!("{:?}", value.text()),
+            SectionContent::Bitmap(Some(bitmap)) => format!(
+                "{}x{} {:?}",
+                bitmap.width, bitmap.height, bitmap.format_type
+            ),
+            SectionContent::Bitmap(None) => "empty bitmap".to_string(),
+            SectionContent::DateTimeLocale(raw_contents)
+            | SectionContent::Unknown(raw_contents) => {
+                format!("{} bytes", raw_contents.len())
             }
-            _ => println!("Unknown content type!"),
-        }
+        };
+        println!(
+            "{} {}: {}",
+            section.section_type.to_name(),
+            symbols.describe_in(section.section_type, resource.id),
+            summary
+        );
     }
+    println!("Found {} matching resources.", matches.len());
 }
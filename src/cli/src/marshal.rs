@@ -3,7 +3,9 @@ use std::path::{Path, PathBuf};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use silverlib::{
-    RawBitmapType, SectionContent, SectionType, SilverDB, SilverResource, SilverSection,
+    Atlas, AtlasSprite, BitmapImage, DeviceProfile, QuantizeOptions, RawBitmapData,
+    RawBitmapHeader, RawBitmapType, SectionContent, SectionType, SilverDB, SilverResource,
+    SilverResourceID, SilverSection, SymbolTable,
 };
 
 #[derive(Deserialize, Serialize)]
@@ -11,7 +13,26 @@ use silverlib::{
 pub struct SectionMetadata {
     magic: String,
     is_sequential: u32,
-    resources: Vec<SilverResource>,
+    resources: Vec<ResourceEntry>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+/// A resource ID, or a symbol name resolving to one.
+pub enum ResourceKey {
+    Id(u32),
+    Name(String),
+}
+
+#[derive(Deserialize, Serialize)]
+/// Generic resource entry.
+pub struct ResourceEntry {
+    id: ResourceKey,
+    /// The symbol name of this resource, if known.
+    /// This is informative only: `id` determines the resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    contents: SectionContent,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -25,17 +46,47 @@ pub struct BitmapMetadata {
 #[derive(Deserialize, Serialize)]
 /// Bitmap-specific entry metadata.
 pub struct BitmapImageMetadata {
+    /// The ID of this bitmap's resource entry.
+    /// If absent, `resource_id` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<ResourceKey>,
     pub width: u32,
     pub height: u32,
     pub rendered_width: u16,
     pub format_type: RawBitmapType,
     pub resource_id: u32,
+    /// The symbol name of this bitmap, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     pub path: String,
+    /// The raw header this bitmap was extracted with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<RawBitmapHeader>,
+    /// The path to this bitmap's raw pixel data, as extracted.
+    /// Alongside `header`, this permits recreating unmodified bitmaps byte-for-byte.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_path: Option<String>,
 }
 
+#[derive(Deserialize, Serialize)]
+/// Placement of bitmaps within a section's atlas pages.
+pub struct AtlasManifest {
+    /// Paths to every page, relative to the input directory.
+    pub pages: Vec<String>,
+    pub sprites: Vec<AtlasSprite>,
+}
+
+/// The special bitmap path indicating pixels reside within an atlas.
+const ATLAS_PATH: &str = "atlas";
+
 type AnyError = Box<dyn std::error::Error>;
 
-pub fn serialize_contents(database: SilverDB, output_dir: &Path) -> Result<(), AnyError> {
+pub fn serialize_contents(
+    database: SilverDB,
+    output_dir: &Path,
+    symbols: &SymbolTable,
+    atlas_size: Option<u32>,
+) -> Result<(), AnyError> {
     // TODO(spotlightishere): Should we blindly obliterate the output directory?
     if output_dir.exists() {
         fs::remove_dir_all(output_dir)?;
@@ -63,6 +114,30 @@ pub fn serialize_contents(database: SilverDB, output_dir: &Path) -> Result<(), A
                 let section_dir = output_dir.join(section_name.clone());
                 fs::create_dir(section_dir.clone())?;
 
+                // If requested, pack bitmaps into atlas pages instead of individual PNGs.
+                let atlas = match atlas_size {
+                    Some(size) => Some(Atlas::pack(&current_section, size)?),
+                    None => None,
+                };
+                if let Some(atlas) = &atlas {
+                    let mut pages = Vec::new();
+                    for (index, page) in atlas.pages.iter().enumerate() {
+                        page.save(section_dir.join(format!("atlas-{index}.png")))?;
+                        pages.push(format!("{section_name}/atlas-{index}.png"));
+                    }
+                    let manifest = AtlasManifest {
+                        pages,
+                        sprites: atlas.sprites.clone(),
+                    };
+                    let manifest_path = output_dir.join(format!("{section_name}.atlas.yaml"));
+                    fs::write(manifest_path, serde_yaml::to_string(&manifest)?)?;
+                }
+                let is_in_atlas = |id: SilverResourceID| {
+                    atlas
+                        .as_ref()
+                        .is_some_and(|atlas| atlas.sprites.iter().any(|sprite| sprite.id == id))
+                };
+
                 // We'll need to write every bitmap image to disk.
                 for bitmap_entry in current_section.resources {
                     // Ensure we truly have a bitmap representation.
@@ -76,30 +151,59 @@ pub fn serialize_contents(database: SilverDB, output_dir: &Path) -> Result<(), A
                     let resource_id = bitmap_entry.id.0;
                     let Some(entry_contents) = entry_contents else {
                         let empty_metadata = BitmapImageMetadata {
+                            id: Some(ResourceKey::Id(resource_id)),
                             width: 0,
                             height: 0,
                             rendered_width: 0,
                             // This is not actually the format type, as it has none.
                             format_type: RawBitmapType::Rgb565,
                             resource_id,
+                            name: symbols
+                                .name_in(current_section.section_type, bitmap_entry.id)
+                                .map(str::to_string),
                             path: "empty".to_string(),
+                            header: None,
+                            raw_path: None,
                         };
                         bitmap_list.push(empty_metadata);
                         continue;
                     };
 
-                    // Write out our image.
-                    let output_relative = format!("{}/{}.png", section_name, resource_id);
-                    let output_path = section_dir.join(format!("{}.png", resource_id));
-                    fs::write(output_path, entry_contents.contents)?;
+                    // Write out our image, if it has any pixel data.
+                    let output_relative = if entry_contents.contents.is_empty() {
+                        "empty".to_string()
+                    } else if is_in_atlas(bitmap_entry.id) {
+                        ATLAS_PATH.to_string()
+                    } else {
+                        let output_path = section_dir.join(format!("{}.png", resource_id));
+                        fs::write(output_path, &entry_contents.contents)?;
+                        format!("{}/{}.png", section_name, resource_id)
+                    };
+
+                    // Retain its raw pixel data alongside, so that it can be recreated losslessly.
+                    let raw_format = entry_contents.raw;
+                    let raw_relative = match &raw_format {
+                        Some(raw_format) if !raw_format.contents.is_empty() => {
+                            let raw_path = section_dir.join(format!("{}.raw", resource_id));
+                            fs::write(raw_path, &raw_format.contents)?;
+                            Some(format!("{}/{}.raw", section_name, resource_id))
+                        }
+                        _ => None,
+                    };
 
                     let entry_metadata = BitmapImageMetadata {
+                        id: Some(ResourceKey::Id(resource_id)),
                         width: entry_contents.width,
                         height: entry_contents.height,
                         rendered_width: entry_contents.rendered_width,
                         format_type: entry_contents.format_type,
                         resource_id: entry_contents.resource_id,
+                        name: symbols
+                            .name_in(current_section.section_type, bitmap_entry.id)
+                            .map(str::to_string),
                         path: output_relative,
+                        header: raw_format.map(|raw_format| raw_format.header),
+                        raw_path: raw_relative,
                     };
                     bitmap_list.push(entry_metadata);
                 }
@@ -114,10 +218,21 @@ pub fn serialize_contents(database: SilverDB, output_dir: &Path) -> Result<(), A
             }
             // Otherwise, simply write out a raw representation of the section's metadata.
             _ => {
+                let resources = current_section
+                    .resources
+                    .into_iter()
+                    .map(|resource| ResourceEntry {
+                        id: ResourceKey::Id(resource.id.0),
+                        name: symbols
+                            .name_in(current_section.section_type, resource.id)
+                            .map(str::to_string),
+                        contents: resource.contents,
+                    })
+                    .collect();
                 let section_metadata = SectionMetadata {
                     magic: current_section.section_type.to_name(),
                     is_sequential: current_section.is_sequential,
-                    resources: current_section.resources,
+                    resources,
                 };
                 let all_contents = serde_yaml::to_string(&section_metadata)?;
                 fs::write(output_file, all_contents)?;
@@ -145,7 +260,13 @@ where
     Ok(unmarshalled)
 }
 
-pub fn deserialize_contents(input_dir: &Path, database_path: &Path) -> Result<(), AnyError> {
+pub fn deserialize_contents(
+    input_dir: &Path,
+    database_path: &Path,
+    symbols: &SymbolTable,
+    profile: &DeviceProfile,
+    quantize_options: &QuantizeOptions,
+) -> Result<(), AnyError> {
     // First, load section metadata.
     let metadata_path = input_dir.join(Path::new("metadata.yaml"));
     let section_list: Vec<String> = read_yaml(&metadata_path)?;
@@ -156,24 +277,120 @@ pub fn deserialize_contents(input_dir: &Path, database_path: &Path) -> Result<()
         // For every metadata section, parse its respective YAML representation.
         let file_name = format!("{}.yaml", section_name);
         let section_path = input_dir.join(Path::new(&file_name));
+        let section_type = SectionType::from_name(section_name.clone())?;
+
+        if section_type == SectionType::Bitmap || section_type == SectionType::StatusBarBitmap {
+            let bitmap_contents: BitmapMetadata = read_yaml(&section_path)?;
+            let mut resources = Vec::new();
+            for mut entry in bitmap_contents.resources {
+                let id = match entry.id.take() {
+                    Some(key) => resolve_key(key, symbols, &section_name)?,
+                    None => SilverResourceID(entry.resource_id),
+                };
+                resources.push(SilverResource {
+                    id,
+                    contents: SectionContent::Bitmap(read_bitmap(input_dir, entry)?),
+                });
+            }
+
+            let mut section = SilverSection {
+                section_type: SectionType::from_name(bitmap_contents.magic)?,
+                is_sequential: bitmap_contents.is_sequential,
+                resources,
+            };
+
+            // Bitmaps within an atlas have their pixels sliced from its pages.
+            let manifest_path = input_dir.join(format!("{section_name}.atlas.yaml"));
+            if manifest_path.exists() {
+                let manifest: AtlasManifest = read_yaml(&manifest_path)?;
+                let mut pages = Vec::new();
+                for page in manifest.pages {
+                    pages.push(image::open(input_dir.join(page))?.to_rgba8());
+                }
+                let atlas = Atlas {
+                    pages,
+                    sprites: manifest.sprites,
+                };
+                atlas.apply(&mut section)?;
+            }
+
+            all_sections.push(section);
+            continue;
+        }
+
         let section_contents: SectionMetadata = read_yaml(&section_path)?;
         let section_type = SectionType::from_name(section_contents.magic)?;
 
-        // TODO(spotlightishere): Implement
-        if section_type == SectionType::Bitmap || section_type == SectionType::StatusBarBitmap {
-            todo!("deserialization of bitmap contents is not currently implemented");
+        // Resolve any symbol names to their IDs.
+        let mut resources = Vec::new();
+        for entry in section_contents.resources {
+            resources.push(SilverResource {
+                id: resolve_key(entry.id, symbols, &section_name)?,
+                contents: entry.contents,
+            });
         }
 
         let current_section = SilverSection {
             section_type,
             is_sequential: section_contents.is_sequential,
-            resources: section_contents.resources,
+            resources,
         };
         all_sections.push(current_section);
     }
 
     // Finally, write our raw database.
-    let raw_database = SilverDB::write(all_sections)?;
+    let raw_database = SilverDB::write_with_options(all_sections, profile, quantize_options)?;
     fs::write(database_path, raw_database)?;
     Ok(())
 }
+
+/// Resolves a resource ID, or a symbol name to its ID.
+fn resolve_key(
+    key: ResourceKey,
+    symbols: &SymbolTable,
+    section_name: &str,
+) -> Result<SilverResourceID, AnyError> {
+    let id = match key {
+        ResourceKey::Id(id) => SilverResourceID(id),
+        ResourceKey::Name(name) => symbols
+            .resolve_in(SectionType::from_name(section_name.to_string())?, &name)
+            .ok_or_else(|| format!("unknown symbol {name} in {section_name}"))?,
+    };
+    Ok(id)
+}
+
+/// Reads a bitmap image, alongside its raw representation if present.
+fn read_bitmap(
+    input_dir: &Path,
+    entry: BitmapImageMetadata,
+) -> Result<Option<BitmapImage>, AnyError> {
+    // Bitmaps without any data have the special path "empty" and no header.
+    let is_empty = entry.path == "empty";
+    if is_empty && entry.header.is_none() {
+        return Ok(None);
+    }
+
+    // Pixels within an atlas are filled in once its pages are sliced.
+    let contents = match is_empty || entry.path == ATLAS_PATH {
+        true => Vec::new(),
+        false => fs::read(input_dir.join(&entry.path))?,
+    };
+    let raw_contents = match &entry.raw_path {
+        Some(raw_path) => fs::read(input_dir.join(raw_path))?,
+        None => Vec::new(),
+    };
+    let raw = entry.header.map(|header| RawBitmapData {
+        header,
+        contents: raw_contents,
+    });
+
+    Ok(Some(BitmapImage {
+        width: entry.width,
+        height: entry.height,
+        rendered_width: entry.rendered_width,
+        format_type: entry.format_type,
+        resource_id: entry.resource_id,
+        contents,
+        raw,
+    }))
+}
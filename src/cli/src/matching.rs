@@ -0,0 +1,49 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use silverlib::{match_resources, MatchOptions, ResourceMapping, SilverDB, SymbolTable};
+
+type AnyError = Box<dyn std::error::Error>;
+
+/// Matches resources between two databases, writing a mapping file.
+pub fn write_mapping(
+    old_database: &SilverDB,
+    new_database: &SilverDB,
+    output_path: &Path,
+    options: &MatchOptions,
+) -> Result<(), AnyError> {
+    let mapping = match_resources(old_database, new_database, options);
+
+    // Summarize how resources were matched.
+    let mut method_counts = BTreeMap::new();
+    for entry in &mapping.entries {
+        *method_counts
+            .entry(format!("{:?}", entry.method))
+            .or_insert(0) += 1;
+    }
+    for (method, count) in method_counts {
+        println!("{method}: {count}");
+    }
+
+    fs::write(output_path, serde_yaml::to_string(&mapping)?)?;
+    Ok(())
+}
+
+/// Migrates a symbol table through the given mapping file.
+pub fn migrate_symbols(
+    symbols: &SymbolTable,
+    mapping_path: &Path,
+    output_path: &Path,
+) -> Result<(), AnyError> {
+    let mapping: ResourceMapping = serde_yaml::from_str(&fs::read_to_string(mapping_path)?)?;
+    let migration = mapping.migrate_symbols(symbols);
+
+    fs::write(output_path, migration.symbols.to_file_contents())?;
+    println!("Migrated {} symbols.", migration.symbols.len());
+    for name in migration.unmigrated {
+        println!("\t- {name} has no matching resource");
+    }
+    for conflict in migration.conflicts {
+        println!("\t- Conflict: {conflict}");
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use silverlib::{SilverDB, SilverDBFormat, SilverError};
+use silverlib::{DeviceProfile, SilverDB, SilverDBFormat, SilverError, SymbolTable};
 use std::{fs, io::Cursor, path::Path};
 
 use crate::marshal;
@@ -11,7 +11,12 @@ pub struct ScrapeMetadata {
 
 type AnyError = Box<dyn std::error::Error>;
 
-pub fn handle_scrape(firmware_path: &Path, output_dir: &Path) -> Result<(), AnyError> {
+pub fn handle_scrape(
+    firmware_path: &Path,
+    output_dir: &Path,
+    symbols: &SymbolTable,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
     // TODO(spotlightishere): We really should not obliterate the output directory.
     if output_dir.exists() {
         fs::remove_dir_all(output_dir)?;
@@ -44,7 +49,7 @@ pub fn handle_scrape(firmware_path: &Path, output_dir: &Path) -> Result<(), AnyE
 
         // Try to load only the database header for validation.
         let header_cursor = Cursor::new(Vec::from(smaller_firmware));
-        _ = match SilverDBFormat::read(header_cursor) {
+        _ = match SilverDBFormat::read_with_profile(header_cursor, profile) {
             // This isn't a valid SilverDB.
             Err(SilverError::InvalidHeader) => continue,
             Err(e) => panic!("failed to parse scraped SilverDB: {}", e),
@@ -55,7 +60,7 @@ pub fn handle_scrape(firmware_path: &Path, output_dir: &Path) -> Result<(), AnyE
 
         // Attempt to load this as a database.
         let firmware_cursor = Cursor::new(Vec::from(smaller_firmware));
-        let database = match SilverDB::read_cursor(firmware_cursor) {
+        let database = match SilverDB::read_cursor_with_profile(firmware_cursor, profile) {
             // This isn't a valid SilverDB.
             Err(SilverError::InvalidHeader) => continue,
             Err(e) => panic!("failed to parse scraped SilverDB: {}", e),
@@ -85,7 +90,7 @@ pub fn handle_scrape(firmware_path: &Path, output_dir: &Path) -> Result<(), AnyE
         let offset_dir = format!("{offset_string}/");
         let output_dir = output_dir.join(offset_dir);
 
-        marshal::serialize_contents(database_metadata.database, &output_dir)?;
+        marshal::serialize_contents(database_metadata.database, &output_dir, symbols, None)?;
     }
 
     Ok(())
@@ -0,0 +1,244 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use clap::ValueEnum;
+use silverlib::{
+    apply_translations, check_translation_ids, decode_strings_file, encode_strings_file,
+    export_apple_strings, export_po, export_spreadsheet, export_xliff, import_apple_strings,
+    import_po, import_xliff, parse_spreadsheet, placeholder_names, translation_units,
+    DeviceProfile, LocaleSet, LocaleStatus, StringsFileEncoding, TranslationUnit, XliffVersion,
+};
+
+type AnyError = Box<dyn std::error::Error>;
+
+/// Formats strings can be exchanged with translation tools in.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StringsFormat {
+    /// gettext PO
+    Po,
+    /// XLIFF 1.2
+    Xliff12,
+    /// XLIFF 2.0
+    Xliff20,
+    /// Apple .strings, encoded as UTF-16
+    Strings,
+    /// Apple .strings, encoded as UTF-8
+    StringsUtf8,
+    /// A single comma-separated spreadsheet holding all locales
+    Csv,
+    /// A single tab-separated spreadsheet holding all locales
+    Tsv,
+}
+
+/// Formats holding the strings of a single locale per file.
+#[derive(Clone, Copy)]
+enum LocaleFormat {
+    Po,
+    Xliff(XliffVersion),
+    AppleStrings(StringsFileEncoding),
+}
+
+/// How strings are laid out across files.
+enum StringsLayout {
+    /// One file per locale, named after its locale code.
+    PerLocale(LocaleFormat),
+    /// A single spreadsheet holding all locales, separated by the given delimiter.
+    Spreadsheet(u8),
+}
+
+impl StringsFormat {
+    /// The file extension used for files of this format.
+    fn extension(&self) -> &'static str {
+        match self {
+            StringsFormat::Po => "po",
+            StringsFormat::Xliff12 | StringsFormat::Xliff20 => "xlf",
+            StringsFormat::Strings | StringsFormat::StringsUtf8 => "strings",
+            StringsFormat::Csv => "csv",
+            StringsFormat::Tsv => "tsv",
+        }
+    }
+
+    /// How files of this format are laid out.
+    fn layout(&self) -> StringsLayout {
+        match self {
+            StringsFormat::Po => StringsLayout::PerLocale(LocaleFormat::Po),
+            StringsFormat::Xliff12 => {
+                StringsLayout::PerLocale(LocaleFormat::Xliff(XliffVersion::V1_2))
+            }
+            StringsFormat::Xliff20 => {
+                StringsLayout::PerLocale(LocaleFormat::Xliff(XliffVersion::V2_0))
+            }
+            StringsFormat::Strings => {
+                StringsLayout::PerLocale(LocaleFormat::AppleStrings(StringsFileEncoding::Utf16))
+            }
+            StringsFormat::StringsUtf8 => {
+                StringsLayout::PerLocale(LocaleFormat::AppleStrings(StringsFileEncoding::Utf8))
+            }
+            StringsFormat::Csv => StringsLayout::Spreadsheet(b','),
+            StringsFormat::Tsv => StringsLayout::Spreadsheet(b'\t'),
+        }
+    }
+}
+
+/// The file name spreadsheets are exported to, and imported from.
+fn spreadsheet_name(format: StringsFormat) -> String {
+    format!("strings.{}", format.extension())
+}
+
+/// Exports strings of every locale database within a directory, one file per locale.
+/// Strings a locale's status file marks as untranslated are exported without a translation.
+pub fn export_strings(
+    locale_dir: &Path,
+    output_dir: &Path,
+    format: StringsFormat,
+    reference: &str,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    let locale_set = LocaleSet::load_with_profile(locale_dir, profile)?;
+    let reference_database = locale_set.get(reference)?;
+    let names = placeholder_names(reference_database);
+    fs::create_dir_all(output_dir)?;
+
+    // Spreadsheets hold all locales within a single file.
+    let locale_format = match format.layout() {
+        StringsLayout::PerLocale(locale_format) => locale_format,
+        StringsLayout::Spreadsheet(delimiter) => {
+            let contents = export_spreadsheet(&locale_set, reference, delimiter)?;
+            fs::write(output_dir.join(spreadsheet_name(format)), contents)?;
+            println!("Exported {} locales.", locale_set.locales.len());
+            return Ok(());
+        }
+    };
+
+    for (locale_code, database) in &locale_set.locales {
+        if locale_code == reference {
+            continue;
+        }
+
+        let mut units = translation_units(reference_database, database);
+        if let Some(status) = read_status(locale_dir, locale_code)? {
+            status.mask_untranslated(&mut units);
+        }
+        let contents = match locale_format {
+            LocaleFormat::Po => export_po(&units, locale_code).into_bytes(),
+            LocaleFormat::Xliff(version) => {
+                export_xliff(&units, reference, locale_code, version).into_bytes()
+            }
+            LocaleFormat::AppleStrings(encoding) => {
+                encode_strings_file(&export_apple_strings(&units, &names), encoding)
+            }
+        };
+
+        // e.g. "./output/ja_JP.po"
+        let file_name = format!("{}.{}", locale_code, format.extension());
+        fs::write(output_dir.join(file_name), contents)?;
+        println!("Exported {} strings for {}.", units.len(), locale_code);
+    }
+
+    Ok(())
+}
+
+/// Parses translations from the per-locale files within a directory, keyed by locale code.
+/// Locales lacking a file are omitted.
+fn read_locale_files(
+    locale_set: &LocaleSet,
+    input_dir: &Path,
+    format: StringsFormat,
+    locale_format: LocaleFormat,
+    reference: &str,
+) -> Result<BTreeMap<String, Vec<TranslationUnit>>, AnyError> {
+    let mut all_units = BTreeMap::new();
+    for locale_code in locale_set.locales.keys() {
+        let file_name = format!("{}.{}", locale_code, format.extension());
+        let input_path = input_dir.join(file_name);
+        if !input_path.exists() {
+            continue;
+        }
+
+        let raw_contents = fs::read(input_path)?;
+        let units = match locale_format {
+            LocaleFormat::Po => import_po(&String::from_utf8(raw_contents)?)?,
+            LocaleFormat::Xliff(_) => import_xliff(&String::from_utf8(raw_contents)?)?,
+            // Only .strings files without resource ID comments require our reference.
+            // Their encoding is detected, regardless of the format given.
+            LocaleFormat::AppleStrings(_) => import_apple_strings(
+                &decode_strings_file(&raw_contents)?,
+                locale_set.get(reference)?,
+            )?,
+        };
+        // Stale or foreign files may hold strings our reference lacks.
+        check_translation_ids(locale_set.get(reference)?, &units)
+            .map_err(|e| format!("{locale_code}: {e}"))?;
+        all_units.insert(locale_code.clone(), units);
+    }
+    Ok(all_units)
+}
+
+/// Reads a locale's status file, as created by `new-locale`, if present.
+fn read_status(locale_dir: &Path, locale_code: &str) -> Result<Option<LocaleStatus>, AnyError> {
+    let status_path = locale_dir.join(LocaleStatus::file_name(locale_code));
+    if !status_path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_yaml::from_str(&fs::read_to_string(
+        status_path,
+    )?)?))
+}
+
+/// Marks imported strings as translated within a locale's status file.
+/// Locales without a status file are left as-is.
+fn update_status(
+    locale_dir: &Path,
+    locale_code: &str,
+    units: &[TranslationUnit],
+) -> Result<(), AnyError> {
+    let Some(mut status) = read_status(locale_dir, locale_code)? else {
+        return Ok(());
+    };
+    if status.record(units) > 0 {
+        let status_path = locale_dir.join(LocaleStatus::file_name(locale_code));
+        fs::write(status_path, serde_yaml::to_string(&status)?)?;
+    }
+    println!(
+        "\t{} strings remain untranslated for {}.",
+        status.untranslated_count(),
+        locale_code
+    );
+    Ok(())
+}
+
+/// Imports strings to every locale database within a directory,
+/// from files named similarly to those created by `export_strings`.
+/// Status files of locales are updated to reflect their newly translated strings.
+pub fn import_strings(
+    locale_dir: &Path,
+    input_dir: &Path,
+    format: StringsFormat,
+    reference: &str,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    let mut locale_set = LocaleSet::load_with_profile(locale_dir, profile)?;
+
+    // Parse all translations prior to modifying any database.
+    let all_units = match format.layout() {
+        StringsLayout::PerLocale(locale_format) => {
+            read_locale_files(&locale_set, input_dir, format, locale_format, reference)?
+        }
+        StringsLayout::Spreadsheet(delimiter) => {
+            let contents = fs::read_to_string(input_dir.join(spreadsheet_name(format)))?;
+            parse_spreadsheet(&locale_set, reference, &contents, delimiter)?
+        }
+    };
+
+    let reference_database = locale_set.get(reference)?.clone();
+    for (locale_code, units) in all_units {
+        let Some(database) = locale_set.locales.get_mut(&locale_code) else {
+            continue;
+        };
+        let updated = apply_translations(database, &reference_database, &units)?;
+        println!("Updated {} strings for {}.", updated, locale_code);
+        update_status(locale_dir, &locale_code, &units)?;
+    }
+
+    locale_set.write_with_profile(locale_dir, profile)?;
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use std::{fs, path::Path};
+
+use silverlib::{DeviceProfile, QuantizeOptions, SilverDB, SymbolTable, Theme};
+
+use crate::open_database;
+
+type AnyError = Box<dyn std::error::Error>;
+
+/// Replaces bitmaps within the database at the given path with PNGs from a directory,
+/// reporting any images that could not be applied.
+pub fn apply_theme(
+    database_path: &Path,
+    theme_dir: &Path,
+    output_path: &Path,
+    symbols: &SymbolTable,
+    quantize_options: &QuantizeOptions,
+    profile: &DeviceProfile,
+) -> Result<(), AnyError> {
+    let mut database = open_database(database_path.to_path_buf(), profile);
+    let theme = Theme::read_dir(theme_dir)?;
+    let report = theme.apply(&mut database, symbols, quantize_options, profile);
+
+    for (name, skip) in &report.skipped {
+        println!("Skipped {name}: {skip}");
+    }
+
+    let raw_database = SilverDB::write_with_profile(database.sections, profile)?;
+    fs::write(output_path, raw_database)?;
+    println!(
+        "Applied {} images, skipped {}.",
+        report.applied.len(),
+        report.skipped.len()
+    );
+    Ok(())
+}
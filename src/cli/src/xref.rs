@@ -0,0 +1,89 @@
+use silverlib::{
+    ReferenceConfidence, ReferenceGraph, ResourceLocation, ResourceReference, SectionType,
+    SilverDB, SilverResourceID, SymbolTable,
+};
+
+type AnyError = Box<dyn std::error::Error>;
+
+/// Describes a reference's confidence, if it is not high.
+fn describe_confidence(reference: &ResourceReference) -> &'static str {
+    match reference.confidence {
+        ReferenceConfidence::High => "",
+        ReferenceConfidence::Low => ", low confidence",
+    }
+}
+
+/// Prints all resources referencing, or referenced by, the given resource ID
+/// within the given section, or within every section it is present within.
+///
+/// References to `Str ` resources are only matched if `include_strings` is set.
+pub fn print_xref(
+    database: SilverDB,
+    resource_id: SilverResourceID,
+    section: Option<&str>,
+    include_strings: bool,
+    symbols: &SymbolTable,
+) -> Result<(), AnyError> {
+    let section_type = section
+        .map(|name| SectionType::from_name(name.to_string()))
+        .transpose()?;
+
+    // Determine which sections this resource is present within.
+    let locations: Vec<ResourceLocation> = database
+        .sections
+        .iter()
+        .filter(|section| {
+            section_type.is_none_or(|section_type| section.section_type == section_type)
+        })
+        .filter(|section| section.resources.iter().any(|r| r.id == resource_id))
+        .map(|section| ResourceLocation {
+            section_type: section.section_type,
+            id: resource_id,
+        })
+        .collect();
+    if locations.is_empty() {
+        println!(
+            "Resource {} is not present within this database.",
+            symbols.describe(resource_id)
+        );
+        return Ok(());
+    }
+
+    let graph = match include_strings {
+        true => ReferenceGraph::build_including_strings(&database),
+        false => ReferenceGraph::build(&database),
+    };
+
+    for location in locations {
+        println!(
+            "Resource {} ({})",
+            symbols.describe_in(location.section_type, resource_id),
+            location.section_type.to_name()
+        );
+
+        let outgoing: Vec<_> = graph.outgoing(location).collect();
+        println!("Outgoing references ({}):", outgoing.len());
+        for reference in outgoing {
+            println!(
+                "\t- {} {} (at offset 0x{:x}{})",
+                reference.target.section_type.to_name(),
+                symbols.describe_in(reference.target.section_type, reference.target.id),
+                reference.offset,
+                describe_confidence(reference)
+            );
+        }
+
+        let incoming: Vec<_> = graph.incoming(location).collect();
+        println!("Incoming references ({}):", incoming.len());
+        for reference in incoming {
+            println!(
+                "\t- {} {} (at offset 0x{:x}{})",
+                reference.source.section_type.to_name(),
+                symbols.describe_in(reference.source.section_type, reference.source.id),
+                reference.offset,
+                describe_confidence(reference)
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,15 @@
 use serde::{Deserialize, Serialize};
-use std::{fmt, io::Cursor};
+use std::{
+    fmt, fs,
+    io::Cursor,
+    num::ParseIntError,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use crate::{
+    bitmap::QuantizeOptions,
+    device::DeviceProfile,
     format::SilverDBFormat,
     format::{ResourceMetadata, SectionHeader, SilverDBHeader},
     section_content::SectionContent,
@@ -10,12 +18,14 @@ use crate::{
 };
 
 /// A high-level representation of a SilverDB file.
+#[derive(Clone)]
 pub struct SilverDB {
     /// Available sections within this database.
     pub sections: Vec<SilverSection>,
 }
 
 /// A high-level representation of section contents.
+#[derive(Clone)]
 pub struct SilverSection {
     /// The magic identifying this section (i.e. 'Str ', 'BMap', 'LDTm', etc.)
     pub section_type: SectionType,
@@ -30,7 +40,7 @@ pub struct SilverSection {
 
 /// The ID identifying this resource.
 /// In general you should never modify the ID as it may be hardcoded in firmware.
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
 pub struct SilverResourceID(pub u32);
 
 impl fmt::Display for SilverResourceID {
@@ -39,6 +49,19 @@ impl fmt::Display for SilverResourceID {
     }
 }
 
+/// Parses an ID from either its hexadecimal form (i.e. `0x0dad06d8`) or a decimal value.
+impl FromStr for SilverResourceID {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex_value) => u32::from_str_radix(hex_value, 16)?,
+            None => s.parse()?,
+        };
+        Ok(SilverResourceID(value))
+    }
+}
+
 impl From<u32> for SilverResourceID {
     fn from(value: u32) -> Self {
         SilverResourceID(value)
@@ -46,7 +69,7 @@ impl From<u32> for SilverResourceID {
 }
 
 /// A high-level representation of resources within a section.
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct SilverResource {
     /// An ID used to identify this resources. For example, 0x0dad06d8.
     pub id: SilverResourceID,
@@ -56,14 +79,37 @@ pub struct SilverResource {
 }
 
 impl SilverDB {
+    /// Returns the first section of the given type, if present.
+    pub fn find_section(&self, section_type: &SectionType) -> Option<&SilverSection> {
+        self.sections
+            .iter()
+            .find(|section| &section.section_type == section_type)
+    }
+
     pub fn read(file_contents: Vec<u8>) -> Result<Self, SilverError> {
+        SilverDB::read_with_profile(file_contents, &DeviceProfile::generic())
+    }
+
+    /// Reads a database using the rules of the given device profile.
+    pub fn read_with_profile(
+        file_contents: Vec<u8>,
+        profile: &DeviceProfile,
+    ) -> Result<Self, SilverError> {
         let reader = Cursor::new(file_contents);
-        SilverDB::read_cursor(reader)
+        SilverDB::read_cursor_with_profile(reader, profile)
     }
 
     pub fn read_cursor(reader: Cursor<Vec<u8>>) -> Result<Self, SilverError> {
+        SilverDB::read_cursor_with_profile(reader, &DeviceProfile::generic())
+    }
+
+    /// Reads a database from the given cursor using the rules of the given device profile.
+    pub fn read_cursor_with_profile(
+        reader: Cursor<Vec<u8>>,
+        profile: &DeviceProfile,
+    ) -> Result<Self, SilverError> {
         // First, parse the actual file.
-        let database_file = SilverDBFormat::read(reader)?;
+        let database_file = SilverDBFormat::read_with_profile(reader, profile)?;
 
         // Next, create the high-level representation.
         let mut sections: Vec<SilverSection> = Vec::new();
@@ -73,7 +119,11 @@ impl SilverDB {
 
             for raw_resource in raw_section.resources {
                 // TODO(spotlightishere): Have section contents parsed accordingly
-                let contents = SectionContent::parse_section(&section_type, raw_resource.contents)?;
+                let contents = SectionContent::parse_section_with_profile(
+                    &section_type,
+                    raw_resource.contents,
+                    profile,
+                )?;
 
                 resources.push(SilverResource {
                     id: SilverResourceID(raw_resource.id),
@@ -93,6 +143,24 @@ impl SilverDB {
     }
 
     pub fn write(all_sections: Vec<SilverSection>) -> Result<Vec<u8>, SilverError> {
+        SilverDB::write_with_profile(all_sections, &DeviceProfile::generic())
+    }
+
+    /// Writes a database using the rules of the given device profile.
+    pub fn write_with_profile(
+        all_sections: Vec<SilverSection>,
+        profile: &DeviceProfile,
+    ) -> Result<Vec<u8>, SilverError> {
+        SilverDB::write_with_options(all_sections, profile, &QuantizeOptions::default())
+    }
+
+    /// Writes a database using the rules of the given device profile,
+    /// quantizing changed bitmaps with the given options.
+    pub fn write_with_options(
+        all_sections: Vec<SilverSection>,
+        profile: &DeviceProfile,
+        quantize_options: &QuantizeOptions,
+    ) -> Result<Vec<u8>, SilverError> {
         // First, we need to reduce the high-level representations to their binary formats.
         let mut raw_sections: Vec<SectionHeader> = Vec::new();
 
@@ -101,7 +169,11 @@ impl SilverDB {
             let mut all_resources: Vec<ResourceMetadata> = Vec::new();
             for current_resource in current_section.resources {
                 // We reduce this section back to its raw, Vec<u8> form.
-                let raw_resource = SectionContent::reduce_section(current_resource.contents)?;
+                let raw_resource = SectionContent::reduce_section_with_options(
+                    current_resource.contents,
+                    profile,
+                    quantize_options,
+                )?;
 
                 let resource = ResourceMetadata {
                     id: current_resource.id.0,
@@ -139,7 +211,23 @@ impl SilverDB {
             sections: raw_sections,
         };
 
-        let raw_contents = mock_database.write()?;
+        let raw_contents = mock_database.write_with_profile(profile)?;
         Ok(raw_contents)
     }
 }
+
+/// Recursively collects the paths of all databases (`*.bin`) within the given directory,
+/// sorted by path.
+pub(crate) fn find_database_files(directory: &Path) -> Result<Vec<PathBuf>, SilverError> {
+    let mut database_paths = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            database_paths.extend(find_database_files(&path)?);
+        } else if path.extension().is_some_and(|extension| extension == "bin") {
+            database_paths.push(path);
+        }
+    }
+    database_paths.sort();
+    Ok(database_paths)
+}
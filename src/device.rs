@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+use crate::{
+    bitmap::{BitmapIssue, RawBitmapType},
+    database::{SilverDB, SilverResourceID},
+    format::SectionMagic,
+    section_content::SectionContent,
+    silver_error::SilverError,
+};
+
+/// Devices with known SilverDB quirks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Device {
+    /// iPod nano (5th generation).
+    Nano5G,
+    /// iPod nano (6th generation).
+    Nano6G,
+    /// iPod nano (7th generation).
+    Nano7G,
+    /// iPod classic.
+    Classic,
+}
+
+impl Device {
+    /// All known devices.
+    pub const ALL: [Device; 4] = [
+        Device::Nano5G,
+        Device::Nano6G,
+        Device::Nano7G,
+        Device::Classic,
+    ];
+
+    /// The short name of this device, as accepted by `Device::from_str`.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Device::Nano5G => "nano5g",
+            Device::Nano6G => "nano6g",
+            Device::Nano7G => "nano7g",
+            Device::Classic => "classic",
+        }
+    }
+}
+
+impl FromStr for Device {
+    type Err = SilverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Device::ALL
+            .into_iter()
+            .find(|device| device.short_name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| SilverError::UnknownDevice(s.to_string()))
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.short_name())
+    }
+}
+
+/// 'SRVL' (BE) or 'LVRS' (LE)
+const SRVL_MAGIC: SectionMagic = [0x4C, 0x56, 0x52, 0x53];
+
+/// The largest header length considered valid when a device's own limit is unknown.
+///
+/// The header length can get very long: firmware 1.1.2 for the
+/// iPod nano 7th gen has a 136,912 byte long header.
+/// However, for sanity, we should likely never see it exceed 256 kilobytes.
+pub const DEFAULT_MAX_HEADER_LENGTH: u32 = 256 * 1024;
+
+/// The largest amount of sections considered valid when a device's own limit is unknown.
+///
+/// Official firmware imposes no limit on the amount of sections in a database.
+/// However, we can assume no iPod will ever have more than 128 sections.
+pub const DEFAULT_MAX_SECTION_COUNT: u32 = 128;
+
+/// Per-device rules for reading, writing and validating databases.
+#[derive(Clone, Debug)]
+pub struct DeviceProfile {
+    /// The device this profile describes, or `None` for a generic profile.
+    pub device: Option<Device>,
+    /// Whether bitmap headers contain a resource ID field.
+    /// When `None`, its presence is inferred from the field's value.
+    pub bitmap_resource_id: Option<bool>,
+    /// Sections requiring four additional bytes of padding after their resource data.
+    pub padded_sections: Vec<SectionMagic>,
+    /// The largest header length this device accepts, if known.
+    /// When `None`, `DEFAULT_MAX_HEADER_LENGTH` applies.
+    pub max_header_length: Option<u32>,
+    /// The largest amount of sections this device accepts, if known.
+    /// When `None`, `DEFAULT_MAX_SECTION_COUNT` applies.
+    pub max_section_count: Option<u32>,
+    /// The screen's width and height in pixels, if known.
+    pub screen_size: Option<(u32, u32)>,
+    /// Bitmap formats this device is expected to use, if known.
+    /// When `None`, any format is accepted.
+    pub bitmap_formats: Option<Vec<RawBitmapType>>,
+}
+
+impl DeviceProfile {
+    /// A profile suitable for any device, inferring device-specific behavior where possible.
+    pub fn generic() -> Self {
+        DeviceProfile {
+            device: None,
+            bitmap_resource_id: None,
+            padded_sections: vec![SRVL_MAGIC],
+            max_header_length: None,
+            max_section_count: None,
+            screen_size: None,
+            bitmap_formats: None,
+        }
+    }
+
+    /// The profile for the given device.
+    ///
+    /// Limits and bitmap formats have not yet been surveyed across firmware
+    /// for any device, so they remain unknown.
+    pub fn for_device(device: Device) -> Self {
+        let unknown = DeviceProfile::generic();
+        match device {
+            Device::Nano5G => DeviceProfile {
+                device: Some(device),
+                bitmap_resource_id: Some(true),
+                // As observed by the internal "SRVL" section in firmware 1.0.2.
+                padded_sections: vec![SRVL_MAGIC],
+                screen_size: Some((240, 376)),
+                ..unknown
+            },
+            Device::Nano6G => DeviceProfile {
+                device: Some(device),
+                bitmap_resource_id: Some(true),
+                padded_sections: Vec::new(),
+                screen_size: Some((240, 240)),
+                ..unknown
+            },
+            Device::Nano7G => DeviceProfile {
+                device: Some(device),
+                bitmap_resource_id: Some(true),
+                padded_sections: Vec::new(),
+                screen_size: Some((240, 432)),
+                ..unknown
+            },
+            Device::Classic => DeviceProfile {
+                device: Some(device),
+                // Bitmap headers on the iPod classic lack a resource ID.
+                bitmap_resource_id: Some(false),
+                padded_sections: Vec::new(),
+                screen_size: Some((320, 240)),
+                ..unknown
+            },
+        }
+    }
+
+    /// Whether resource data within the given section requires additional padding.
+    pub fn requires_padding(&self, magic: SectionMagic) -> bool {
+        self.padded_sections.contains(&magic)
+    }
+
+    /// The largest header length considered valid for this device.
+    pub fn header_length_limit(&self) -> u32 {
+        self.max_header_length.unwrap_or(DEFAULT_MAX_HEADER_LENGTH)
+    }
+
+    /// The largest amount of sections considered valid for this device.
+    pub fn section_count_limit(&self) -> u32 {
+        self.max_section_count.unwrap_or(DEFAULT_MAX_SECTION_COUNT)
+    }
+
+    /// Checks that bitmaps within the given database are well-formed and suitable for this device.
+    pub fn check(&self, database: &SilverDB) -> Vec<ProfileIssue> {
+        let mut issues = Vec::new();
+        for section in &database.sections {
+            for resource in &section.resources {
+                let SectionContent::Bitmap(Some(bitmap)) = &resource.contents else {
+                    continue;
+                };
+
+                if let Some(raw) = &bitmap.raw {
+                    for issue in raw.validate() {
+                        issues.push(ProfileIssue::MalformedBitmap {
+                            id: resource.id,
+                            issue,
+                        });
+                    }
+                }
+                let is_expected_format = self
+                    .bitmap_formats
+                    .as_ref()
+                    .is_none_or(|formats| formats.contains(&bitmap.format_type));
+                if !is_expected_format {
+                    issues.push(ProfileIssue::UnexpectedBitmapFormat {
+                        id: resource.id,
+                        format: bitmap.format_type,
+                    });
+                }
+                if let Some((screen_width, screen_height)) = self.screen_size {
+                    let longest_edge = screen_width.max(screen_height);
+                    if bitmap.width > longest_edge || bitmap.height > longest_edge {
+                        issues.push(ProfileIssue::OversizedBitmap {
+                            id: resource.id,
+                            width: bitmap.width,
+                            height: bitmap.height,
+                        });
+                    }
+                }
+            }
+        }
+        issues
+    }
+}
+
+impl Default for DeviceProfile {
+    fn default() -> Self {
+        DeviceProfile::generic()
+    }
+}
+
+/// Problems found when checking a database against a device profile.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProfileIssue {
+    /// A bitmap uses a format this device is not expected to use.
+    UnexpectedBitmapFormat {
+        id: SilverResourceID,
+        format: RawBitmapType,
+    },
+    /// A bitmap is larger than this device's screen in either orientation.
+    OversizedBitmap {
+        id: SilverResourceID,
+        width: u32,
+        height: u32,
+    },
+    /// A bitmap's raw data is inconsistent with its header.
+    MalformedBitmap {
+        id: SilverResourceID,
+        issue: BitmapIssue,
+    },
+}
+
+impl fmt::Display for ProfileIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileIssue::UnexpectedBitmapFormat { id, format } => {
+                write!(f, "{id}: unexpected bitmap format {format:?}")
+            }
+            ProfileIssue::OversizedBitmap { id, width, height } => {
+                write!(f, "{id}: bitmap of {width}x{height} exceeds screen size")
+            }
+            ProfileIssue::MalformedBitmap { id, issue } => write!(f, "{id}: {issue}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitmap::BitmapImage,
+        database::{SilverResource, SilverSection},
+        section_types::SectionType,
+    };
+
+    fn database(format_type: RawBitmapType) -> SilverDB {
+        SilverDB {
+            sections: vec![SilverSection {
+                section_type: SectionType::from_name("BMap".to_string()).unwrap(),
+                is_sequential: 0,
+                resources: vec![SilverResource {
+                    id: SilverResourceID(0x0dad0001),
+                    contents: SectionContent::Bitmap(Some(BitmapImage {
+                        width: 2,
+                        height: 2,
+                        rendered_width: 0,
+                        format_type,
+                        resource_id: 0x0dad0001,
+                        contents: Vec::new(),
+                        raw: None,
+                    })),
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn reports_unexpected_bitmap_formats() {
+        let database = database(RawBitmapType::Argb4444);
+        assert!(DeviceProfile::generic().check(&database).is_empty());
+
+        let profile = DeviceProfile {
+            bitmap_formats: Some(vec![RawBitmapType::Rgb565]),
+            ..DeviceProfile::generic()
+        };
+        assert_eq!(
+            profile.check(&database),
+            vec![ProfileIssue::UnexpectedBitmapFormat {
+                id: SilverResourceID(0x0dad0001),
+                format: RawBitmapType::Argb4444,
+            }]
+        );
+    }
+
+    #[test]
+    fn applies_header_limits() {
+        let sections = vec![SilverSection {
+            section_type: SectionType::String,
+            is_sequential: 1,
+            resources: vec![SilverResource {
+                id: SilverResourceID(1),
+                contents: SectionContent::String("Music".into()),
+            }],
+        }];
+        let raw_database = SilverDB::write(sections.clone()).unwrap();
+
+        let profile = DeviceProfile {
+            max_header_length: Some(16),
+            ..DeviceProfile::generic()
+        };
+        assert!(matches!(
+            SilverDB::read_with_profile(raw_database, &profile),
+            Err(SilverError::InvalidHeader)
+        ));
+        assert!(matches!(
+            SilverDB::write_with_profile(sections.clone(), &profile),
+            Err(SilverError::DeviceLimitExceeded(_))
+        ));
+
+        let profile = DeviceProfile {
+            max_section_count: Some(0),
+            ..DeviceProfile::generic()
+        };
+        assert!(matches!(
+            SilverDB::write_with_profile(sections, &profile),
+            Err(SilverError::DeviceLimitExceeded(_))
+        ));
+    }
+}
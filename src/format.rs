@@ -1,6 +1,6 @@
 use std::io::Cursor;
 
-use crate::{little_helper::LittleHelper, silver_error::SilverError};
+use crate::{device::DeviceProfile, little_helper::LittleHelper, silver_error::SilverError};
 
 /// Simple function to determine whether the current byte is printable ASCII.
 fn is_ascii(current_byte: u8) -> bool {
@@ -64,6 +64,14 @@ pub struct ResourceMetadata {
 impl SilverDBFormat {
     /// Reads a SilverDB-format file, returning a representation of its contents.
     pub fn read(raw_reader: Cursor<Vec<u8>>) -> Result<Self, SilverError> {
+        SilverDBFormat::read_with_profile(raw_reader, &DeviceProfile::generic())
+    }
+
+    /// Reads a SilverDB-format file, applying the limits of the given device profile.
+    pub fn read_with_profile(
+        raw_reader: Cursor<Vec<u8>>,
+        profile: &DeviceProfile,
+    ) -> Result<Self, SilverError> {
         let mut reader = LittleHelper(raw_reader);
         let reader_length = reader.len()?;
 
@@ -88,23 +96,15 @@ impl SilverDBFormat {
             return Err(SilverError::InvalidHeader);
         }
 
-        // Next, let's validate our header's length.
-        // The header length can get very long: firmware 1.1.2 for the
-        // iPod nano 7th gen has a 136,912 byte long header.
-        //
-        // However, for sanity, we should likely never see it exceed 256 kilobytes.
-        let max_header_size = 256 * 1024;
+        // Next, let's validate our header's length against our profile.
         let read_header_length = db_header.header_length;
-        if read_header_length == 0 || read_header_length > max_header_size {
+        if read_header_length == 0 || read_header_length > profile.header_length_limit() {
             return Err(SilverError::InvalidHeader);
         }
 
-        // We'll then validate section count. Official firmware imposes
-        // no limit on the amount of sections in a database by official firmware.
-        // However, we can assume no iPod will ever have more than 128 sections.
-        let max_section_count = 128;
+        // We'll then validate section count, similarly per our profile.
         let read_section_count = db_header.section_count;
-        if read_section_count == 0 || read_section_count > max_section_count {
+        if read_section_count == 0 || read_section_count > profile.section_count_limit() {
             return Err(SilverError::InvalidHeader);
         }
 
@@ -199,6 +199,21 @@ impl SilverDBFormat {
 
     /// Writes a representation of SilverDB contents to its binary format.
     pub fn write(&self) -> Result<Vec<u8>, SilverError> {
+        self.write_with_profile(&DeviceProfile::generic())
+    }
+
+    /// Writes a representation of SilverDB contents to its binary format,
+    /// applying the padding rules and limits of the given device profile.
+    pub fn write_with_profile(&self, profile: &DeviceProfile) -> Result<Vec<u8>, SilverError> {
+        // Databases this device would not read are not worth writing.
+        let section_count_limit = profile.section_count_limit();
+        if self.header.section_count > section_count_limit {
+            return Err(SilverError::DeviceLimitExceeded(format!(
+                "{} sections exceed the limit of {section_count_limit}",
+                self.header.section_count
+            )));
+        }
+
         // We'll have three writers: for our header/section metadata contents,
         // for our resource metadata, and for the raw resource data itself.
         let mut header_writer = LittleHelper::new();
@@ -249,11 +264,8 @@ impl SilverDBFormat {
             raw_data_writer.write_padding(current_raw_data_offset)?;
 
             // TODO(spotlightishere): It appears some sections require more than 4 bytes of alignment. Why?
-            // As observed by the internal "SRVL" section in firmware 1.0.2 of the iPod nano 5th generation,
-            // it has an extra four bytes of padding.
-            //
-            // We'll manually handle this.
-            if current_section.magic == [0x4C, 0x56, 0x52, 0x53] {
+            // Our device profile specifies which sections require an extra four bytes of padding.
+            if profile.requires_padding(current_section.magic) {
                 raw_data_writer.write_u32_le(0)?;
             }
         }
@@ -263,6 +275,12 @@ impl SilverDBFormat {
         let resource_metadata_length = raw_resource_metadata.len() as u32;
         // The total header length is the raw header/ plus raw resource metadata sizes.
         let total_header_length = header_writer.pos_as_u32() + resource_metadata_length;
+        let header_length_limit = profile.header_length_limit();
+        if total_header_length > header_length_limit {
+            return Err(SilverError::DeviceLimitExceeded(format!(
+                "header of {total_header_length} bytes exceeds the limit of {header_length_limit}"
+            )));
+        }
         header_writer.seek_to_u32(4);
         header_writer.write_u32_le(total_header_length)?;
 
@@ -1,14 +1,46 @@
 mod bitmap;
+mod catalog;
 mod database;
+mod device;
 mod format;
 mod little_helper;
+mod localization;
+mod matching;
+mod query;
 mod section_content;
 mod section_types;
 mod silver_error;
+mod silver_string;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod symbols;
+#[cfg(test)]
+mod test_fixtures;
+mod xref;
 
-pub use bitmap::{BitmapImage, RawBitmapType};
+pub use bitmap::{
+    expand_channel, reduce_channel, Atlas, AtlasSprite, BitmapImage, BitmapIssue, ConversionReport,
+    Dither, QuantizeOptions, Quantizer, RawBitmapData, RawBitmapHeader, RawBitmapType, Theme,
+    ThemeImage, ThemeReport, ThemeSkip,
+};
+pub use catalog::{
+    BitmapVersion, Catalog, CatalogBitmap, CatalogEntry, CatalogKey, CatalogOccurrence,
+    CatalogResource, IngestReport,
+};
 pub use database::*;
+pub use device::{Device, DeviceProfile, ProfileIssue};
 pub use format::*;
+pub use localization::*;
+pub use matching::{
+    match_resources, MappingEntry, MatchMethod, MatchOptions, ResourceMapping, SymbolConflict,
+    SymbolMigration,
+};
+pub use query::Filter;
 pub use section_content::SectionContent;
 pub use section_types::SectionType;
 pub use silver_error::SilverError;
+pub use silver_string::{SilverString, StringEncoding};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteExporter;
+pub use symbols::SymbolTable;
+pub use xref::{ReferenceConfidence, ReferenceGraph, ResourceLocation, ResourceReference};
@@ -63,6 +63,11 @@ impl LittleHelper {
         Ok(raw_magic)
     }
 
+    /// Writes a little-endian u16.
+    pub fn write_u16_le(&mut self, value: u16) -> Result<(), io::Error> {
+        self.0.write_u16::<LittleEndian>(value)
+    }
+
     /// Writes a little-endian u32.
     pub fn write_u32_le(&mut self, value: u32) -> Result<(), io::Error> {
         self.0.write_u32::<LittleEndian>(value)
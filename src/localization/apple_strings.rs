@@ -0,0 +1,368 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use crate::{
+    database::{SilverDB, SilverResourceID},
+    localization::{string_resources, TranslationUnit},
+    silver_error::SilverError,
+};
+
+/// Encodings Apple `.strings` files are commonly stored in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringsFileEncoding {
+    /// Little-endian UTF-16 with a byte order mark, as traditionally used by Apple.
+    Utf16,
+    /// UTF-8 without a byte order mark.
+    Utf8,
+}
+
+/// Encodes the contents of a `.strings` file.
+pub fn encode_strings_file(contents: &str, encoding: StringsFileEncoding) -> Vec<u8> {
+    match encoding {
+        StringsFileEncoding::Utf8 => contents.as_bytes().to_vec(),
+        StringsFileEncoding::Utf16 => {
+            let mut raw_contents = vec![0xff, 0xfe];
+            for unit in contents.encode_utf16() {
+                raw_contents.extend(unit.to_le_bytes());
+            }
+            raw_contents
+        }
+    }
+}
+
+/// Decodes the contents of a `.strings` file, detecting its encoding by its byte order mark.
+/// Files lacking a byte order mark are assumed to be UTF-8.
+pub fn decode_strings_file(raw_contents: &[u8]) -> Result<String, SilverError> {
+    let invalid = || SilverError::InvalidTranslation("invalid .strings encoding".to_string());
+
+    let utf16_units: Vec<u16> = match raw_contents {
+        [0xff, 0xfe, rest @ ..] => rest
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect(),
+        [0xfe, 0xff, rest @ ..] => rest
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect(),
+        [0xef, 0xbb, 0xbf, rest @ ..] => {
+            return String::from_utf8(rest.to_vec()).map_err(|_| invalid())
+        }
+        _ => return String::from_utf8(raw_contents.to_vec()).map_err(|_| invalid()),
+    };
+    String::from_utf16(&utf16_units).map_err(|_| invalid())
+}
+
+/// Escapes text to be a quoted `.strings` value.
+fn escape_strings(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for current in text.chars() {
+        match current {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\U{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Exports translation units as the contents of an Apple `.strings` file.
+///
+/// As is customary, the reference locale's text is used as the key.
+/// A comment preceding every entry holds its resource ID and, if known,
+/// its `StrT` placeholder name (see `placeholder_names`).
+/// Untranslated units are written commented out, so that they are not imported
+/// until a translator provides their value.
+///
+/// `.stringsdict` files are not supported: they hold plural variants of a string,
+/// whereas every `Str ` resource holds exactly one text, with no plural forms.
+pub fn export_apple_strings(
+    units: &[TranslationUnit],
+    names: &BTreeMap<SilverResourceID, String>,
+) -> String {
+    let mut output = String::new();
+    for unit in units {
+        // Comments cannot contain their terminator.
+        let comment = match names.get(&unit.id) {
+            Some(name) => format!("{} {}", unit.id, name.replace("*/", "* /")),
+            None => unit.id.to_string(),
+        };
+        let (prefix, target) = match unit.target.as_deref() {
+            Some(target) => ("", target),
+            None => ("// ", unit.source.as_str()),
+        };
+
+        output.push_str(&format!("/* {comment} */\n"));
+        output.push_str(&format!(
+            "{prefix}{} = {};\n\n",
+            escape_strings(&unit.source),
+            escape_strings(target)
+        ));
+    }
+    output
+}
+
+/// A simple parser for the `.strings` format.
+struct StringsParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+/// A single key/value pair, alongside the comment preceding it.
+struct StringsEntry {
+    comment: Option<String>,
+    key: String,
+    value: String,
+}
+
+impl StringsParser {
+    fn error(&self, description: &str) -> SilverError {
+        // Determine the line number for our current position.
+        let line = self.chars[..self.position.min(self.chars.len())]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count()
+            + 1;
+        SilverError::InvalidTranslation(format!("{description} on line {line}"))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn starts_with(&self, pattern: &str) -> bool {
+        pattern
+            .chars()
+            .enumerate()
+            .all(|(offset, c)| self.chars.get(self.position + offset) == Some(&c))
+    }
+
+    /// Skips whitespace and comments, returning the last comment encountered.
+    fn skip_trivia(&mut self) -> Result<Option<String>, SilverError> {
+        let mut last_comment = None;
+        loop {
+            if self.peek().is_some_and(char::is_whitespace) {
+                self.position += 1;
+            } else if self.starts_with("/*") {
+                let start = self.position + 2;
+                let end = (start..self.chars.len())
+                    .find(|index| {
+                        self.chars[*index] == '*' && self.chars.get(index + 1) == Some(&'/')
+                    })
+                    .ok_or_else(|| self.error("unterminated comment"))?;
+                last_comment = Some(self.chars[start..end].iter().collect());
+                self.position = end + 2;
+            } else if self.starts_with("//") {
+                let start = self.position + 2;
+                let end = (start..self.chars.len())
+                    .find(|index| self.chars[*index] == '\n')
+                    .unwrap_or(self.chars.len());
+                last_comment = Some(self.chars[start..end].iter().collect());
+                self.position = end;
+            } else {
+                return Ok(last_comment);
+            }
+        }
+    }
+
+    /// Reads a quoted string, or an unquoted word.
+    fn read_string(&mut self) -> Result<String, SilverError> {
+        if self.peek() != Some('"') {
+            let start = self.position;
+            while self
+                .peek()
+                .is_some_and(|c| c.is_alphanumeric() || "_.$:/-".contains(c))
+            {
+                self.position += 1;
+            }
+            if start == self.position {
+                return Err(self.error("expected string"));
+            }
+            return Ok(self.chars[start..self.position].iter().collect());
+        }
+
+        self.position += 1;
+        let mut text = String::new();
+        loop {
+            let current = self
+                .peek()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.position += 1;
+            match current {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escaped = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.position += 1;
+                    match escaped {
+                        'n' => text.push('\n'),
+                        'r' => text.push('\r'),
+                        't' => text.push('\t'),
+                        'U' | 'u' => text.push(self.read_unicode_escape()?),
+                        other => text.push(other),
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    /// Reads the four hex digits of a UTF-16 code unit following `\U`.
+    fn read_code_unit(&mut self) -> Result<u16, SilverError> {
+        let digits: String = self.chars.iter().skip(self.position).take(4).collect();
+        let unit = (digits.len() == 4)
+            .then(|| u16::from_str_radix(&digits, 16).ok())
+            .flatten()
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.position += 4;
+        Ok(unit)
+    }
+
+    /// Reads a `\U` escape, whose leading backslash and `U` have been consumed.
+    /// Characters outside the Basic Multilingual Plane are escaped as a surrogate pair,
+    /// i.e. `\Ud83c\Udfb5`.
+    fn read_unicode_escape(&mut self) -> Result<char, SilverError> {
+        let high = self.read_code_unit()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high as u32)
+                .ok_or_else(|| self.error("unpaired surrogate in unicode escape"));
+        }
+
+        if !(self.starts_with("\\U") || self.starts_with("\\u")) {
+            return Err(self.error("unpaired surrogate in unicode escape"));
+        }
+        self.position += 2;
+        let low = self.read_code_unit()?;
+        char::decode_utf16([high, low])
+            .next()
+            .and_then(Result::ok)
+            .ok_or_else(|| self.error("unpaired surrogate in unicode escape"))
+    }
+
+    /// Expects the given character, ignoring preceding whitespace and comments.
+    fn expect(&mut self, expected: char) -> Result<(), SilverError> {
+        self.skip_trivia()?;
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected '{expected}'")));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn parse(mut self) -> Result<Vec<StringsEntry>, SilverError> {
+        let mut entries = Vec::new();
+        loop {
+            let comment = self.skip_trivia()?;
+            if self.peek().is_none() {
+                return Ok(entries);
+            }
+
+            let key = self.read_string()?;
+            self.expect('=')?;
+            self.skip_trivia()?;
+            let value = self.read_string()?;
+            self.expect(';')?;
+            entries.push(StringsEntry {
+                comment,
+                key,
+                value,
+            });
+        }
+    }
+}
+
+/// Determines a resource ID within a comment, i.e. `0x0000002a` within `/* 0x0000002a Title */`.
+fn comment_resource_id(comment: &str) -> Option<SilverResourceID> {
+    comment
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.starts_with("0x"))
+        .find_map(|word| SilverResourceID::from_str(word).ok())
+}
+
+/// Parses translation units from the contents of an Apple `.strings` file.
+///
+/// Entries preceded by a comment holding a resource ID (as written by `export_apple_strings`)
+/// apply to that resource. Otherwise, as is the case with `.strings` files from other projects,
+/// entries apply to every resource whose reference text matches their key.
+pub fn import_apple_strings(
+    contents: &str,
+    reference: &SilverDB,
+) -> Result<Vec<TranslationUnit>, SilverError> {
+    let parser = StringsParser {
+        chars: contents.chars().collect(),
+        position: 0,
+    };
+    let reference_strings = string_resources(reference);
+
+    let mut units = Vec::new();
+    for entry in parser.parse()? {
+        if let Some(id) = entry.comment.as_deref().and_then(comment_resource_id) {
+            units.push(TranslationUnit {
+                id,
+                source: entry.key,
+                target: Some(entry.value),
+            });
+            continue;
+        }
+
+        for (id, reference_string) in &reference_strings {
+            if reference_string.text() == entry.key {
+                units.push(TranslationUnit {
+                    id: *id,
+                    source: entry.key.clone(),
+                    target: Some(entry.value.clone()),
+                });
+            }
+        }
+    }
+
+    Ok(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_database() -> SilverDB {
+        SilverDB {
+            sections: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn untranslated_units_are_not_imported() {
+        let units = [
+            TranslationUnit {
+                id: SilverResourceID(1),
+                source: "Music".to_string(),
+                target: Some("Musik".to_string()),
+            },
+            TranslationUnit {
+                id: SilverResourceID(2),
+                source: "Photos".to_string(),
+                target: None,
+            },
+        ];
+        let contents = export_apple_strings(&units, &BTreeMap::new());
+        assert!(contents.contains("// \"Photos\" = \"Photos\";"));
+
+        let imported = import_apple_strings(&contents, &empty_database()).unwrap();
+        assert_eq!(imported, units[..1]);
+    }
+
+    #[test]
+    fn combines_escaped_surrogate_pairs() {
+        let contents = "/* 0x00000001 */\n\"Note\" = \"\\U266a \\Ud83c\\udfb5\";\n";
+        let imported = import_apple_strings(contents, &empty_database()).unwrap();
+        assert_eq!(imported[0].target.as_deref(), Some("\u{266a} \u{1f3b5}"));
+
+        for unpaired in ["\\Ud83c", "\\Ud83c\\U0041", "\\Udfb5"] {
+            let contents = format!("\"Note\" = \"{unpaired}\";\n");
+            assert!(import_apple_strings(&contents, &empty_database()).is_err());
+        }
+    }
+}
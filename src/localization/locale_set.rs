@@ -0,0 +1,92 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{database::SilverDB, device::DeviceProfile, silver_error::SilverError};
+
+/// A set of locale databases, keyed by their locale code (i.e. `en_US`).
+pub struct LocaleSet {
+    pub locales: BTreeMap<String, SilverDB>,
+}
+
+impl LocaleSet {
+    /// Determines the locale code of a locale database based on its file name.
+    /// For example, `SilverDB.ja_JP.LE.bin` has the locale code `ja_JP`.
+    pub fn locale_code(file_name: &str) -> Option<&str> {
+        let locale_code = file_name
+            .strip_prefix("SilverDB.")?
+            .strip_suffix(".LE.bin")?;
+        // Ensure we don't match something like `SilverDB.LE.bin`.
+        if locale_code.is_empty() || locale_code.contains('.') {
+            return None;
+        }
+        Some(locale_code)
+    }
+
+    /// The file name a locale database with the given code is expected to have.
+    pub fn file_name(locale_code: &str) -> String {
+        format!("SilverDB.{locale_code}.LE.bin")
+    }
+
+    /// Loads every locale database within the given directory.
+    /// Files not named similarly to `SilverDB.xx_XX.LE.bin` are ignored.
+    pub fn load(directory: &Path) -> Result<Self, SilverError> {
+        LocaleSet::load_with_profile(directory, &DeviceProfile::generic())
+    }
+
+    /// Loads every locale database within the given directory,
+    /// using the rules of the given device profile.
+    pub fn load_with_profile(
+        directory: &Path,
+        profile: &DeviceProfile,
+    ) -> Result<Self, SilverError> {
+        let mut locales = BTreeMap::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some(locale_code) = LocaleSet::locale_code(file_name) else {
+                continue;
+            };
+
+            let database = SilverDB::read_with_profile(fs::read(&path)?, profile)?;
+            locales.insert(locale_code.to_string(), database);
+        }
+
+        Ok(LocaleSet { locales })
+    }
+
+    /// Returns the database for the given locale code.
+    pub fn get(&self, locale_code: &str) -> Result<&SilverDB, SilverError> {
+        self.locales
+            .get(locale_code)
+            .ok_or_else(|| SilverError::MissingLocale(locale_code.to_string()))
+    }
+
+    /// Writes every locale database to the given directory, named by their locale code.
+    ///
+    /// Databases identical to the file already present are left untouched.
+    /// Returns the codes of locales written.
+    pub fn write(self, directory: &Path) -> Result<Vec<String>, SilverError> {
+        self.write_with_profile(directory, &DeviceProfile::generic())
+    }
+
+    /// Writes every locale database to the given directory using the rules of the given
+    /// device profile. Returns the codes of locales written, as with `LocaleSet::write`.
+    pub fn write_with_profile(
+        self,
+        directory: &Path,
+        profile: &DeviceProfile,
+    ) -> Result<Vec<String>, SilverError> {
+        let mut written = Vec::new();
+        for (locale_code, database) in self.locales {
+            let raw_database = SilverDB::write_with_profile(database.sections, profile)?;
+            let database_path = directory.join(LocaleSet::file_name(&locale_code));
+            if fs::read(&database_path).is_ok_and(|existing| existing == raw_database) {
+                continue;
+            }
+            fs::write(database_path, raw_database)?;
+            written.push(locale_code);
+        }
+        Ok(written)
+    }
+}
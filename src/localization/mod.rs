@@ -0,0 +1,70 @@
+mod apple_strings;
+mod locale_set;
+mod placeholder;
+mod po;
+mod pseudo;
+mod report;
+mod spreadsheet;
+mod template;
+mod translation;
+mod xliff;
+
+use std::collections::BTreeMap;
+
+pub use apple_strings::{
+    decode_strings_file, encode_strings_file, export_apple_strings, import_apple_strings,
+    StringsFileEncoding,
+};
+pub use locale_set::LocaleSet;
+pub use placeholder::{
+    check_placeholders, parse_placeholders, Placeholder, PlaceholderIssue, PlaceholderIssueKind,
+};
+pub use po::{export_po, import_po};
+pub use pseudo::{pseudo_localize, pseudo_localize_database, PseudoLocaleOptions};
+pub use report::LocaleReport;
+pub use spreadsheet::{export_spreadsheet, import_spreadsheet, parse_spreadsheet};
+pub use template::{new_locale, LocaleStatus, NewLocale, TranslationStatus};
+pub use translation::{
+    apply_translations, check_translation_ids, translation_units, TranslationUnit,
+};
+pub use xliff::{export_xliff, import_xliff, XliffVersion};
+
+use crate::{
+    database::{SilverDB, SilverResourceID},
+    section_content::SectionContent,
+    section_types::SectionType,
+    silver_string::SilverString,
+};
+
+/// Collects all strings within the `Str ` section of a database, keyed by their ID.
+fn string_resources(database: &SilverDB) -> BTreeMap<SilverResourceID, &SilverString> {
+    section_strings(database, &SectionType::String)
+}
+
+/// Collects all strings within the given section of a database, keyed by their ID.
+fn section_strings<'a>(
+    database: &'a SilverDB,
+    section_type: &SectionType,
+) -> BTreeMap<SilverResourceID, &'a SilverString> {
+    let Some(section) = database.find_section(section_type) else {
+        return BTreeMap::new();
+    };
+
+    section
+        .resources
+        .iter()
+        .filter_map(|resource| match &resource.contents {
+            SectionContent::String(string) => Some((resource.id, string)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects the placeholder names of `Str ` resources from the `StrT` section of a database.
+/// A `StrT` resource describes the `Str ` resource sharing its ID.
+pub fn placeholder_names(database: &SilverDB) -> BTreeMap<SilverResourceID, String> {
+    section_strings(database, &SectionType::StringTranslation)
+        .into_iter()
+        .map(|(id, name)| (id, name.text().to_string()))
+        .collect()
+}
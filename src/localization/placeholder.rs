@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    database::{SilverDB, SilverResourceID},
+    section_content::SectionContent,
+    section_types::SectionType,
+};
+
+/// A runtime substitution token within a string, i.e. `%d`, `%@` or `%2$s`.
+///
+/// These follow printf-style syntax:
+/// `%[position$][flags][width][.precision][length]conversion`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    /// The token exactly as written, i.e. `%-2$05ld`.
+    pub token: String,
+    /// The byte offset of this token within its string.
+    pub offset: usize,
+    /// The explicit argument position (i.e. 2 for `%2$s`), if present.
+    pub position: Option<u32>,
+    /// The length modifier and conversion, i.e. `ld` for `%05ld`.
+    /// This determines the argument type expected at runtime.
+    pub specifier: String,
+}
+
+/// Flags permitted after the argument position.
+/// The space flag is intentionally omitted, as text such as "50% off" would otherwise match.
+const FLAGS: &[char] = &['-', '+', '#', '0', '\''];
+/// Length modifiers permitted prior to the conversion.
+const LENGTH_MODIFIERS: &[char] = &['h', 'l', 'q', 'L', 'z', 't', 'j'];
+/// Conversions ending a placeholder.
+const CONVERSIONS: &[char] = &[
+    'd', 'i', 'u', 'o', 'x', 'X', 'f', 'F', 'e', 'E', 'g', 'G', 'a', 'A', 'c', 'C', 's', 'S', 'p',
+    '@',
+];
+
+/// Parses all placeholders within the given text.
+/// Escaped percent signs (`%%`) and incomplete tokens are not placeholders.
+pub fn parse_placeholders(text: &str) -> Vec<Placeholder> {
+    let mut placeholders = Vec::new();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+
+    let mut index = 0;
+    while index < chars.len() {
+        let (offset, current) = chars[index];
+        index += 1;
+        if current != '%' {
+            continue;
+        }
+
+        // `%%` is a literal percent sign.
+        if let Some((_, '%')) = chars.get(index) {
+            index += 1;
+            continue;
+        }
+
+        let mut cursor = index;
+        let next_char = |cursor: usize| chars.get(cursor).map(|(_, c)| *c);
+
+        // Argument position: one or more digits followed by `$`.
+        let mut position = None;
+        let digits_end = (cursor..chars.len())
+            .find(|i| !chars[*i].1.is_ascii_digit())
+            .unwrap_or(chars.len());
+        if digits_end > cursor && next_char(digits_end) == Some('$') {
+            let digits: String = chars[cursor..digits_end].iter().map(|(_, c)| c).collect();
+            position = digits.parse().ok();
+            cursor = digits_end + 1;
+        }
+
+        // Flags, width and precision do not affect the argument type.
+        while next_char(cursor).is_some_and(|c| FLAGS.contains(&c)) {
+            cursor += 1;
+        }
+        while next_char(cursor).is_some_and(|c| c.is_ascii_digit() || c == '*') {
+            cursor += 1;
+        }
+        if next_char(cursor) == Some('.') {
+            cursor += 1;
+            while next_char(cursor).is_some_and(|c| c.is_ascii_digit() || c == '*') {
+                cursor += 1;
+            }
+        }
+
+        let mut specifier = String::new();
+        while let Some(c) = next_char(cursor).filter(|c| LENGTH_MODIFIERS.contains(c)) {
+            specifier.push(c);
+            cursor += 1;
+        }
+
+        let Some(conversion) = next_char(cursor).filter(|c| CONVERSIONS.contains(c)) else {
+            // This isn't a placeholder - resume after the percent sign.
+            continue;
+        };
+        specifier.push(conversion);
+        cursor += 1;
+
+        let end_offset = chars.get(cursor).map_or(text.len(), |(o, _)| *o);
+        placeholders.push(Placeholder {
+            token: text[offset..end_offset].to_string(),
+            offset,
+            position,
+            specifier,
+        });
+        index = cursor;
+    }
+
+    placeholders
+}
+
+/// Resolves which argument every placeholder consumes, mapping argument positions to their specifier.
+/// Placeholders without an explicit position consume arguments sequentially.
+fn resolve_arguments(placeholders: &[Placeholder]) -> BTreeMap<u32, &str> {
+    let mut arguments = BTreeMap::new();
+    let mut next_position = 1;
+    for placeholder in placeholders {
+        let position = placeholder.position.unwrap_or(next_position);
+        next_position = position + 1;
+        arguments.insert(position, placeholder.specifier.as_str());
+    }
+    arguments
+}
+
+/// Possible problems with placeholders in a localized string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlaceholderIssueKind {
+    /// A placeholder within the base string is absent.
+    Missing(String),
+    /// A placeholder is present that the base string lacks.
+    Extra(String),
+    /// All placeholders are present, but consume arguments in a different order.
+    /// Translations should use explicit positions (i.e. `%2$s`) to reorder.
+    Reordered {
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+}
+
+/// A problem with placeholders within a single localized resource.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlaceholderIssue {
+    /// The ID of the `Str ` resource with this issue.
+    pub id: SilverResourceID,
+    /// What the problem is.
+    pub kind: PlaceholderIssueKind,
+}
+
+/// Compares placeholders of every `Str ` resource within a localized database against
+/// the resource with the same ID in a base database (i.e. `en_US`).
+///
+/// Resources present only within one database are not considered.
+pub fn check_placeholders(base: &SilverDB, localized: &SilverDB) -> Vec<PlaceholderIssue> {
+    let mut issues = Vec::new();
+    let (Some(base_section), Some(localized_section)) = (
+        base.find_section(&SectionType::String),
+        localized.find_section(&SectionType::String),
+    ) else {
+        return issues;
+    };
+
+    for base_resource in &base_section.resources {
+        let SectionContent::String(base_string) = &base_resource.contents else {
+            continue;
+        };
+        let Some(SectionContent::String(localized_string)) = localized_section
+            .resources
+            .iter()
+            .find(|resource| resource.id == base_resource.id)
+            .map(|resource| &resource.contents)
+        else {
+            continue;
+        };
+
+        let base_placeholders = parse_placeholders(base_string.text());
+        let localized_placeholders = parse_placeholders(localized_string.text());
+        let id = base_resource.id;
+
+        // First, compare which placeholders are present, disregarding order.
+        let mut unmatched: Vec<&Placeholder> = localized_placeholders.iter().collect();
+        let mut found_difference = false;
+        for placeholder in &base_placeholders {
+            let matching = unmatched
+                .iter()
+                .position(|candidate| candidate.specifier == placeholder.specifier);
+            match matching {
+                Some(matching) => {
+                    unmatched.remove(matching);
+                }
+                None => {
+                    found_difference = true;
+                    issues.push(PlaceholderIssue {
+                        id,
+                        kind: PlaceholderIssueKind::Missing(placeholder.token.clone()),
+                    });
+                }
+            }
+        }
+        for placeholder in unmatched {
+            found_difference = true;
+            issues.push(PlaceholderIssue {
+                id,
+                kind: PlaceholderIssueKind::Extra(placeholder.token.clone()),
+            });
+        }
+
+        // Next, if all are present, ensure arguments are consumed identically.
+        if !found_difference
+            && resolve_arguments(&base_placeholders) != resolve_arguments(&localized_placeholders)
+        {
+            let tokens = |placeholders: &[Placeholder]| {
+                placeholders.iter().map(|p| p.token.clone()).collect()
+            };
+            issues.push(PlaceholderIssue {
+                id,
+                kind: PlaceholderIssueKind::Reordered {
+                    expected: tokens(&base_placeholders),
+                    found: tokens(&localized_placeholders),
+                },
+            });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::string_database;
+
+    #[test]
+    fn parses_placeholders() {
+        let placeholders = parse_placeholders("%d of %2$@, %-05ld and %.*f, 100%% or 50% off %");
+        let tokens: Vec<_> = placeholders.iter().map(|p| p.token.as_str()).collect();
+        assert_eq!(tokens, vec!["%d", "%2$@", "%-05ld", "%.*f"]);
+
+        let specifiers: Vec<_> = placeholders.iter().map(|p| p.specifier.as_str()).collect();
+        assert_eq!(specifiers, vec!["d", "@", "ld", "f"]);
+        let positions: Vec<_> = placeholders.iter().map(|p| p.position).collect();
+        assert_eq!(positions, vec![None, Some(2), None, None]);
+    }
+
+    #[test]
+    fn offsets_are_in_bytes() {
+        let placeholders = parse_placeholders("Été: %s");
+        assert_eq!(placeholders.len(), 1);
+        assert_eq!(placeholders[0].offset, 7);
+        assert_eq!(placeholders[0].token, "%s");
+    }
+
+    #[test]
+    fn finds_missing_extra_and_reordered_placeholders() {
+        let base = string_database(&[
+            (1, "%d songs"),
+            (2, "%s by %s"),
+            (3, "%d of %@"),
+            (4, "%d of %@"),
+            (5, "Only in base: %d"),
+        ]);
+        let localized = string_database(&[
+            (1, "%s chansons"),
+            (2, "%s de %s et %d"),
+            (3, "%@ sur %d"),
+            (4, "%2$@ sur %1$d"),
+        ]);
+
+        let issues = check_placeholders(&base, &localized);
+        let issue = |id, kind| PlaceholderIssue {
+            id: SilverResourceID(id),
+            kind,
+        };
+        assert_eq!(
+            issues,
+            vec![
+                issue(1, PlaceholderIssueKind::Missing("%d".to_string())),
+                issue(1, PlaceholderIssueKind::Extra("%s".to_string())),
+                issue(2, PlaceholderIssueKind::Extra("%d".to_string())),
+                issue(
+                    3,
+                    PlaceholderIssueKind::Reordered {
+                        expected: vec!["%d".to_string(), "%@".to_string()],
+                        found: vec!["%@".to_string(), "%d".to_string()],
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use std::str::FromStr;
+
+use crate::{database::SilverResourceID, localization::TranslationUnit, silver_error::SilverError};
+
+/// Escapes text to be a quoted PO string.
+fn escape_po(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for current in text.chars() {
+        match current {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // Other control characters (i.e. embedded null bytes) use octal escapes.
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\{:03o}", c as u8)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Writes a keyword and its value, splitting multi-line values across lines as is customary.
+fn write_keyword(output: &mut String, keyword: &str, text: &str) {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    if lines.len() <= 1 {
+        output.push_str(&format!("{keyword} {}\n", escape_po(text)));
+        return;
+    }
+
+    output.push_str(&format!("{keyword} \"\"\n"));
+    for line in lines {
+        output.push_str(&escape_po(line));
+        output.push('\n');
+    }
+}
+
+/// Exports translation units as a gettext PO file.
+///
+/// The resource ID is used as message context (`msgctxt`), as
+/// the same source text may be used by many resources.
+pub fn export_po(units: &[TranslationUnit], locale_code: &str) -> String {
+    let mut output = String::new();
+    output.push_str("msgid \"\"\nmsgstr \"\"\n");
+    output.push_str(&format!("\"Language: {locale_code}\\n\"\n"));
+    output.push_str("\"MIME-Version: 1.0\\n\"\n");
+    output.push_str("\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    output.push_str("\"Content-Transfer-Encoding: 8bit\\n\"\n");
+    output.push_str("\"X-Generator: silverutil\\n\"\n");
+
+    for unit in units {
+        output.push('\n');
+        write_keyword(&mut output, "msgctxt", &unit.id.to_string());
+        write_keyword(&mut output, "msgid", &unit.source);
+        write_keyword(&mut output, "msgstr", unit.target.as_deref().unwrap_or(""));
+    }
+
+    output
+}
+
+/// Unescapes a quoted PO string, i.e. `"Hello\n"`.
+fn unescape_po(quoted: &str, line_number: usize) -> Result<String, SilverError> {
+    let invalid =
+        || SilverError::InvalidTranslation(format!("invalid string on line {line_number}"));
+
+    let inner = quoted
+        .strip_prefix('"')
+        .and_then(|q| q.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+
+    let mut text = String::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(current) = chars.next() {
+        if current != '\\' {
+            text.push(current);
+            continue;
+        }
+
+        match chars.next().ok_or_else(invalid)? {
+            'n' => text.push('\n'),
+            'r' => text.push('\r'),
+            't' => text.push('\t'),
+            'a' => text.push('\x07'),
+            'b' => text.push('\x08'),
+            'f' => text.push('\x0c'),
+            'v' => text.push('\x0b'),
+            'x' => {
+                let mut value = 0u32;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(16)) {
+                    value = value * 16 + digit;
+                    chars.next();
+                }
+                text.push(char::from_u32(value).ok_or_else(invalid)?);
+            }
+            octal @ '0'..='7' => {
+                let mut value = octal.to_digit(8).unwrap_or_default();
+                for _ in 0..2 {
+                    let Some(digit) = chars.peek().and_then(|c| c.to_digit(8)) else {
+                        break;
+                    };
+                    value = value * 8 + digit;
+                    chars.next();
+                }
+                text.push(char::from_u32(value).ok_or_else(invalid)?);
+            }
+            other => text.push(other),
+        }
+    }
+
+    Ok(text)
+}
+
+/// A PO entry, as it is being parsed.
+#[derive(Default)]
+struct PoEntry {
+    context: Option<String>,
+    source: Option<String>,
+    target: Option<String>,
+    fuzzy: bool,
+}
+
+impl PoEntry {
+    /// Converts this entry to a translation unit.
+    /// The header entry, and entries lacking a resource ID, are skipped.
+    fn into_unit(self) -> Result<Option<TranslationUnit>, SilverError> {
+        let (Some(context), Some(source)) = (self.context, self.source) else {
+            return Ok(None);
+        };
+        let id = SilverResourceID::from_str(&context).map_err(|_| {
+            SilverError::InvalidTranslation(format!("invalid resource ID {context:?}"))
+        })?;
+
+        // Fuzzy translations require review, and are considered untranslated.
+        // As is customary for gettext, so are empty translations.
+        let target = self
+            .target
+            .filter(|target| !self.fuzzy && !target.is_empty());
+        Ok(Some(TranslationUnit { id, source, target }))
+    }
+}
+
+/// Which value continuation strings are appended to.
+enum PoField {
+    Context,
+    Source,
+    Target,
+    Ignored,
+}
+
+/// Parses translation units from a gettext PO file.
+///
+/// Plural forms are not used within SilverDBs: only `msgstr[0]` is honored.
+/// Fuzzy, obsolete and empty entries are considered untranslated.
+pub fn import_po(contents: &str) -> Result<Vec<TranslationUnit>, SilverError> {
+    let mut units = Vec::new();
+    let mut entry = PoEntry::default();
+    let mut current_field: Option<PoField> = None;
+
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // Comments precede the entry they describe, so they end any translated entry.
+        // Obsolete entries (`#~`) are entirely comments, and are thus skipped.
+        if line.starts_with('#') {
+            if entry.target.is_some() {
+                units.extend(std::mem::take(&mut entry).into_unit()?);
+                current_field = None;
+            }
+            if let Some(flags) = line.strip_prefix("#,") {
+                entry.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            }
+            continue;
+        }
+
+        // Continuation of the previous keyword's value.
+        if line.starts_with('"') {
+            let value = unescape_po(line, line_number)?;
+            match current_field {
+                Some(PoField::Context) => entry.context.get_or_insert_default().push_str(&value),
+                Some(PoField::Source) => entry.source.get_or_insert_default().push_str(&value),
+                Some(PoField::Target) => entry.target.get_or_insert_default().push_str(&value),
+                Some(PoField::Ignored) => {}
+                None => {
+                    return Err(SilverError::InvalidTranslation(format!(
+                        "unexpected string on line {line_number}"
+                    )))
+                }
+            }
+            continue;
+        }
+
+        let (keyword, quoted) = line.split_once(char::is_whitespace).ok_or_else(|| {
+            SilverError::InvalidTranslation(format!("invalid line {line_number}"))
+        })?;
+        let value = unescape_po(quoted.trim(), line_number)?;
+
+        // A context or source following a translation begins a new entry.
+        if (keyword == "msgctxt" || keyword == "msgid") && entry.target.is_some() {
+            units.extend(std::mem::take(&mut entry).into_unit()?);
+        }
+
+        current_field = Some(match keyword {
+            "msgctxt" => {
+                entry.context = Some(value);
+                PoField::Context
+            }
+            "msgid" => {
+                entry.source = Some(value);
+                PoField::Source
+            }
+            "msgstr" | "msgstr[0]" => {
+                entry.target = Some(value);
+                PoField::Target
+            }
+            // Other plural forms and unknown keywords are ignored.
+            _ => PoField::Ignored,
+        });
+    }
+    units.extend(entry.into_unit()?);
+
+    Ok(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::unit;
+
+    #[test]
+    fn round_trips_units() {
+        let units = [
+            unit(1, "Music", Some("Musik")),
+            unit(2, "Photos", None),
+            unit(3, "Line one\nLine two\n", Some("Zeile eins\nZeile zwei\n")),
+            unit(4, "\"Quoted\"\t\\", Some("\u{201e}Zitiert\u{201c}\t\\")),
+            unit(5, "Nul\0byte", Some("Null\0byte")),
+        ];
+        let contents = export_po(&units, "de_DE");
+        assert_eq!(import_po(&contents).unwrap(), units);
+    }
+
+    #[test]
+    fn empty_and_fuzzy_entries_are_untranslated() {
+        let contents = export_po(&[unit(1, "Music", Some(""))], "de_DE");
+        assert_eq!(import_po(&contents).unwrap(), [unit(1, "Music", None)]);
+
+        let contents = "#, fuzzy\nmsgctxt \"0x00000001\"\nmsgid \"Music\"\nmsgstr \"Musik\"\n";
+        assert_eq!(import_po(contents).unwrap(), [unit(1, "Music", None)]);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    database::SilverDB, localization::parse_placeholders, section_content::SectionContent,
+    section_types::SectionType,
+};
+
+/// Transforms applied when pseudo-localizing strings.
+#[derive(Clone, Debug)]
+pub struct PseudoLocaleOptions {
+    /// Replaces ASCII letters with accented look-alikes (i.e. `Music` becomes `Ḿúšîç`).
+    pub accents: bool,
+    /// Lengthens text by the given percentage, emulating languages with longer text.
+    pub expansion: u32,
+    /// Surrounds text with brackets, making truncation and concatenation apparent.
+    pub brackets: bool,
+    /// Reverses text, emulating right-to-left languages.
+    pub mirror: bool,
+}
+
+impl Default for PseudoLocaleOptions {
+    fn default() -> Self {
+        PseudoLocaleOptions {
+            accents: true,
+            expansion: 30,
+            brackets: true,
+            mirror: false,
+        }
+    }
+}
+
+/// Character used to lengthen text.
+const EXPANSION_CHARACTER: char = '~';
+
+/// Maps ASCII letters to an accented look-alike.
+fn accented(current: char) -> char {
+    let (plain, accented) = match current {
+        'a'..='z' => ("abcdefghijklmnopqrstuvwxyz", "áƀçđéƒĝĥîĵķĺḿñóþǫŕšţúṽŵẋýž"),
+        'A'..='Z' => ("ABCDEFGHIJKLMNOPQRSTUVWXYZ", "ÁƁÇĐÉƑĜĤÎĴĶĹḾÑÓÞǪŔŠŢÚṼŴẊÝŽ"),
+        _ => return current,
+    };
+    let index = plain.chars().position(|c| c == current).unwrap_or_default();
+    accented.chars().nth(index).unwrap_or(current)
+}
+
+/// Pseudo-localizes the given text. Placeholders (i.e. `%d` or `%1$@`) are kept intact,
+/// and remain in their original order so that their arguments are unaffected.
+///
+/// Empty text is left empty.
+pub fn pseudo_localize(text: &str, options: &PseudoLocaleOptions) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+
+    // Separate placeholders from the text surrounding them.
+    // Only the latter is transformed.
+    let mut segments: Vec<(bool, &str)> = Vec::new();
+    let mut last_end = 0;
+    for placeholder in parse_placeholders(text) {
+        segments.push((false, &text[last_end..placeholder.offset]));
+        let end = placeholder.offset + placeholder.token.len();
+        segments.push((true, &text[placeholder.offset..end]));
+        last_end = end;
+    }
+    segments.push((false, &text[last_end..]));
+
+    let mut output = String::new();
+    let mut text_length = 0;
+    for (is_placeholder, segment) in segments {
+        if is_placeholder {
+            output.push_str(segment);
+            continue;
+        }
+
+        let mut transformed: Vec<char> = segment
+            .chars()
+            .map(|c| if options.accents { accented(c) } else { c })
+            .collect();
+        if options.mirror {
+            transformed.reverse();
+        }
+        text_length += transformed.len();
+        output.extend(transformed);
+    }
+
+    // Round up, so that short strings are lengthened too.
+    let expansion = (text_length * options.expansion as usize).div_ceil(100);
+    if expansion > 0 {
+        let padding: String = std::iter::repeat_n(EXPANSION_CHARACTER, expansion).collect();
+        if options.mirror {
+            output.insert_str(0, &padding);
+        } else {
+            output.push_str(&padding);
+        }
+    }
+
+    if options.brackets {
+        output = format!("[{output}]");
+    }
+    output
+}
+
+/// Pseudo-localizes every `Str ` resource within the given database.
+/// All other sections are left as-is.
+pub fn pseudo_localize_database(database: &mut SilverDB, options: &PseudoLocaleOptions) {
+    for section in database.sections.iter_mut() {
+        if section.section_type != SectionType::String {
+            continue;
+        }
+
+        for resource in section.resources.iter_mut() {
+            if let SectionContent::String(string) = &mut resource.contents {
+                let transformed = pseudo_localize(string.text(), options);
+                string.set_text(transformed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_placeholders_intact() {
+        let options = PseudoLocaleOptions::default();
+        assert_eq!(
+            pseudo_localize("Play %d songs", &options),
+            "[Þĺáý %d šóñĝš~~~~]"
+        );
+        assert_eq!(pseudo_localize("%1$@", &options), "[%1$@]");
+    }
+
+    #[test]
+    fn mirrors_text_between_placeholders() {
+        let mirrored = PseudoLocaleOptions {
+            accents: false,
+            expansion: 0,
+            brackets: false,
+            mirror: true,
+        };
+        assert_eq!(pseudo_localize("%1$@ of %2$@", &mirrored), "%1$@ fo %2$@");
+
+        // Expansion is placed at the start of mirrored text.
+        let all = PseudoLocaleOptions {
+            accents: true,
+            expansion: 100,
+            brackets: true,
+            mirror: true,
+        };
+        assert_eq!(pseudo_localize("Hi %1$@", &all), "[~~~ îĤ%1$@]");
+    }
+
+    #[test]
+    fn leaves_empty_text_empty() {
+        let options = PseudoLocaleOptions {
+            mirror: true,
+            ..Default::default()
+        };
+        assert_eq!(pseudo_localize("", &options), "");
+    }
+}
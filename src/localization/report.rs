@@ -0,0 +1,171 @@
+use crate::{
+    database::SilverResourceID,
+    localization::{string_resources, LocaleSet},
+    silver_error::SilverError,
+};
+
+/// Differences in `Str ` resources between a locale and its reference locale.
+pub struct LocaleReport {
+    /// The locale code this report describes, i.e. `ja_JP`.
+    pub locale: String,
+    /// IDs present within the reference locale, but absent here.
+    pub missing: Vec<SilverResourceID>,
+    /// IDs present here, but absent within the reference locale.
+    pub extra: Vec<SilverResourceID>,
+    /// IDs whose string is identical to the reference locale's.
+    pub untranslated: Vec<SilverResourceID>,
+    /// IDs whose string is empty, despite the reference locale's not being so.
+    pub empty: Vec<SilverResourceID>,
+}
+
+impl LocaleReport {
+    /// Whether this locale has no differences needing attention.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.untranslated.is_empty()
+            && self.empty.is_empty()
+    }
+}
+
+impl LocaleSet {
+    /// Compares every locale against the given reference locale.
+    /// The reference locale itself is not included within the report.
+    pub fn coverage_report(&self, reference: &str) -> Result<Vec<LocaleReport>, SilverError> {
+        let reference_strings = string_resources(self.get(reference)?);
+
+        let mut reports = Vec::new();
+        for (locale, database) in &self.locales {
+            if locale == reference {
+                continue;
+            }
+            let strings = string_resources(database);
+
+            let mut report = LocaleReport {
+                locale: locale.clone(),
+                missing: Vec::new(),
+                extra: Vec::new(),
+                untranslated: Vec::new(),
+                empty: Vec::new(),
+            };
+
+            for (id, reference_string) in &reference_strings {
+                let Some(string) = strings.get(id) else {
+                    report.missing.push(*id);
+                    continue;
+                };
+
+                if reference_string.text().is_empty() {
+                    continue;
+                } else if string.text().is_empty() {
+                    report.empty.push(*id);
+                } else if string.text() == reference_string.text() {
+                    report.untranslated.push(*id);
+                }
+            }
+
+            report.extra = strings
+                .keys()
+                .filter(|id| !reference_strings.contains_key(id))
+                .copied()
+                .collect();
+
+            reports.push(report);
+        }
+
+        Ok(reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::test_fixtures::string_database;
+
+    fn ids(ids: &[SilverResourceID]) -> Vec<u32> {
+        ids.iter().map(|id| id.0).collect()
+    }
+
+    #[test]
+    fn reports_coverage_against_reference() {
+        let locale_set = LocaleSet {
+            locales: BTreeMap::from([
+                (
+                    "en_US".to_string(),
+                    string_database(&[
+                        (1, "Music"),
+                        (2, "Photos"),
+                        (3, "OK"),
+                        (4, ""),
+                        (5, "Videos"),
+                    ]),
+                ),
+                (
+                    "de_DE".to_string(),
+                    string_database(&[(1, "Musik"), (2, ""), (3, "OK"), (4, ""), (6, "Extra")]),
+                ),
+                (
+                    "fr_FR".to_string(),
+                    string_database(&[
+                        (1, "Musique"),
+                        (2, "Photos"),
+                        (3, "OK"),
+                        (4, ""),
+                        (5, "Vidéos"),
+                    ]),
+                ),
+            ]),
+        };
+
+        let reports = locale_set.coverage_report("en_US").unwrap();
+        let locales: Vec<_> = reports
+            .iter()
+            .map(|report| report.locale.as_str())
+            .collect();
+        assert_eq!(locales, vec!["de_DE", "fr_FR"]);
+
+        let german = &reports[0];
+        assert_eq!(ids(&german.missing), vec![5]);
+        assert_eq!(ids(&german.extra), vec![6]);
+        assert_eq!(ids(&german.untranslated), vec![3]);
+        assert_eq!(ids(&german.empty), vec![2]);
+        assert!(!german.is_complete());
+
+        // Empty reference strings need no translation.
+        let french = &reports[1];
+        assert_eq!(ids(&french.untranslated), vec![2, 3]);
+        assert!(french.missing.is_empty() && french.extra.is_empty() && french.empty.is_empty());
+    }
+
+    #[test]
+    fn complete_locales_have_no_differences() {
+        let locale_set = LocaleSet {
+            locales: BTreeMap::from([
+                (
+                    "en_US".to_string(),
+                    string_database(&[(1, "Music"), (2, "")]),
+                ),
+                (
+                    "de_DE".to_string(),
+                    string_database(&[(1, "Musik"), (2, "")]),
+                ),
+            ]),
+        };
+        let reports = locale_set.coverage_report("en_US").unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].is_complete());
+    }
+
+    #[test]
+    fn requires_reference_locale() {
+        let locale_set = LocaleSet {
+            locales: BTreeMap::from([("de_DE".to_string(), string_database(&[(1, "Musik")]))]),
+        };
+        assert!(matches!(
+            locale_set.coverage_report("en_US"),
+            Err(SilverError::MissingLocale(locale)) if locale == "en_US"
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use crate::{
+    database::SilverResourceID,
+    localization::TranslationUnit,
+    localization::{apply_translations, placeholder_names, string_resources, LocaleSet},
+    silver_error::SilverError,
+};
+
+/// Columns preceding locale columns.
+const ID_COLUMN: &str = "id";
+const NAME_COLUMN: &str = "name";
+
+/// Marks a string that is present, but deliberately empty.
+/// Empty cells instead represent strings absent within, or untranslated for, a locale.
+const EMPTY_MARKER: &str = "<empty>";
+
+/// Converts a CSV error.
+fn csv_error(error: csv::Error) -> SilverError {
+    SilverError::InvalidTranslation(error.to_string())
+}
+
+/// Exports the strings of every locale as a single spreadsheet, i.e. CSV (`,`) or TSV (`\t`).
+///
+/// Every row represents one resource ID within the reference locale,
+/// alongside its `StrT` placeholder name, if known.
+/// Every following column represents a locale, beginning with the reference locale.
+/// Cells for resources absent within a locale are empty, while empty strings
+/// are written as `<empty>`.
+pub fn export_spreadsheet(
+    locale_set: &LocaleSet,
+    reference: &str,
+    delimiter: u8,
+) -> Result<String, SilverError> {
+    let reference_database = locale_set.get(reference)?;
+    let names = placeholder_names(reference_database);
+
+    // The reference locale comes first, followed by all others alphabetically.
+    let mut locale_codes = vec![reference];
+    locale_codes.extend(
+        locale_set
+            .locales
+            .keys()
+            .map(String::as_str)
+            .filter(|code| *code != reference),
+    );
+    let all_strings: Vec<_> = locale_codes
+        .iter()
+        .map(|code| string_resources(&locale_set.locales[*code]))
+        .collect();
+
+    // Resources only present within other locales are omitted, as
+    // `import_spreadsheet` expects IDs to match the reference locale's.
+    let all_ids: Vec<SilverResourceID> = all_strings[0].keys().copied().collect();
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+
+    let mut header = vec![ID_COLUMN, NAME_COLUMN];
+    header.extend(&locale_codes);
+    writer.write_record(&header).map_err(csv_error)?;
+
+    for id in all_ids {
+        let mut row = vec![id.to_string(), names.get(&id).cloned().unwrap_or_default()];
+        row.extend(all_strings.iter().map(|strings| {
+            match strings.get(&id).map(|string| string.text()) {
+                Some("") => EMPTY_MARKER.to_string(),
+                Some(text) => text.to_string(),
+                None => String::new(),
+            }
+        }));
+        writer.write_record(&row).map_err(csv_error)?;
+    }
+
+    let raw_contents = writer
+        .into_inner()
+        .map_err(|e| SilverError::InvalidTranslation(e.to_string()))?;
+    String::from_utf8(raw_contents).map_err(SilverError::from)
+}
+
+/// Parses translation units for every locale column of a spreadsheet created by
+/// `export_spreadsheet`, keyed by locale code.
+///
+/// The spreadsheet's resource IDs must match the reference locale's exactly.
+/// Empty cells are considered untranslated, and have no target.
+/// Cells holding `<empty>` have an empty target.
+pub fn parse_spreadsheet(
+    locale_set: &LocaleSet,
+    reference: &str,
+    contents: &str,
+    delimiter: u8,
+) -> Result<BTreeMap<String, Vec<TranslationUnit>>, SilverError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(contents.as_bytes());
+
+    // Determine which column represents what.
+    let header = reader.headers().map_err(csv_error)?.clone();
+    if header.get(0) != Some(ID_COLUMN) {
+        return Err(SilverError::InvalidTranslation(format!(
+            "expected first column to be \"{ID_COLUMN}\""
+        )));
+    }
+    let mut locale_columns: Vec<(usize, String)> = Vec::new();
+    for (index, column) in header.iter().enumerate().skip(1) {
+        if column == NAME_COLUMN {
+            continue;
+        }
+        // Ensure we have a database for every locale prior to modifying any.
+        locale_set.get(column)?;
+        locale_columns.push((index, column.to_string()));
+    }
+
+    let mut all_units: BTreeMap<String, Vec<TranslationUnit>> = BTreeMap::new();
+    let mut sheet_ids = BTreeSet::new();
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        let raw_id = record.get(0).unwrap_or_default();
+        let id = SilverResourceID::from_str(raw_id).map_err(|_| {
+            SilverError::InvalidTranslation(format!("invalid resource ID {raw_id:?}"))
+        })?;
+        if !sheet_ids.insert(id) {
+            return Err(SilverError::InvalidTranslation(format!(
+                "resource ID {id} is present multiple times"
+            )));
+        }
+
+        for (index, locale_code) in &locale_columns {
+            let text = record.get(*index).unwrap_or_default();
+            all_units
+                .entry(locale_code.clone())
+                .or_default()
+                .push(TranslationUnit {
+                    id,
+                    source: String::new(),
+                    target: match text {
+                        "" => None,
+                        EMPTY_MARKER => Some(String::new()),
+                        text => Some(text.to_string()),
+                    },
+                });
+        }
+    }
+
+    // Validate that no resources were added or removed.
+    let reference_ids: BTreeSet<SilverResourceID> = string_resources(locale_set.get(reference)?)
+        .into_keys()
+        .collect();
+    if sheet_ids != reference_ids {
+        let describe = |ids: Vec<&SilverResourceID>| {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            ids.join(", ")
+        };
+        return Err(SilverError::InvalidTranslation(format!(
+            "resource IDs do not match {reference}: missing [{}], unknown [{}]",
+            describe(reference_ids.difference(&sheet_ids).collect()),
+            describe(sheet_ids.difference(&reference_ids).collect()),
+        )));
+    }
+
+    Ok(all_units)
+}
+
+/// Imports strings from a spreadsheet created by `export_spreadsheet`, updating every locale
+/// column's database. Returns the amount of strings updated per locale.
+///
+/// See `parse_spreadsheet` for how cells are interpreted.
+pub fn import_spreadsheet(
+    locale_set: &mut LocaleSet,
+    reference: &str,
+    contents: &str,
+    delimiter: u8,
+) -> Result<BTreeMap<String, usize>, SilverError> {
+    let all_units = parse_spreadsheet(locale_set, reference, contents, delimiter)?;
+
+    // The reference may itself be updated, so we compare against it as it was.
+    let reference_database = locale_set.get(reference)?.clone();
+    let mut updated = BTreeMap::new();
+    for (locale_code, units) in all_units {
+        let database = locale_set
+            .locales
+            .get_mut(&locale_code)
+            .ok_or_else(|| SilverError::MissingLocale(locale_code.clone()))?;
+        let updated_count = apply_translations(database, &reference_database, &units)?;
+        updated.insert(locale_code, updated_count);
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{localization::string_resources, test_fixtures::string_database};
+
+    fn texts(locale_set: &LocaleSet, locale_code: &str) -> Vec<(u32, String)> {
+        string_resources(&locale_set.locales[locale_code])
+            .into_iter()
+            .map(|(id, string)| (id.0, string.text().to_string()))
+            .collect()
+    }
+
+    fn locale_set() -> LocaleSet {
+        LocaleSet {
+            locales: BTreeMap::from([
+                (
+                    "en_US".to_string(),
+                    string_database(&[(1, "Music"), (2, "Photos"), (3, "")]),
+                ),
+                (
+                    "de_DE".to_string(),
+                    string_database(&[(1, "Musik"), (3, "")]),
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn round_trips_empty_and_absent_strings() {
+        let mut locale_set = locale_set();
+        let contents = export_spreadsheet(&locale_set, "en_US", b',').unwrap();
+        assert_eq!(
+            contents,
+            "id,name,en_US,de_DE\n\
+             0x00000001,,Music,Musik\n\
+             0x00000002,,Photos,\n\
+             0x00000003,,<empty>,<empty>\n"
+        );
+
+        let updated = import_spreadsheet(&mut locale_set, "en_US", &contents, b',').unwrap();
+        assert!(updated.values().all(|updated| *updated == 0));
+        assert_eq!(
+            texts(&locale_set, "de_DE"),
+            [(1, "Musik".to_string()), (3, String::new())]
+        );
+    }
+
+    #[test]
+    fn empty_marker_clears_and_empty_cells_skip() {
+        let mut locale_set = locale_set();
+        let contents = "id\tde_DE\n0x00000001\t<empty>\n0x00000002\t\n0x00000003\tLeer\n";
+
+        let updated = import_spreadsheet(&mut locale_set, "en_US", contents, b'\t').unwrap();
+        assert_eq!(updated["de_DE"], 2);
+        assert_eq!(
+            texts(&locale_set, "de_DE"),
+            [(1, String::new()), (3, "Leer".to_string())]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{SilverDB, SilverResourceID},
+    localization::{string_resources, LocaleSet, TranslationUnit},
+    silver_error::SilverError,
+};
+
+/// The translation status of a single string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationStatus {
+    /// The string still holds the base locale's text.
+    Untranslated,
+    /// The string has been translated.
+    Translated,
+}
+
+/// Translation status for every string within a locale, stored alongside its database.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LocaleStatus {
+    /// The locale code this status describes, i.e. `ja_JP`.
+    pub locale: String,
+    /// The locale code of the database this locale was created from, if known.
+    pub base_locale: Option<String>,
+    /// The status of every `Str ` resource, keyed by ID.
+    pub strings: BTreeMap<SilverResourceID, TranslationStatus>,
+}
+
+impl LocaleStatus {
+    /// The file name the status of the given locale is stored as, i.e. `SilverDB.ja_JP.status.yaml`.
+    pub fn file_name(locale_code: &str) -> String {
+        format!("SilverDB.{locale_code}.status.yaml")
+    }
+
+    /// Marks every unit with a target as translated, returning the amount newly translated.
+    /// Units lacking a target are left as-is.
+    pub fn record(&mut self, units: &[TranslationUnit]) -> usize {
+        let mut translated = 0;
+        for unit in units.iter().filter(|unit| unit.target.is_some()) {
+            let previous = self.strings.insert(unit.id, TranslationStatus::Translated);
+            if previous != Some(TranslationStatus::Translated) {
+                translated += 1;
+            }
+        }
+        translated
+    }
+
+    /// Removes the targets of units yet to be translated, as they still hold the base locale's text.
+    pub fn mask_untranslated(&self, units: &mut [TranslationUnit]) {
+        for unit in units {
+            if self.strings.get(&unit.id) == Some(&TranslationStatus::Untranslated) {
+                unit.target = None;
+            }
+        }
+    }
+
+    /// The amount of strings yet to be translated.
+    pub fn untranslated_count(&self) -> usize {
+        self.strings
+            .values()
+            .filter(|status| **status == TranslationStatus::Untranslated)
+            .count()
+    }
+}
+
+/// A newly created locale, prior to translation.
+pub struct NewLocale {
+    /// The database for this locale, identical to its base.
+    pub database: SilverDB,
+    /// The status of this locale's strings, all of which are untranslated.
+    pub status: LocaleStatus,
+}
+
+impl NewLocale {
+    /// The file name this locale's database should be written as, i.e. `SilverDB.ja_JP.LE.bin`.
+    pub fn file_name(&self) -> String {
+        LocaleSet::file_name(&self.status.locale)
+    }
+}
+
+/// Creates a new locale from a reference locale's database.
+///
+/// All sections, their order and their flags are kept as-is. This includes
+/// `LDTm` and any other non-string sections, which should be reviewed separately.
+pub fn new_locale(
+    base: &SilverDB,
+    base_locale: Option<&str>,
+    locale_code: &str,
+) -> Result<NewLocale, SilverError> {
+    // Ensure this locale code results in a valid file name.
+    let file_name = LocaleSet::file_name(locale_code);
+    if LocaleSet::locale_code(&file_name) != Some(locale_code) || locale_code.contains('/') {
+        return Err(SilverError::InvalidLocale(locale_code.to_string()));
+    }
+
+    let strings = string_resources(base)
+        .into_keys()
+        .map(|id| (id, TranslationStatus::Untranslated))
+        .collect();
+
+    Ok(NewLocale {
+        database: base.clone(),
+        status: LocaleStatus {
+            locale: locale_code.to_string(),
+            base_locale: base_locale.map(str::to_string),
+            strings,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{string_database, unit};
+
+    #[test]
+    fn records_translated_units() {
+        let base = string_database(&[(1, "Music"), (2, "Photos")]);
+        let mut status = new_locale(&base, Some("en_US"), "de_DE").unwrap().status;
+        assert_eq!(status.untranslated_count(), 2);
+
+        let units = [unit(1, "Music", Some("Musik")), unit(2, "Photos", None)];
+        let mut masked = units.clone();
+        status.mask_untranslated(&mut masked);
+        assert_eq!(masked[0].target, None);
+
+        assert_eq!(status.record(&units), 1);
+        assert_eq!(status.record(&units), 0);
+        assert_eq!(
+            status.strings[&SilverResourceID(1)],
+            TranslationStatus::Translated
+        );
+        assert_eq!(
+            status.strings[&SilverResourceID(2)],
+            TranslationStatus::Untranslated
+        );
+
+        let mut masked = units.clone();
+        status.mask_untranslated(&mut masked);
+        assert_eq!(masked, units);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{
+    database::{SilverDB, SilverResource, SilverResourceID, SilverSection},
+    localization::string_resources,
+    section_content::SectionContent,
+    section_types::SectionType,
+    silver_error::SilverError,
+    silver_string::SilverString,
+};
+
+/// The translation of a single `Str ` resource, as exchanged with translation tools.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranslationUnit {
+    /// The ID of the `Str ` resource being translated.
+    pub id: SilverResourceID,
+    /// The reference locale's text.
+    pub source: String,
+    /// The translated text, if any.
+    pub target: Option<String>,
+}
+
+/// Creates translation units for every `Str ` resource within the reference database.
+/// Resources absent within the locale database have no target.
+pub fn translation_units(reference: &SilverDB, locale: &SilverDB) -> Vec<TranslationUnit> {
+    let locale_strings = string_resources(locale);
+    string_resources(reference)
+        .into_iter()
+        .map(|(id, source)| TranslationUnit {
+            id,
+            source: source.text().to_string(),
+            target: locale_strings
+                .get(&id)
+                .map(|target| target.text().to_string()),
+        })
+        .collect()
+}
+
+/// Ensures every unit translates a `Str ` resource present within the reference database.
+///
+/// Units from stale or foreign files would otherwise add unrelated resources.
+pub fn check_translation_ids(
+    reference: &SilverDB,
+    units: &[TranslationUnit],
+) -> Result<(), SilverError> {
+    let reference_strings = string_resources(reference);
+    let unknown_ids: Vec<String> = units
+        .iter()
+        .filter(|unit| !reference_strings.contains_key(&unit.id))
+        .map(|unit| unit.id.to_string())
+        .collect();
+    if !unknown_ids.is_empty() {
+        return Err(SilverError::InvalidTranslation(format!(
+            "resource IDs not present within the reference: [{}]",
+            unknown_ids.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Applies translated text to the `Str ` section of the given database, returning
+/// the amount of resources updated.
+///
+/// Units lacking a target are considered untranslated and skipped. Empty targets are
+/// deliberately empty translations, and are applied as-is.
+/// Resources absent within the database are inserted in order of their ID, but every
+/// unit must translate a resource within the reference database: see `check_translation_ids`.
+pub fn apply_translations(
+    database: &mut SilverDB,
+    reference: &SilverDB,
+    units: &[TranslationUnit],
+) -> Result<usize, SilverError> {
+    check_translation_ids(reference, units)?;
+
+    // Create a string section if this database somehow lacks one, similar to our reference's.
+    if database.find_section(&SectionType::String).is_none() {
+        let is_sequential = reference
+            .find_section(&SectionType::String)
+            .map_or(1, |section| section.is_sequential);
+        database.sections.push(SilverSection {
+            section_type: SectionType::String,
+            is_sequential,
+            resources: Vec::new(),
+        });
+    }
+    let section = database
+        .sections
+        .iter_mut()
+        .find(|section| section.section_type == SectionType::String)
+        .expect("string section should be present");
+
+    let mut updated = 0;
+    for unit in units {
+        let Some(target) = &unit.target else {
+            continue;
+        };
+
+        let existing = section.resources.iter().position(|r| r.id == unit.id);
+        match existing {
+            Some(index) => {
+                let resource = &mut section.resources[index];
+                match &mut resource.contents {
+                    SectionContent::String(string) if string.text() == target => continue,
+                    SectionContent::String(string) => string.set_text(target.clone()),
+                    contents => *contents = SectionContent::String(target.as_str().into()),
+                }
+            }
+            None => {
+                let index = section
+                    .resources
+                    .iter()
+                    .position(|r| r.id > unit.id)
+                    .unwrap_or(section.resources.len());
+                let resource = SilverResource {
+                    id: unit.id,
+                    contents: SectionContent::String(SilverString::from(target.as_str())),
+                };
+                section.resources.insert(index, resource);
+            }
+        }
+        updated += 1;
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{string_database, unit};
+
+    #[test]
+    fn applies_empty_targets_and_skips_missing() {
+        let reference = string_database(&[(1, "Music"), (2, "Photos")]);
+        let mut database = string_database(&[(1, "Musik"), (2, "Fotos")]);
+        let units = [unit(1, "Music", Some("")), unit(2, "Photos", None)];
+
+        assert_eq!(
+            apply_translations(&mut database, &reference, &units).unwrap(),
+            1
+        );
+        let texts: Vec<&str> = string_resources(&database)
+            .values()
+            .map(|string| string.text())
+            .collect();
+        assert_eq!(texts, ["", "Fotos"]);
+    }
+
+    #[test]
+    fn rejects_ids_absent_from_reference() {
+        let reference = string_database(&[(1, "Music")]);
+        let mut localized = string_database(&[(1, "Musik")]);
+        let units = [unit(1, "", Some("Musique")), unit(7, "", Some("Junk"))];
+
+        assert!(matches!(
+            apply_translations(&mut localized, &reference, &units),
+            Err(SilverError::InvalidTranslation(_))
+        ));
+        assert_eq!(
+            string_resources(&localized)[&SilverResourceID(1)].text(),
+            "Musik"
+        );
+    }
+
+    #[test]
+    fn creates_sections_like_the_reference() {
+        let mut reference = string_database(&[(1, "Music"), (2, "Photos")]);
+        reference.sections[0].is_sequential = 0;
+        let mut localized = SilverDB {
+            sections: Vec::new(),
+        };
+        let units = [unit(2, "", Some("Fotos")), unit(1, "", Some("Musik"))];
+
+        assert_eq!(
+            apply_translations(&mut localized, &reference, &units).unwrap(),
+            2
+        );
+        assert_eq!(localized.sections[0].is_sequential, 0);
+        let ids: Vec<u32> = localized.sections[0]
+            .resources
+            .iter()
+            .map(|r| r.id.0)
+            .collect();
+        assert_eq!(ids, [1, 2]);
+    }
+}
@@ -0,0 +1,259 @@
+use std::str::FromStr;
+
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::{database::SilverResourceID, localization::TranslationUnit, silver_error::SilverError};
+
+/// Supported XLIFF versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XliffVersion {
+    /// XLIFF 1.2, using `<trans-unit>` elements.
+    V1_2,
+    /// XLIFF 2.0, using `<unit>` and `<segment>` elements.
+    V2_0,
+}
+
+/// XLIFF expects BCP 47 language tags (i.e. `en-US` rather than `en_US`).
+fn language_tag(locale_code: &str) -> String {
+    locale_code.replace('_', "-")
+}
+
+/// Exports translation units as an XLIFF document.
+///
+/// The resource ID is used as the ID of each unit.
+/// Units lacking a translation are exported without a target.
+pub fn export_xliff(
+    units: &[TranslationUnit],
+    source_locale: &str,
+    target_locale: &str,
+    version: XliffVersion,
+) -> String {
+    let source_language = escape(language_tag(source_locale)).into_owned();
+    let target_language = escape(language_tag(target_locale)).into_owned();
+
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    match version {
+        XliffVersion::V1_2 => {
+            output.push_str(
+                "<xliff version=\"1.2\" xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n",
+            );
+            output.push_str(&format!(
+                "  <file original=\"Str\" datatype=\"plaintext\" source-language=\"{source_language}\" target-language=\"{target_language}\">\n"
+            ));
+            output.push_str("    <body>\n");
+            for unit in units {
+                output.push_str(&format!(
+                    "      <trans-unit id=\"{}\" xml:space=\"preserve\">\n",
+                    unit.id
+                ));
+                output.push_str(&format!(
+                    "        <source>{}</source>\n",
+                    escape(unit.source.as_str())
+                ));
+                if let Some(target) = &unit.target {
+                    output.push_str(&format!(
+                        "        <target>{}</target>\n",
+                        escape(target.as_str())
+                    ));
+                }
+                output.push_str("      </trans-unit>\n");
+            }
+            output.push_str("    </body>\n");
+            output.push_str("  </file>\n");
+        }
+        XliffVersion::V2_0 => {
+            output.push_str(&format!(
+                "<xliff version=\"2.0\" xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" srcLang=\"{source_language}\" trgLang=\"{target_language}\">\n"
+            ));
+            output.push_str("  <file id=\"Str\">\n");
+            for unit in units {
+                output.push_str(&format!(
+                    "    <unit id=\"{}\" xml:space=\"preserve\">\n",
+                    unit.id
+                ));
+                output.push_str("      <segment>\n");
+                output.push_str(&format!(
+                    "        <source>{}</source>\n",
+                    escape(unit.source.as_str())
+                ));
+                if let Some(target) = &unit.target {
+                    output.push_str(&format!(
+                        "        <target>{}</target>\n",
+                        escape(target.as_str())
+                    ));
+                }
+                output.push_str("      </segment>\n");
+                output.push_str("    </unit>\n");
+            }
+            output.push_str("  </file>\n");
+        }
+    }
+    output.push_str("</xliff>\n");
+
+    output
+}
+
+/// Converts an XML parsing error.
+fn xml_error(error: impl ToString) -> SilverError {
+    SilverError::InvalidTranslation(error.to_string())
+}
+
+/// Reads the given attribute's value, if present.
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, SilverError> {
+    let Some(attribute) = element.try_get_attribute(name).map_err(xml_error)? else {
+        return Ok(None);
+    };
+    let value = attribute.unescape_value().map_err(xml_error)?;
+    Ok(Some(value.into_owned()))
+}
+
+/// States indicating that a target has yet to be translated.
+/// (`new` and `needs-translation` are from XLIFF 1.2, `initial` from XLIFF 2.0.)
+const UNTRANSLATED_STATES: &[&str] = &["new", "needs-translation", "initial"];
+
+/// Where text within the document is currently being collected to.
+enum XliffField {
+    Source,
+    Target,
+}
+
+/// Parses translation units from an XLIFF 1.2 or 2.0 document.
+///
+/// Inline markup within sources and targets is discarded, retaining only text.
+/// Targets explicitly marked as untranslated are ignored. Empty targets (i.e. `<target/>`)
+/// are deliberately empty translations.
+pub fn import_xliff(contents: &str) -> Result<Vec<TranslationUnit>, SilverError> {
+    let mut reader = Reader::from_str(contents);
+
+    let mut units = Vec::new();
+    let mut current_unit: Option<TranslationUnit> = None;
+    let mut current_field: Option<XliffField> = None;
+    let mut target_untranslated = false;
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                // XLIFF 1.2 and 2.0 respectively.
+                b"trans-unit" | b"unit" => {
+                    let id = attribute(&element, "id")?.ok_or_else(|| {
+                        SilverError::InvalidTranslation("unit lacks an ID".to_string())
+                    })?;
+                    let id = SilverResourceID::from_str(&id).map_err(|_| {
+                        SilverError::InvalidTranslation(format!("invalid resource ID {id:?}"))
+                    })?;
+                    current_unit = Some(TranslationUnit {
+                        id,
+                        source: String::new(),
+                        target: None,
+                    });
+                    target_untranslated = false;
+                }
+                // XLIFF 2.0 places state upon segments.
+                b"segment" => {
+                    if let Some(state) = attribute(&element, "state")? {
+                        target_untranslated |= UNTRANSLATED_STATES.contains(&state.as_str());
+                    }
+                }
+                b"source" if current_unit.is_some() => current_field = Some(XliffField::Source),
+                b"target" if current_unit.is_some() => {
+                    // XLIFF 1.2 places state upon targets.
+                    if let Some(state) = attribute(&element, "state")? {
+                        target_untranslated |= UNTRANSLATED_STATES.contains(&state.as_str());
+                    }
+                    if let Some(unit) = current_unit.as_mut() {
+                        unit.target.get_or_insert_default();
+                    }
+                    current_field = Some(XliffField::Target);
+                }
+                _ => {}
+            },
+            // Empty targets hold no text, but are translated nonetheless.
+            Event::Empty(element) if element.local_name().as_ref() == b"target" => {
+                if let Some(state) = attribute(&element, "state")? {
+                    target_untranslated |= UNTRANSLATED_STATES.contains(&state.as_str());
+                }
+                if let Some(unit) = current_unit.as_mut() {
+                    unit.target.get_or_insert_default();
+                }
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(xml_error)?;
+                push_text(&mut current_unit, &current_field, &text);
+            }
+            Event::CData(data) => {
+                let data = data.into_inner();
+                let text = std::str::from_utf8(&data).map_err(xml_error)?;
+                push_text(&mut current_unit, &current_field, text);
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"source" | b"target" => current_field = None,
+                b"trans-unit" | b"unit" => {
+                    if let Some(mut unit) = current_unit.take() {
+                        if target_untranslated {
+                            unit.target = None;
+                        }
+                        units.push(unit);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(units)
+}
+
+/// Appends text to the field currently being read.
+fn push_text(unit: &mut Option<TranslationUnit>, field: &Option<XliffField>, text: &str) {
+    let Some(unit) = unit.as_mut() else {
+        return;
+    };
+    match field {
+        Some(XliffField::Source) => unit.source.push_str(text),
+        Some(XliffField::Target) => unit.target.get_or_insert_default().push_str(text),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::unit;
+
+    #[test]
+    fn round_trips_units() {
+        let units = [
+            unit(1, "Music", Some("Musik")),
+            unit(2, "Photos", None),
+            unit(3, "Line one\nLine two", Some("Zeile eins\nZeile zwei")),
+            unit(
+                4,
+                "<b>&amp; \"Quoted\"</b>",
+                Some("<b>&amp; \u{201e}Zitiert\u{201c}</b>"),
+            ),
+            unit(5, "Blank", Some("")),
+        ];
+        for version in [XliffVersion::V1_2, XliffVersion::V2_0] {
+            let contents = export_xliff(&units, "en_US", "de_DE", version);
+            assert_eq!(import_xliff(&contents).unwrap(), units, "{version:?}");
+        }
+    }
+
+    #[test]
+    fn reads_empty_and_untranslated_targets() {
+        let contents = r#"<xliff version="1.2"><file><body>
+            <trans-unit id="0x00000001"><source>Music</source><target/></trans-unit>
+            <trans-unit id="0x00000002"><source>Photos</source><target state="new">Fotos</target></trans-unit>
+        </body></file></xliff>"#;
+        assert_eq!(
+            import_xliff(contents).unwrap(),
+            [unit(1, "Music", Some("")), unit(2, "Photos", None)]
+        );
+    }
+}
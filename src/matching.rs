@@ -0,0 +1,687 @@
+use std::{collections::HashMap, fmt};
+
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{SilverDB, SilverResource, SilverResourceID, SilverSection},
+    section_content::SectionContent,
+    symbols::SymbolTable,
+};
+
+/// How a resource was matched between two databases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatchMethod {
+    /// The resource has the same ID and identical contents.
+    Identical,
+    /// The resource has identical contents, but a different ID.
+    ContentHash,
+    /// The resource has similar contents (strings or images).
+    Similarity,
+    /// The resource has the same ID, but dissimilar contents.
+    SameId,
+    /// The resource is only present within the old database.
+    Removed,
+    /// The resource is only present within the new database.
+    Added,
+}
+
+/// A single resource's mapping between two databases.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MappingEntry {
+    /// The four-character name of this resource's section, i.e. `BMap`.
+    pub section: String,
+    /// The ID of this resource within the old database, if present.
+    pub old_id: Option<SilverResourceID>,
+    /// The ID of this resource within the new database, if present.
+    pub new_id: Option<SilverResourceID>,
+    /// How this resource was matched.
+    pub method: MatchMethod,
+    /// How similar the matched contents are, from 0.0 to 1.0.
+    pub score: f32,
+}
+
+/// A mapping of resources from an old database to a new database.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ResourceMapping {
+    pub entries: Vec<MappingEntry>,
+}
+
+/// Options controlling how resources are matched.
+#[derive(Clone, Debug)]
+pub struct MatchOptions {
+    /// The minimum similarity, from 0.0 to 1.0, for strings or images to be considered a match.
+    pub minimum_similarity: f32,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        MatchOptions {
+            minimum_similarity: 0.8,
+        }
+    }
+}
+
+/// A symbol which could not be migrated unambiguously.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SymbolConflict {
+    /// The symbol's ID is present within several sections, each mapping to a different new ID.
+    Ambiguous {
+        name: String,
+        new_ids: Vec<SilverResourceID>,
+    },
+    /// Another symbol was already migrated to the same new ID.
+    Duplicate {
+        name: String,
+        new_id: SilverResourceID,
+        existing: String,
+    },
+}
+
+impl fmt::Display for SymbolConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolConflict::Ambiguous { name, new_ids } => {
+                let new_ids: Vec<String> = new_ids.iter().map(ToString::to_string).collect();
+                write!(
+                    f,
+                    "{name} maps to several resources: {}",
+                    new_ids.join(", ")
+                )
+            }
+            SymbolConflict::Duplicate {
+                name,
+                new_id,
+                existing,
+            } => write!(
+                f,
+                "{name} maps to {new_id}, which {existing} already maps to"
+            ),
+        }
+    }
+}
+
+/// The result of migrating a symbol table through a mapping.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolMigration {
+    /// Symbols with their new IDs.
+    pub symbols: SymbolTable,
+    /// Names of symbols whose resource was removed.
+    pub unmigrated: Vec<String>,
+    /// Symbols which could not be migrated unambiguously.
+    pub conflicts: Vec<SymbolConflict>,
+}
+
+impl ResourceMapping {
+    /// Determines which ID the given old ID within the given section has
+    /// within the new database, if any.
+    ///
+    /// IDs are only unique within their section (i.e. `Str ` and `StrT` share IDs).
+    pub fn migrate(&self, section: &str, old_id: SilverResourceID) -> Option<SilverResourceID> {
+        self.entries
+            .iter()
+            .find(|entry| entry.section == section && entry.old_id == Some(old_id))
+            .and_then(|entry| entry.new_id)
+    }
+
+    /// Migrates a symbol table's IDs from the old database to the new database.
+    ///
+    /// Symbols limited to a section migrate with that section alone. Otherwise,
+    /// if an ID is present within several sections, they must agree upon its new ID.
+    pub fn migrate_symbols(&self, symbols: &SymbolTable) -> SymbolMigration {
+        let mut migration = SymbolMigration::default();
+        for (id, section_type, name) in symbols.iter() {
+            let section_name = section_type.map(|section_type| section_type.to_name());
+            let mut new_ids: Vec<SilverResourceID> = self
+                .entries
+                .iter()
+                .filter(|entry| entry.old_id == Some(id))
+                .filter(|entry| {
+                    section_name
+                        .as_ref()
+                        .is_none_or(|name| entry.section == *name)
+                })
+                .filter_map(|entry| entry.new_id)
+                .collect();
+            new_ids.sort();
+            new_ids.dedup();
+
+            let new_id = match new_ids.as_slice() {
+                [] => {
+                    migration.unmigrated.push(name.to_string());
+                    continue;
+                }
+                [new_id] => *new_id,
+                _ => {
+                    migration.conflicts.push(SymbolConflict::Ambiguous {
+                        name: name.to_string(),
+                        new_ids,
+                    });
+                    continue;
+                }
+            };
+
+            // Inserting would otherwise silently replace the other symbol.
+            let existing = match section_type {
+                Some(section_type) => migration.symbols.name_in(section_type, new_id),
+                None => migration.symbols.name(new_id),
+            };
+            if let Some(existing) = existing {
+                migration.conflicts.push(SymbolConflict::Duplicate {
+                    name: name.to_string(),
+                    new_id,
+                    existing: existing.to_string(),
+                });
+                continue;
+            }
+            // Our names were already validated as unique.
+            _ = match section_type {
+                Some(section_type) => {
+                    migration
+                        .symbols
+                        .insert_in(section_type, new_id, name.to_string())
+                }
+                None => migration.symbols.insert(new_id, name.to_string()),
+            };
+        }
+        migration
+    }
+}
+
+/// The Levenshtein distance between two strings, by character.
+fn levenshtein(first: &[char], second: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=second.len()).collect();
+    let mut current = vec![0; second.len() + 1];
+    for (i, first_char) in first.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, second_char) in second.iter().enumerate() {
+            let substitution = previous[j] + usize::from(first_char != second_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[second.len()]
+}
+
+/// Contents decoded for similarity comparisons.
+enum Comparable {
+    Text(Vec<char>),
+    Image(RgbaImage),
+    Other,
+}
+
+impl Comparable {
+    fn new(contents: &SectionContent) -> Self {
+        match contents {
+            SectionContent::String(string) => Comparable::Text(string.text().chars().collect()),
+            SectionContent::Bitmap(Some(bitmap)) => {
+                match image::load_from_memory_with_format(&bitmap.contents, image::ImageFormat::Png)
+                {
+                    Ok(image) => Comparable::Image(image.to_rgba8()),
+                    Err(_) => Comparable::Other,
+                }
+            }
+            _ => Comparable::Other,
+        }
+    }
+
+    /// How similar these contents are, from 0.0 to 1.0.
+    fn similarity(&self, other: &Comparable) -> f32 {
+        match (self, other) {
+            (Comparable::Text(first), Comparable::Text(second)) => {
+                let longest = first.len().max(second.len());
+                if longest == 0 {
+                    return 1.0;
+                }
+                // Strings differing greatly in length cannot be similar, so avoid comparing them.
+                let shortest = first.len().min(second.len());
+                if (shortest as f32) / (longest as f32) < 0.5 {
+                    return 0.0;
+                }
+                1.0 - (levenshtein(first, second) as f32 / longest as f32)
+            }
+            (Comparable::Image(first), Comparable::Image(second)) => {
+                if first.dimensions() != second.dimensions() || first.is_empty() {
+                    return 0.0;
+                }
+                let total_difference: u64 = first
+                    .as_raw()
+                    .iter()
+                    .zip(second.as_raw())
+                    .map(|(a, b)| a.abs_diff(*b) as u64)
+                    .sum();
+                let mean_difference = total_difference as f32 / first.as_raw().len() as f32;
+                1.0 - (mean_difference / 255.0)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// Matches resources within a single section.
+fn match_section(
+    old_section: &SilverSection,
+    new_section: &SilverSection,
+    options: &MatchOptions,
+) -> Vec<MappingEntry> {
+    let section = old_section.section_type.to_name();
+    let entry = |old: Option<&SilverResource>,
+                 new: Option<&SilverResource>,
+                 method: MatchMethod,
+                 score: f32| MappingEntry {
+        section: section.clone(),
+        old_id: old.map(|r| r.id),
+        new_id: new.map(|r| r.id),
+        method,
+        score,
+    };
+
+    let old_hashes: Vec<String> = old_section
+        .resources
+        .iter()
+        .map(|r| r.contents.content_hash())
+        .collect();
+    let new_hashes: Vec<String> = new_section
+        .resources
+        .iter()
+        .map(|r| r.contents.content_hash())
+        .collect();
+
+    // Index our new resources by their ID and hash.
+    let mut new_by_id: HashMap<SilverResourceID, usize> = HashMap::new();
+    let mut new_by_hash: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, resource) in new_section.resources.iter().enumerate() {
+        new_by_id.insert(resource.id, index);
+        new_by_hash
+            .entry(new_hashes[index].as_str())
+            .or_default()
+            .push(index);
+    }
+
+    // Which new resource every old resource is matched with, if any.
+    let mut old_matches: Vec<Option<(usize, MatchMethod, f32)>> =
+        vec![None; old_section.resources.len()];
+    let mut new_matched = vec![false; new_section.resources.len()];
+
+    // First, match identical resources.
+    for (old_index, resource) in old_section.resources.iter().enumerate() {
+        if let Some(&new_index) = new_by_id.get(&resource.id) {
+            if old_hashes[old_index] == new_hashes[new_index] {
+                old_matches[old_index] = Some((new_index, MatchMethod::Identical, 1.0));
+                new_matched[new_index] = true;
+            }
+        }
+    }
+
+    // Next, match resources which have moved, preferring the closest ID.
+    for (old_index, resource) in old_section.resources.iter().enumerate() {
+        if old_matches[old_index].is_some() {
+            continue;
+        }
+        let Some(candidates) = new_by_hash.get(old_hashes[old_index].as_str()) else {
+            continue;
+        };
+        let closest = candidates
+            .iter()
+            .filter(|index| !new_matched[**index])
+            .min_by_key(|index| new_section.resources[**index].id.0.abs_diff(resource.id.0));
+        if let Some(&new_index) = closest {
+            old_matches[old_index] = Some((new_index, MatchMethod::ContentHash, 1.0));
+            new_matched[new_index] = true;
+        }
+    }
+
+    // Then, match similar strings and images.
+    let new_comparables: Vec<Option<Comparable>> = new_section
+        .resources
+        .iter()
+        .enumerate()
+        .map(|(index, r)| (!new_matched[index]).then(|| Comparable::new(&r.contents)))
+        .collect();
+    for (old_index, resource) in old_section.resources.iter().enumerate() {
+        if old_matches[old_index].is_some() {
+            continue;
+        }
+        let old_comparable = Comparable::new(&resource.contents);
+        let best = new_comparables
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !new_matched[*index])
+            .filter_map(|(index, comparable)| {
+                let score = old_comparable.similarity(comparable.as_ref()?);
+                Some((index, score))
+            })
+            .filter(|(_, score)| *score >= options.minimum_similarity)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        if let Some((new_index, score)) = best {
+            old_matches[old_index] = Some((new_index, MatchMethod::Similarity, score));
+            new_matched[new_index] = true;
+        }
+    }
+
+    // Lastly, fall back to matching IDs.
+    for (old_index, resource) in old_section.resources.iter().enumerate() {
+        if old_matches[old_index].is_some() {
+            continue;
+        }
+        if let Some(&new_index) = new_by_id.get(&resource.id) {
+            if !new_matched[new_index] {
+                old_matches[old_index] = Some((new_index, MatchMethod::SameId, 0.0));
+                new_matched[new_index] = true;
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (old_index, old_match) in old_matches.into_iter().enumerate() {
+        let old = Some(&old_section.resources[old_index]);
+        entries.push(match old_match {
+            Some((new_index, method, score)) => {
+                entry(old, Some(&new_section.resources[new_index]), method, score)
+            }
+            None => entry(old, None, MatchMethod::Removed, 0.0),
+        });
+    }
+    for (new_index, matched) in new_matched.into_iter().enumerate() {
+        if !matched {
+            let new = Some(&new_section.resources[new_index]);
+            entries.push(entry(None, new, MatchMethod::Added, 0.0));
+        }
+    }
+    entries
+}
+
+/// Matches resources between two versions of a database, i.e. across firmware releases.
+///
+/// Resources are matched within sections of the same type, in order of preference:
+/// by identical ID and contents, by identical contents, by similar contents
+/// (for strings and images), and finally by ID alone.
+pub fn match_resources(
+    old_database: &SilverDB,
+    new_database: &SilverDB,
+    options: &MatchOptions,
+) -> ResourceMapping {
+    let empty_section = |section: &SilverSection| SilverSection {
+        section_type: section.section_type,
+        is_sequential: section.is_sequential,
+        resources: Vec::new(),
+    };
+
+    let mut entries = Vec::new();
+    for old_section in &old_database.sections {
+        // Sections only present within the old database have all resources removed.
+        let entire_removal;
+        let new_section = match new_database.find_section(&old_section.section_type) {
+            Some(new_section) => new_section,
+            None => {
+                entire_removal = empty_section(old_section);
+                &entire_removal
+            }
+        };
+        entries.extend(match_section(old_section, new_section, options));
+    }
+
+    // Similarly, sections only present within the new database have all resources added.
+    for new_section in &new_database.sections {
+        if old_database
+            .find_section(&new_section.section_type)
+            .is_none()
+        {
+            entries.extend(match_section(
+                &empty_section(new_section),
+                new_section,
+                options,
+            ));
+        }
+    }
+    ResourceMapping { entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitmap::{BitmapImage, RawBitmapType},
+        section_types::SectionType,
+        test_fixtures::{section, string_database},
+    };
+
+    fn entry(section: &str, old_id: u32, new_id: u32) -> MappingEntry {
+        MappingEntry {
+            section: section.to_string(),
+            old_id: Some(SilverResourceID(old_id)),
+            new_id: Some(SilverResourceID(new_id)),
+            method: MatchMethod::ContentHash,
+            score: 1.0,
+        }
+    }
+
+    fn symbols(entries: &[(u32, &str)]) -> SymbolTable {
+        let mut symbols = SymbolTable::new();
+        for (id, name) in entries {
+            symbols
+                .insert(SilverResourceID(*id), name.to_string())
+                .unwrap();
+        }
+        symbols
+    }
+
+    #[test]
+    fn migrate_is_keyed_by_section() {
+        let mapping = ResourceMapping {
+            entries: vec![entry("StrT", 1, 10), entry("Str ", 1, 20)],
+        };
+        assert_eq!(
+            mapping.migrate("Str ", SilverResourceID(1)),
+            Some(SilverResourceID(20))
+        );
+        assert_eq!(
+            mapping.migrate("StrT", SilverResourceID(1)),
+            Some(SilverResourceID(10))
+        );
+        assert_eq!(mapping.migrate("BMap", SilverResourceID(1)), None);
+    }
+
+    #[test]
+    fn migrates_symbols() {
+        let mapping = ResourceMapping {
+            entries: vec![
+                entry("Str ", 1, 10),
+                entry("StrT", 1, 10),
+                MappingEntry {
+                    new_id: None,
+                    method: MatchMethod::Removed,
+                    ..entry("Str ", 2, 0)
+                },
+            ],
+        };
+        let migration = mapping.migrate_symbols(&symbols(&[(1, "title"), (2, "gone")]));
+        assert_eq!(migration.symbols.name(SilverResourceID(10)), Some("title"));
+        assert_eq!(migration.unmigrated, vec!["gone".to_string()]);
+        assert!(migration.conflicts.is_empty());
+    }
+
+    #[test]
+    fn reports_ambiguous_symbols() {
+        let mapping = ResourceMapping {
+            entries: vec![entry("Str ", 1, 10), entry("StrT", 1, 11)],
+        };
+        let migration = mapping.migrate_symbols(&symbols(&[(1, "title")]));
+        assert!(migration.symbols.is_empty());
+        assert_eq!(
+            migration.conflicts,
+            vec![SymbolConflict::Ambiguous {
+                name: "title".to_string(),
+                new_ids: vec![SilverResourceID(10), SilverResourceID(11)],
+            }]
+        );
+    }
+
+    #[test]
+    fn migrates_scoped_symbols_with_their_section() {
+        let mapping = ResourceMapping {
+            entries: vec![entry("Str ", 1, 10), entry("StrT", 1, 11)],
+        };
+        let mut symbols = SymbolTable::new();
+        symbols
+            .insert_in(
+                SectionType::String,
+                SilverResourceID(1),
+                "title".to_string(),
+            )
+            .unwrap();
+
+        let migration = mapping.migrate_symbols(&symbols);
+        assert!(migration.conflicts.is_empty());
+        let id = SilverResourceID(10);
+        assert_eq!(
+            migration.symbols.name_in(SectionType::String, id),
+            Some("title")
+        );
+        assert_eq!(migration.symbols.name(id), None);
+    }
+
+    #[test]
+    fn reports_duplicate_symbols() {
+        let mapping = ResourceMapping {
+            entries: vec![entry("Str ", 1, 10), entry("Str ", 2, 10)],
+        };
+        let migration = mapping.migrate_symbols(&symbols(&[(1, "first"), (2, "second")]));
+        assert_eq!(migration.symbols.name(SilverResourceID(10)), Some("first"));
+        assert_eq!(
+            migration.conflicts,
+            vec![SymbolConflict::Duplicate {
+                name: "second".to_string(),
+                new_id: SilverResourceID(10),
+                existing: "first".to_string(),
+            }]
+        );
+    }
+
+    /// A bitmap holding the given pixels.
+    fn bitmap(pixels: &RgbaImage) -> SectionContent {
+        let mut png_writer = std::io::Cursor::new(Vec::new());
+        pixels
+            .write_to(&mut png_writer, image::ImageFormat::Png)
+            .unwrap();
+        SectionContent::Bitmap(Some(BitmapImage {
+            width: pixels.width(),
+            height: pixels.height(),
+            rendered_width: 0,
+            format_type: RawBitmapType::Argb8888,
+            resource_id: 0,
+            contents: png_writer.into_inner(),
+            raw: None,
+        }))
+    }
+
+    /// The new ID, method and score the given old resource was matched with.
+    fn mapped(
+        mapping: &ResourceMapping,
+        section: &str,
+        old_id: u32,
+    ) -> (Option<u32>, MatchMethod, f32) {
+        let entry = mapping
+            .entries
+            .iter()
+            .find(|entry| {
+                entry.section == section && entry.old_id == Some(SilverResourceID(old_id))
+            })
+            .expect("old resource should be mapped");
+        (entry.new_id.map(|id| id.0), entry.method, entry.score)
+    }
+
+    #[test]
+    fn matches_strings_across_versions() {
+        let old_database = string_database(&[
+            (1, "Photos"),
+            (2, "Music"),
+            (3, "Play all songs"),
+            (4, "Settings"),
+            (6, "Removed text"),
+        ]);
+        let new_database = string_database(&[
+            (1, "Photos"),
+            (5, "Music"),
+            (7, "Play all songs!"),
+            (4, "Einstellungen"),
+            (9, "Brand new string entirely"),
+        ]);
+
+        let mapping = match_resources(&old_database, &new_database, &MatchOptions::default());
+        assert_eq!(
+            mapping.migrate("Str ", SilverResourceID(1)),
+            Some(SilverResourceID(1))
+        );
+        assert_eq!(
+            mapped(&mapping, "Str ", 1),
+            (Some(1), MatchMethod::Identical, 1.0)
+        );
+        assert_eq!(
+            mapped(&mapping, "Str ", 2),
+            (Some(5), MatchMethod::ContentHash, 1.0)
+        );
+        // A single character was appended to 14 others.
+        assert_eq!(
+            mapped(&mapping, "Str ", 3),
+            (Some(7), MatchMethod::Similarity, 1.0 - 1.0 / 15.0)
+        );
+        assert_eq!(
+            mapped(&mapping, "Str ", 4),
+            (Some(4), MatchMethod::SameId, 0.0)
+        );
+        assert_eq!(
+            mapped(&mapping, "Str ", 6),
+            (None, MatchMethod::Removed, 0.0)
+        );
+
+        let added: Vec<_> = mapping
+            .entries
+            .iter()
+            .filter(|entry| entry.method == MatchMethod::Added)
+            .map(|entry| entry.new_id)
+            .collect();
+        assert_eq!(added, [Some(SilverResourceID(9))]);
+    }
+
+    #[test]
+    fn matches_slightly_changed_images() {
+        let original = RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]));
+        let mut changed = original.clone();
+        changed.put_pixel(0, 0, image::Rgba([255, 0, 255, 255]));
+        let unrelated = RgbaImage::from_pixel(4, 4, image::Rgba([0, 255, 255, 0]));
+
+        let old_database = SilverDB {
+            sections: vec![section(
+                "BMap",
+                vec![
+                    (0x0dad0001, bitmap(&original)),
+                    (0x0dad0002, bitmap(&original)),
+                ],
+            )],
+        };
+        let new_database = SilverDB {
+            sections: vec![section(
+                "BMap",
+                vec![
+                    (0x0dad0001, bitmap(&unrelated)),
+                    (0x0dad0003, bitmap(&changed)),
+                ],
+            )],
+        };
+
+        let mapping = match_resources(&old_database, &new_database, &MatchOptions::default());
+        // One channel of 64 differs entirely.
+        let (new_id, method, score) = mapped(&mapping, "BMap", 0x0dad0001);
+        assert_eq!(
+            (new_id, method),
+            (Some(0x0dad0003), MatchMethod::Similarity)
+        );
+        assert!((score - 63.0 / 64.0).abs() < 1e-6, "{score}");
+        assert_eq!(
+            mapped(&mapping, "BMap", 0x0dad0002),
+            (None, MatchMethod::Removed, 0.0)
+        );
+    }
+}
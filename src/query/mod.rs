@@ -0,0 +1,425 @@
+mod parser;
+
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use regex::Regex;
+
+use crate::{
+    database::{SilverDB, SilverResource, SilverSection},
+    section_content::SectionContent,
+    silver_error::SilverError,
+};
+
+/// Fields of a resource that filters can compare against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    /// The four-character name of the resource's section, without trailing spaces.
+    Section,
+    /// The resource's ID.
+    Id,
+    /// The type of content: `string`, `bitmap`, `empty` (zero-length bitmaps) or `raw`.
+    Kind,
+    /// The length of the resource's raw contents. Unavailable for bitmaps.
+    Size,
+    /// The hash of the resource's contents, per `SectionContent::content_hash`.
+    Hash,
+    /// The text of a string.
+    Text,
+    /// The encoding of a string.
+    Encoding,
+    /// The format of a bitmap, i.e. `Argb4444`.
+    Format,
+    Width,
+    Height,
+    RenderedWidth,
+}
+
+impl Field {
+    const ALL: [Field; 11] = [
+        Field::Section,
+        Field::Id,
+        Field::Kind,
+        Field::Size,
+        Field::Hash,
+        Field::Text,
+        Field::Encoding,
+        Field::Format,
+        Field::Width,
+        Field::Height,
+        Field::RenderedWidth,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Field::Section => "section",
+            Field::Id => "id",
+            Field::Kind => "kind",
+            Field::Size => "size",
+            Field::Hash => "hash",
+            Field::Text => "text",
+            Field::Encoding => "encoding",
+            Field::Format => "format",
+            Field::Width => "width",
+            Field::Height => "height",
+            Field::RenderedWidth => "rendered_width",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Field> {
+        Field::ALL.into_iter().find(|field| field.name() == name)
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Field::Id | Field::Size | Field::Width | Field::Height | Field::RenderedWidth
+        )
+    }
+
+    /// Whether this field is compared without regard to case or surrounding whitespace.
+    /// Free-form text is always compared exactly.
+    fn is_name(&self) -> bool {
+        matches!(
+            self,
+            Field::Section | Field::Kind | Field::Encoding | Field::Format
+        )
+    }
+
+    fn supports(&self, operator: Operator) -> bool {
+        match operator {
+            Operator::Equal | Operator::NotEqual => true,
+            Operator::Matches => !self.is_numeric(),
+            Operator::Less
+            | Operator::LessOrEqual
+            | Operator::Greater
+            | Operator::GreaterOrEqual => self.is_numeric(),
+        }
+    }
+
+    /// Determines the value of this field for the given resource.
+    /// Fields not applicable to the resource's type of content are `None`.
+    fn value<'a>(
+        &self,
+        section: &'a SilverSection,
+        resource: &'a SilverResource,
+    ) -> Option<FieldValue<'a>> {
+        let contents = &resource.contents;
+        let bitmap = match contents {
+            SectionContent::Bitmap(Some(bitmap)) => Some(bitmap),
+            _ => None,
+        };
+        let string = match contents {
+            SectionContent::String(string) => Some(string),
+            _ => None,
+        };
+
+        let value = match self {
+            Field::Section => {
+                let name = section.section_type.to_name().trim_end().to_string();
+                FieldValue::Text(Cow::Owned(name))
+            }
+            Field::Id => FieldValue::Number(resource.id.0 as u64),
+            Field::Kind => FieldValue::Text(Cow::Borrowed(match contents {
+                SectionContent::String(_) => "string",
+                SectionContent::Bitmap(Some(_)) => "bitmap",
+                SectionContent::Bitmap(None) => "empty",
+                SectionContent::DateTimeLocale(_) | SectionContent::Unknown(_) => "raw",
+            })),
+            Field::Size => FieldValue::Number(match contents {
+                SectionContent::String(string) => string.encode().len() as u64,
+                SectionContent::DateTimeLocale(raw_contents)
+                | SectionContent::Unknown(raw_contents) => raw_contents.len() as u64,
+                SectionContent::Bitmap(_) => return None,
+            }),
+            Field::Hash => FieldValue::Text(Cow::Owned(contents.content_hash())),
+            Field::Text => FieldValue::Text(Cow::Borrowed(string?.text())),
+            Field::Encoding => {
+                let encoding = format!("{:?}", string?.encoding());
+                FieldValue::Text(Cow::Owned(encoding))
+            }
+            Field::Format => {
+                let format = format!("{:?}", bitmap?.format_type);
+                FieldValue::Text(Cow::Owned(format))
+            }
+            Field::Width => FieldValue::Number(bitmap?.width as u64),
+            Field::Height => FieldValue::Number(bitmap?.height as u64),
+            Field::RenderedWidth => FieldValue::Number(bitmap?.rendered_width as u64),
+        };
+        Some(value)
+    }
+}
+
+/// Operators comparing a field against a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    /// Matches a regular expression.
+    Matches,
+}
+
+/// A value to compare fields against.
+#[derive(Clone, Debug)]
+enum Value {
+    Text(String),
+    Number(u64),
+    Regex(Regex),
+    /// Any of the given values, as used by `in`.
+    List(Vec<Value>),
+}
+
+/// The value of a field for a specific resource.
+enum FieldValue<'a> {
+    Text(Cow<'a, str>),
+    Number(u64),
+}
+
+#[derive(Clone, Debug)]
+struct Comparison {
+    field: Field,
+    operator: Operator,
+    value: Value,
+}
+
+impl Comparison {
+    fn matches(&self, section: &SilverSection, resource: &SilverResource) -> bool {
+        // Resources lacking this field never match.
+        let Some(field_value) = self.field.value(section, resource) else {
+            return false;
+        };
+        self.compare(&field_value, &self.value)
+    }
+
+    fn compare(&self, field_value: &FieldValue, value: &Value) -> bool {
+        match (field_value, value) {
+            (_, Value::List(values)) => values.iter().any(|value| self.compare(field_value, value)),
+            (FieldValue::Number(actual), Value::Number(expected)) => match self.operator {
+                Operator::Equal => actual == expected,
+                Operator::NotEqual => actual != expected,
+                Operator::Less => actual < expected,
+                Operator::LessOrEqual => actual <= expected,
+                Operator::Greater => actual > expected,
+                Operator::GreaterOrEqual => actual >= expected,
+                Operator::Matches => false,
+            },
+            (FieldValue::Text(actual), Value::Regex(regex)) => regex.is_match(actual),
+            (FieldValue::Text(actual), Value::Text(expected)) => {
+                let is_equal = if self.field.is_name() {
+                    actual.trim().eq_ignore_ascii_case(expected.trim())
+                } else {
+                    actual == expected
+                };
+                match self.operator {
+                    Operator::Equal => is_equal,
+                    Operator::NotEqual => !is_equal,
+                    _ => false,
+                }
+            }
+            // The parser ensures values are suitable for their field.
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expression {
+    Comparison(Comparison),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+}
+
+impl Expression {
+    fn matches(&self, section: &SilverSection, resource: &SilverResource) -> bool {
+        match self {
+            Expression::Comparison(comparison) => comparison.matches(section, resource),
+            Expression::And(left, right) => {
+                left.matches(section, resource) && right.matches(section, resource)
+            }
+            Expression::Or(left, right) => {
+                left.matches(section, resource) || right.matches(section, resource)
+            }
+            Expression::Not(expression) => !expression.matches(section, resource),
+        }
+    }
+}
+
+/// A filter expression selecting resources within a database.
+///
+/// Comparisons take the form `field operator value`, and can be combined
+/// with `&&`, `||`, `!` and parentheses. For example:
+/// ```text
+/// section == "Str" && text ~ /Photos/
+/// section in [BMap, StBM] && width > 100 && format == Argb4444
+/// ```
+///
+/// Available fields are `section`, `id`, `kind`, `size`, `hash`, `text`, `encoding`,
+/// `format`, `width`, `height` and `rendered_width`. Numeric fields support
+/// `==`, `!=`, `<`, `<=`, `>` and `>=`; all others support `==`, `!=` and `~`
+/// (regular expressions, i.e. `/photos/i`). Any field supports `in [a, b, ...]`.
+///
+/// Resources lacking a field, such as `width` for strings, never match comparisons against it.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    source: String,
+    expression: Expression,
+}
+
+impl Filter {
+    /// Parses a filter expression.
+    pub fn parse(filter: &str) -> Result<Self, SilverError> {
+        Ok(Filter {
+            source: filter.to_string(),
+            expression: parser::parse(filter)?,
+        })
+    }
+
+    /// Whether the given resource within the given section matches this filter.
+    pub fn matches(&self, section: &SilverSection, resource: &SilverResource) -> bool {
+        self.expression.matches(section, resource)
+    }
+
+    /// All resources matching this filter, alongside their section.
+    pub fn find<'a>(&self, database: &'a SilverDB) -> Vec<(&'a SilverSection, &'a SilverResource)> {
+        database
+            .sections
+            .iter()
+            .flat_map(|section| {
+                section
+                    .resources
+                    .iter()
+                    .map(move |resource| (section, resource))
+            })
+            .filter(|(section, resource)| self.matches(section, resource))
+            .collect()
+    }
+
+    /// Creates a copy of the given database holding only matching resources.
+    /// Sections without any matching resources are omitted.
+    pub fn apply(&self, database: &SilverDB) -> SilverDB {
+        let sections = database
+            .sections
+            .iter()
+            .filter_map(|section| {
+                let resources: Vec<SilverResource> = section
+                    .resources
+                    .iter()
+                    .filter(|resource| self.matches(section, resource))
+                    .cloned()
+                    .collect();
+                if resources.is_empty() {
+                    return None;
+                }
+                Some(SilverSection {
+                    section_type: section.section_type,
+                    is_sequential: section.is_sequential,
+                    resources,
+                })
+            })
+            .collect();
+        SilverDB { sections }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = SilverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::parse(s)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitmap::{BitmapImage, RawBitmapType},
+        test_fixtures::section,
+    };
+
+    fn bitmap(width: u32, format_type: RawBitmapType) -> SectionContent {
+        SectionContent::Bitmap(Some(BitmapImage {
+            width,
+            height: 10,
+            rendered_width: 0,
+            format_type,
+            resource_id: 0,
+            contents: Vec::new(),
+            raw: None,
+        }))
+    }
+
+    fn database() -> SilverDB {
+        SilverDB {
+            sections: vec![
+                section(
+                    "Str ",
+                    vec![
+                        (1, SectionContent::String("Photos".into())),
+                        (2, SectionContent::String("Music".into())),
+                    ],
+                ),
+                section(
+                    "BMap",
+                    vec![
+                        (3, bitmap(120, RawBitmapType::Argb4444)),
+                        (4, bitmap(40, RawBitmapType::Argb4444)),
+                        (5, SectionContent::Bitmap(None)),
+                    ],
+                ),
+                section("StBM", vec![(6, bitmap(200, RawBitmapType::Rgb565))]),
+            ],
+        }
+    }
+
+    fn matching_ids(filter: &str) -> Vec<u32> {
+        Filter::parse(filter)
+            .unwrap()
+            .find(&database())
+            .iter()
+            .map(|(_, resource)| resource.id.0)
+            .collect()
+    }
+
+    #[test]
+    fn matches_resources() {
+        assert_eq!(matching_ids(r#"section == "Str" && text ~ /photos/i"#), [1]);
+        assert_eq!(matching_ids("section == str && text != Photos"), [2]);
+        assert_eq!(
+            matching_ids("section in [BMap, StBM] && width > 100 && format == argb4444"),
+            [3]
+        );
+        assert_eq!(matching_ids("kind == empty || id <= 0x1"), [1, 5]);
+        assert_eq!(matching_ids("!(kind == string) && id in [2, 4, 6]"), [4, 6]);
+    }
+
+    #[test]
+    fn resources_lacking_fields_never_match() {
+        assert_eq!(matching_ids("width >= 0"), [3, 4, 6]);
+        assert_eq!(matching_ids("!(width >= 0)"), [1, 2, 5]);
+        assert!(matching_ids("text ~ /.*/ && format != Rgb565").is_empty());
+    }
+
+    #[test]
+    fn applies_to_a_copy() {
+        let filter = Filter::parse("width < 100 || text == Music").unwrap();
+        let filtered = filter.apply(&database());
+        let sections: Vec<_> = filtered
+            .sections
+            .iter()
+            .map(|section| (section.section_type.to_name(), section.resources.len()))
+            .collect();
+        assert_eq!(sections, [("Str ".to_string(), 1), ("BMap".to_string(), 1)]);
+        assert_eq!(filter.to_string(), "width < 100 || text == Music");
+    }
+}
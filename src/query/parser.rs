@@ -0,0 +1,415 @@
+use regex::Regex;
+
+use crate::silver_error::SilverError;
+
+use super::{Comparison, Expression, Field, Operator, Value};
+
+/// Tokens within a filter expression.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// A bare word, such as a field name or `Argb4444`.
+    Identifier(String),
+    /// A double-quoted string.
+    Text(String),
+    /// A decimal or hexadecimal (`0x`) number.
+    Number(u64),
+    /// A regular expression between slashes, with an optional trailing `i` flag.
+    Regex(String),
+    Operator(Operator),
+    And,
+    Or,
+    Not,
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+}
+
+/// Creates an error describing a problem at the given position.
+fn filter_error(position: usize, message: &str) -> SilverError {
+    SilverError::InvalidFilter(format!("{message} at position {position}"))
+}
+
+/// Splits a filter expression into its tokens, alongside their positions.
+fn tokenize(filter: &str) -> Result<Vec<(usize, Token)>, SilverError> {
+    let characters: Vec<(usize, char)> = filter.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < characters.len() {
+        let (position, current) = characters[index];
+        let next = characters.get(index + 1).map(|(_, next)| *next);
+
+        // Two-character tokens must be checked prior to their one-character prefixes.
+        let two_character_token = match (current, next) {
+            ('&', Some('&')) => Some(Token::And),
+            ('|', Some('|')) => Some(Token::Or),
+            ('=', Some('=')) => Some(Token::Operator(Operator::Equal)),
+            ('!', Some('=')) => Some(Token::Operator(Operator::NotEqual)),
+            ('<', Some('=')) => Some(Token::Operator(Operator::LessOrEqual)),
+            ('>', Some('=')) => Some(Token::Operator(Operator::GreaterOrEqual)),
+            _ => None,
+        };
+        if let Some(token) = two_character_token {
+            tokens.push((position, token));
+            index += 2;
+            continue;
+        }
+
+        let single_character_token = match current {
+            '<' => Some(Token::Operator(Operator::Less)),
+            '>' => Some(Token::Operator(Operator::Greater)),
+            '~' => Some(Token::Operator(Operator::Matches)),
+            '!' => Some(Token::Not),
+            '(' => Some(Token::OpenParen),
+            ')' => Some(Token::CloseParen),
+            '[' => Some(Token::OpenBracket),
+            ']' => Some(Token::CloseBracket),
+            ',' => Some(Token::Comma),
+            _ => None,
+        };
+        if let Some(token) = single_character_token {
+            tokens.push((position, token));
+            index += 1;
+            continue;
+        }
+
+        match current {
+            c if c.is_whitespace() => index += 1,
+            '"' | '/' => {
+                // Both strings and regular expressions may escape their delimiter.
+                let delimiter = current;
+                let mut contents = String::new();
+                index += 1;
+                loop {
+                    let Some((_, c)) = characters.get(index) else {
+                        return Err(filter_error(position, "unterminated literal"));
+                    };
+                    index += 1;
+                    match (*c, characters.get(index).map(|(_, next)| *next)) {
+                        ('\\', Some(escaped)) if escaped == delimiter || escaped == '\\' => {
+                            // Regular expressions retain escaped backslashes as-is.
+                            if delimiter == '/' && escaped == '\\' {
+                                contents.push('\\');
+                            }
+                            contents.push(escaped);
+                            index += 1;
+                        }
+                        (c, _) if c == delimiter => break,
+                        (c, _) => contents.push(c),
+                    }
+                }
+
+                if delimiter == '"' {
+                    tokens.push((position, Token::Text(contents)));
+                } else {
+                    // Allow case-insensitive matching via a trailing `i`, i.e. `/photos/i`.
+                    let is_flag = characters.get(index).is_some_and(|(_, c)| *c == 'i')
+                        && !characters
+                            .get(index + 1)
+                            .is_some_and(|(_, c)| is_identifier(*c));
+                    if is_flag {
+                        contents.insert_str(0, "(?i)");
+                        index += 1;
+                    }
+                    tokens.push((position, Token::Regex(contents)));
+                }
+            }
+            c if is_identifier(c) => {
+                let mut word = String::new();
+                while let Some((_, c)) = characters.get(index) {
+                    if !is_identifier(*c) {
+                        break;
+                    }
+                    word.push(*c);
+                    index += 1;
+                }
+
+                let token = if c.is_ascii_digit() {
+                    let number = match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+                        Some(hex_value) => u64::from_str_radix(hex_value, 16),
+                        None => word.parse(),
+                    };
+                    Token::Number(number.map_err(|_| filter_error(position, "invalid number"))?)
+                } else {
+                    Token::Identifier(word)
+                };
+                tokens.push((position, token));
+            }
+            _ => return Err(filter_error(position, &format!("unexpected {current:?}"))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Whether this character may be present within an identifier or number.
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+/// A recursive descent parser over filter tokens.
+///
+/// In order of increasing precedence:
+/// ```text
+/// or         := and ("||" and)*
+/// and        := unary ("&&" unary)*
+/// unary      := "!" unary | "(" or ")" | comparison
+/// comparison := field operator value | field "in" "[" value ("," value)* "]"
+/// ```
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    /// The length of the original filter, used to describe its end.
+    length: usize,
+}
+
+impl Parser {
+    /// The position of the current token.
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .map(|(position, _)| *position)
+            .unwrap_or(self.length)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).map(|(_, token)| token.clone());
+        self.index += 1;
+        token
+    }
+
+    /// Consumes the given token, or fails with the given description.
+    fn expect(&mut self, expected: Token, description: &str) -> Result<(), SilverError> {
+        let position = self.position();
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(filter_error(position, &format!("expected {description}"))),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, SilverError> {
+        let mut expression = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            let right = self.parse_and()?;
+            expression = Expression::Or(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, SilverError> {
+        let mut expression = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            let right = self.parse_unary()?;
+            expression = Expression::And(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, SilverError> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                Ok(Expression::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::OpenParen) => {
+                self.next();
+                let expression = self.parse_or()?;
+                self.expect(Token::CloseParen, "\")\"")?;
+                Ok(expression)
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expression, SilverError> {
+        let position = self.position();
+        let field = match self.next() {
+            Some(Token::Identifier(name)) => Field::from_name(&name)
+                .ok_or_else(|| filter_error(position, &format!("unknown field {name:?}")))?,
+            _ => return Err(filter_error(position, "expected a field name")),
+        };
+
+        let position = self.position();
+        let (operator, value) = match self.next() {
+            Some(Token::Identifier(keyword)) if keyword == "in" => {
+                self.expect(Token::OpenBracket, "\"[\"")?;
+                let mut values = vec![self.parse_value(field, Operator::Equal)?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    values.push(self.parse_value(field, Operator::Equal)?);
+                }
+                self.expect(Token::CloseBracket, "\"]\"")?;
+                (Operator::Equal, Value::List(values))
+            }
+            Some(Token::Operator(operator)) => {
+                if !field.supports(operator) {
+                    return Err(filter_error(
+                        position,
+                        &format!("{} does not support {:?}", field.name(), operator),
+                    ));
+                }
+                let value = self.parse_value(field, operator)?;
+                (operator, value)
+            }
+            _ => return Err(filter_error(position, "expected an operator")),
+        };
+
+        Ok(Expression::Comparison(Comparison {
+            field,
+            operator,
+            value,
+        }))
+    }
+
+    /// Parses a single value, ensuring it is suitable for the given field and operator.
+    fn parse_value(&mut self, field: Field, operator: Operator) -> Result<Value, SilverError> {
+        let position = self.position();
+        let value = match (self.next(), field.is_numeric()) {
+            (Some(Token::Number(number)), true) => Value::Number(number),
+            (Some(Token::Text(text) | Token::Identifier(text)), false)
+                if operator == Operator::Matches =>
+            {
+                Value::Regex(compile_regex(position, &text)?)
+            }
+            (Some(Token::Regex(pattern)), false) if operator == Operator::Matches => {
+                Value::Regex(compile_regex(position, &pattern)?)
+            }
+            (Some(Token::Text(text) | Token::Identifier(text)), false) => Value::Text(text),
+            (Some(_), true) => return Err(filter_error(position, "expected a number")),
+            (Some(_), false) => return Err(filter_error(position, "expected a string")),
+            (None, _) => return Err(filter_error(position, "expected a value")),
+        };
+        Ok(value)
+    }
+}
+
+fn compile_regex(position: usize, pattern: &str) -> Result<Regex, SilverError> {
+    Regex::new(pattern).map_err(|e| filter_error(position, &format!("invalid regex ({e})")))
+}
+
+/// Parses a filter expression.
+pub(super) fn parse(filter: &str) -> Result<Expression, SilverError> {
+    let mut parser = Parser {
+        tokens: tokenize(filter)?,
+        index: 0,
+        length: filter.len(),
+    };
+
+    let expression = parser.parse_or()?;
+    if parser.peek().is_some() {
+        return Err(filter_error(parser.position(), "unexpected trailing input"));
+    }
+    Ok(expression)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(filter: &str) -> String {
+        match parse(filter) {
+            Err(SilverError::InvalidFilter(message)) => message,
+            Err(e) => panic!("{filter}: unexpected error {e}"),
+            Ok(_) => panic!("{filter}: should not parse"),
+        }
+    }
+
+    #[test]
+    fn tokenizes_literals_and_operators() {
+        let tokens: Vec<Token> =
+            tokenize(r#"id>=0x10 && text ~ /a\/b\\/i || !(name == "say \"hi\"")"#)
+                .unwrap()
+                .into_iter()
+                .map(|(_, token)| token)
+                .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("id".to_string()),
+                Token::Operator(Operator::GreaterOrEqual),
+                Token::Number(0x10),
+                Token::And,
+                Token::Identifier("text".to_string()),
+                Token::Operator(Operator::Matches),
+                Token::Regex(r"(?i)a/b\\".to_string()),
+                Token::Or,
+                Token::Not,
+                Token::OpenParen,
+                Token::Identifier("name".to_string()),
+                Token::Operator(Operator::Equal),
+                Token::Text(r#"say "hi""#.to_string()),
+                Token::CloseParen,
+            ]
+        );
+    }
+
+    #[test]
+    fn regex_flag_requires_separation() {
+        let tokens = tokenize("/a/id").unwrap();
+        assert_eq!(tokens[0].1, Token::Regex("a".to_string()));
+        assert_eq!(tokens[1], (3, Token::Identifier("id".to_string())));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let Expression::Or(left, right) = parse("id == 1 || id == 2 && !id == 3").unwrap() else {
+            panic!("|| should be outermost");
+        };
+        assert!(matches!(*left, Expression::Comparison(_)));
+        let Expression::And(_, negated) = *right else {
+            panic!("&& should be within ||");
+        };
+        assert!(matches!(*negated, Expression::Not(_)));
+
+        let Expression::And(left, _) = parse("(id == 1 || id == 2) && id == 3").unwrap() else {
+            panic!("parentheses should group ||");
+        };
+        assert!(matches!(*left, Expression::Or(..)));
+    }
+
+    #[test]
+    fn parses_lists() {
+        let Expression::Comparison(comparison) = parse("section in [BMap, \"StBM\"]").unwrap()
+        else {
+            panic!("should be a comparison");
+        };
+        assert_eq!(comparison.field, Field::Section);
+        assert_eq!(comparison.operator, Operator::Equal);
+        assert!(matches!(comparison.value, Value::List(values) if values.len() == 2));
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        assert_eq!(
+            error("colour == red"),
+            "unknown field \"colour\" at position 0"
+        );
+        assert_eq!(
+            error("width ~ 10"),
+            "width does not support Matches at position 6"
+        );
+        assert_eq!(error("width == big"), "expected a number at position 9");
+        assert_eq!(error("text == 5"), "expected a string at position 8");
+        assert!(error("text ~ /(/").starts_with("invalid regex"));
+        assert_eq!(error("(id == 1"), "expected \")\" at position 8");
+        assert_eq!(
+            error("id == 1 id"),
+            "unexpected trailing input at position 8"
+        );
+        assert_eq!(
+            error("text == \"open"),
+            "unterminated literal at position 8"
+        );
+        assert_eq!(error("id == 0xzz"), "invalid number at position 6");
+        assert_eq!(error("id == 1 & id"), "unexpected '&' at position 8");
+        assert_eq!(error("id =="), "expected a value at position 5");
+    }
+}
@@ -1,9 +1,16 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
 
-use crate::{bitmap::BitmapImage, section_types::SectionType, silver_error::SilverError};
+use crate::{
+    bitmap::{BitmapImage, QuantizeOptions},
+    device::DeviceProfile,
+    section_types::SectionType,
+    silver_error::SilverError,
+    silver_string::SilverString,
+};
 
 /// Content represented by sections within.
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub enum SectionContent {
     // TODO(spotlightishere): Images should be parsed accordingly
     /// A bitmap image.
@@ -13,8 +20,9 @@ pub enum SectionContent {
     #[serde(with = "RawData")]
     DateTimeLocale(Vec<u8>),
 
-    /// A generic string type. Handled as a C string.
-    String(String),
+    /// A generic string type. Most are null-terminated UTF-8 C strings,
+    /// but their original encoding and terminator are preserved regardless.
+    String(SilverString),
 
     /// Not an actual section type - used to represent an unknown section's raw binary contents.
     #[serde(with = "RawData")]
@@ -43,34 +51,28 @@ impl RawData {
     }
 }
 
-/// As many resources are C strings, they contain a null terminator.
-/// We process them by removing the trailing null terminator.
-fn process_c_string(raw_data: Vec<u8>) -> Result<String, SilverError> {
-    let mut contents = String::from_utf8(raw_data)?;
-    // Remove the last byte, a null byte.
-    contents.truncate(contents.len() - 1);
-    Ok(contents)
-}
-
-/// Similarly to `process_c_string`, when we create C strings once more,
-/// they need to end with a null terminator. We simply tack one, where possible.
-fn create_c_string(raw_string: String) -> Vec<u8> {
-    // TODO(spotlightishere): Using the string's existing bytes doesn't quite work if
-    // we're presented a non-ASCII character. Can we, or should we, use a different approach?
-    let contents = raw_string.as_bytes();
-    let null_terminator: &[u8; 1] = &[0x00];
-    [contents, null_terminator].concat()
-}
-
 impl SectionContent {
     /// Parses contents to a higher-level type accordingly based on their section.
     pub fn parse_section(
         section_type: &SectionType,
         raw_data: Vec<u8>,
+    ) -> Result<SectionContent, SilverError> {
+        SectionContent::parse_section_with_profile(
+            section_type,
+            raw_data,
+            &DeviceProfile::generic(),
+        )
+    }
+
+    /// Parses contents based on their section, using the rules of the given device profile.
+    pub fn parse_section_with_profile(
+        section_type: &SectionType,
+        raw_data: Vec<u8>,
+        profile: &DeviceProfile,
     ) -> Result<SectionContent, SilverError> {
         let section_content = match section_type {
             SectionType::Bitmap | SectionType::StatusBarBitmap => {
-                SectionContent::Bitmap(BitmapImage::parse(raw_data)?)
+                SectionContent::Bitmap(BitmapImage::parse_with_profile(raw_data, profile)?)
             }
             SectionType::DateTimeLocale => SectionContent::DateTimeLocale(raw_data),
             // Several types are simply C strings.
@@ -78,7 +80,7 @@ impl SectionContent {
             | SectionType::StringTranslation
             | SectionType::AnimControllerString
             | SectionType::SilverControllerString => {
-                SectionContent::String(process_c_string(raw_data)?)
+                SectionContent::String(SilverString::decode(&raw_data))
             }
             _ => SectionContent::Unknown(raw_data),
         };
@@ -87,21 +89,111 @@ impl SectionContent {
 
     /// Reduces contents from their higher-level type to their raw binary representation.
     pub fn reduce_section(section_content: SectionContent) -> Result<Vec<u8>, SilverError> {
+        SectionContent::reduce_section_with_profile(section_content, &DeviceProfile::generic())
+    }
+
+    /// Reduces contents to their raw binary representation,
+    /// using the rules of the given device profile.
+    pub fn reduce_section_with_profile(
+        section_content: SectionContent,
+        profile: &DeviceProfile,
+    ) -> Result<Vec<u8>, SilverError> {
+        SectionContent::reduce_section_with_options(
+            section_content,
+            profile,
+            &QuantizeOptions::default(),
+        )
+    }
+
+    /// Reduces contents to their raw binary representation using the rules of the given
+    /// device profile, quantizing changed bitmaps with the given options.
+    pub fn reduce_section_with_options(
+        section_content: SectionContent,
+        profile: &DeviceProfile,
+        quantize_options: &QuantizeOptions,
+    ) -> Result<Vec<u8>, SilverError> {
         let raw_data = match section_content {
-            // TODO(spotlightishere): Handle bitmap parsing
             SectionContent::Bitmap(raw_contents) => {
                 // For our zero-length bitmap images, return an empty vector.
                 if let Some(contents) = raw_contents {
-                    contents.reduce()?
+                    contents.reduce_with_options(profile, quantize_options)?
                 } else {
                     Vec::new()
                 }
             }
             SectionContent::DateTimeLocale(raw_contents) => raw_contents,
             SectionContent::Unknown(raw_contents) => raw_contents,
-            SectionContent::String(raw_string) => create_c_string(raw_string),
+            SectionContent::String(raw_string) => raw_string.encode(),
         };
 
         Ok(raw_data)
     }
+
+    /// A SHA-256 hash of this content, as a hexadecimal string.
+    ///
+    /// Contents are hashed as their raw representation. Bitmap images are hashed
+    /// as the raw header and pixel data they were parsed from, so that bitmaps
+    /// lacking a PNG representation (such as those of unknown types) remain distinct.
+    /// Bitmaps without a raw representation are hashed as their PNG representation.
+    pub fn content_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        match self {
+            SectionContent::Bitmap(Some(image)) => match image.raw.as_ref().map(|raw| raw.reduce())
+            {
+                Some(Ok(raw_contents)) => hasher.update(raw_contents),
+                _ => hasher.update(&image.contents),
+            },
+            SectionContent::Bitmap(None) => {}
+            SectionContent::DateTimeLocale(raw_contents) => hasher.update(raw_contents),
+            SectionContent::Unknown(raw_contents) => hasher.update(raw_contents),
+            SectionContent::String(string) => hasher.update(string.encode()),
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitmap::{RawBitmapData, RawBitmapHeader, RawBitmapType};
+
+    /// A bitmap of an unknown type, which has no PNG representation.
+    fn unknown_bitmap(contents: Vec<u8>) -> SectionContent {
+        let header = RawBitmapHeader {
+            image_type: RawBitmapType::Unknown(0x1911),
+            is_external: 0,
+            rendered_width: 0,
+            color_depth: 0,
+            padding_one: 0,
+            padding_two: 0,
+            width: 1,
+            height: 1,
+            resource_id: Some(1),
+            contents_length: contents.len() as u32,
+        };
+        SectionContent::Bitmap(Some(BitmapImage {
+            width: 1,
+            height: 1,
+            rendered_width: 0,
+            format_type: header.image_type,
+            resource_id: 1,
+            contents: Vec::new(),
+            raw: Some(RawBitmapData { header, contents }),
+        }))
+    }
+
+    #[test]
+    fn bitmaps_without_png_hash_distinctly() {
+        let first = unknown_bitmap(vec![1, 2, 3]);
+        let second = unknown_bitmap(vec![4, 5, 6]);
+        assert_ne!(first.content_hash(), second.content_hash());
+        assert_ne!(
+            first.content_hash(),
+            SectionContent::Bitmap(None).content_hash()
+        );
+        assert_eq!(
+            first.content_hash(),
+            unknown_bitmap(vec![1, 2, 3]).content_hash()
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use crate::bitmap::BitmapIssue;
 use image::ImageError;
 use serde::ser::StdError;
 use std::array::TryFromSliceError;
@@ -13,7 +14,23 @@ pub enum SilverError {
     InvalidMagic,
     InvalidBitmap,
     UnknownBitmap,
+    UnknownBitmapFormat(String),
+    MalformedBitmap(BitmapIssue),
     ImageError(ImageError),
+    MissingLocale(String),
+    InvalidTranslation(String),
+    InvalidLocale(String),
+    InvalidSymbol(String),
+    UnknownDevice(String),
+    UnknownQuantizer(String),
+    UnknownDither(String),
+    InvalidFilter(String),
+    BitmapEncodingFailure(String),
+    InvalidAtlas(String),
+    DeviceLimitExceeded(String),
+    InvalidQuantizeOptions(String),
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
 }
 
 impl From<io::Error> for SilverError {
@@ -36,6 +53,14 @@ impl From<TryFromSliceError> for SilverError {
     }
 }
 
+// Used for when exporting to SQLite.
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for SilverError {
+    fn from(value: rusqlite::Error) -> Self {
+        SilverError::Sqlite(value)
+    }
+}
+
 // Used for when converting between images and bitmaps.
 impl From<ImageError> for SilverError {
     fn from(value: ImageError) -> Self {
@@ -52,7 +77,27 @@ impl fmt::Display for SilverError {
             Self::InvalidHeader => write!(f, "Invalid header for SilverDB file encountered!"),
             Self::InvalidBitmap => write!(f, "Invalid bitmap resource entry encountered!"),
             Self::UnknownBitmap => write!(f, "Unknown bitmap resource entry type encountered!"),
+            Self::UnknownBitmapFormat(format) => {
+                write!(f, "Unknown bitmap format {format} provided!")
+            }
+            Self::MalformedBitmap(issue) => write!(f, "Malformed bitmap: {issue}"),
             Self::ImageError(e) => write!(f, "Failed to convert image: {}", e),
+            Self::MissingLocale(locale) => write!(f, "No database for locale {locale} was found!"),
+            Self::InvalidTranslation(e) => write!(f, "Failed to parse translations: {e}"),
+            Self::InvalidLocale(locale) => write!(f, "Invalid locale code {locale} provided!"),
+            Self::InvalidSymbol(symbol) => write!(f, "Invalid or duplicate symbol: {symbol}"),
+            Self::UnknownDevice(device) => write!(f, "Unknown device {device} provided!"),
+            Self::UnknownQuantizer(quantizer) => {
+                write!(f, "Unknown quantizer {quantizer} provided!")
+            }
+            Self::UnknownDither(dither) => write!(f, "Unknown dithering method {dither} provided!"),
+            Self::InvalidFilter(e) => write!(f, "Invalid filter: {e}"),
+            Self::BitmapEncodingFailure(e) => write!(f, "Failed to encode bitmap: {e}"),
+            Self::InvalidAtlas(e) => write!(f, "Invalid atlas: {e}"),
+            Self::DeviceLimitExceeded(e) => write!(f, "Database exceeds device limits: {e}"),
+            Self::InvalidQuantizeOptions(e) => write!(f, "Invalid quantize options: {e}"),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(e) => write!(f, "Failed to export to SQLite: {e}"),
         }
     }
 }
@@ -66,7 +111,23 @@ impl StdError for SilverError {
             Self::InvalidHeader => "Invalid header for SilverDB file encountered!",
             Self::InvalidBitmap => "Invalid bitmap resource entry encountered!",
             Self::UnknownBitmap => "Unknown bitmap resource entry type encountered!",
+            Self::UnknownBitmapFormat(_) => "Unknown bitmap format provided!",
+            Self::MalformedBitmap(_) => "Malformed bitmap encountered!",
             Self::ImageError(_) => "Failed to convert image.",
+            Self::MissingLocale(_) => "No database for the given locale was found!",
+            Self::InvalidTranslation(_) => "Failed to parse translations.",
+            Self::InvalidLocale(_) => "Invalid locale code provided!",
+            Self::InvalidSymbol(_) => "Invalid or duplicate symbol.",
+            Self::UnknownDevice(_) => "Unknown device provided!",
+            Self::UnknownQuantizer(_) => "Unknown quantizer provided!",
+            Self::UnknownDither(_) => "Unknown dithering method provided!",
+            Self::InvalidFilter(_) => "Invalid filter.",
+            Self::BitmapEncodingFailure(_) => "Failed to encode bitmap.",
+            Self::InvalidAtlas(_) => "Invalid atlas.",
+            Self::DeviceLimitExceeded(_) => "Database exceeds device limits.",
+            Self::InvalidQuantizeOptions(_) => "Invalid quantize options.",
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(_) => "Failed to export to SQLite.",
         }
     }
 }
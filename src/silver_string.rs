@@ -0,0 +1,291 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Encodings observed within string resources.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StringEncoding {
+    /// The vast majority of strings are UTF-8.
+    #[default]
+    Utf8,
+    /// A legacy, single-byte encoding (ISO 8859-1).
+    /// Every byte maps directly to the Unicode code point of the same value.
+    Latin1,
+    /// Contents could not be decoded, and are preserved as-is.
+    Raw,
+}
+
+/// A string resource, alongside everything necessary to encode it back
+/// to its exact original bytes.
+///
+/// Most strings are UTF-8 C strings with a single trailing null terminator.
+/// However, some resources may be empty, lack a terminator, contain
+/// embedded null bytes, or not be UTF-8 at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SilverString {
+    /// Decoded text. For raw strings, this is a lossy representation.
+    text: String,
+    /// The encoding this string was decoded from.
+    encoding: StringEncoding,
+    /// Whether this string ends with a null terminator.
+    terminated: bool,
+    /// Original contents (without any terminator), retained only for raw strings.
+    raw_contents: Option<Vec<u8>>,
+}
+
+/// Whether this byte could plausibly be Latin-1 text.
+/// We reject C0 and C1 control characters, other than common whitespace and null.
+fn is_latin1_text(current_byte: u8) -> bool {
+    !matches!(current_byte, 0x01..=0x08 | 0x0b | 0x0c | 0x0e..=0x1f | 0x7f..=0x9f)
+}
+
+impl SilverString {
+    /// Decodes a resource's raw contents, detecting its encoding and terminator.
+    pub fn decode(raw_data: &[u8]) -> Self {
+        // Strip a single trailing null terminator, if present.
+        // Any further null bytes are considered part of the string.
+        let (contents, terminated) = match raw_data.split_last() {
+            Some((0x00, contents)) => (contents, true),
+            _ => (raw_data, false),
+        };
+
+        if let Ok(text) = std::str::from_utf8(contents) {
+            return SilverString {
+                text: text.to_string(),
+                encoding: StringEncoding::Utf8,
+                terminated,
+                raw_contents: None,
+            };
+        }
+
+        if contents.iter().all(|byte| is_latin1_text(*byte)) {
+            return SilverString {
+                text: contents.iter().map(|byte| *byte as char).collect(),
+                encoding: StringEncoding::Latin1,
+                terminated,
+                raw_contents: None,
+            };
+        }
+
+        SilverString {
+            text: String::from_utf8_lossy(contents).into_owned(),
+            encoding: StringEncoding::Raw,
+            terminated,
+            raw_contents: Some(contents.to_vec()),
+        }
+    }
+
+    /// Encodes this string back to its raw form, using its original encoding and terminator.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut contents: Vec<u8> = match self.encoding {
+            StringEncoding::Utf8 => self.text.as_bytes().to_vec(),
+            // `set_text` ensures we only hold characters representable in Latin-1.
+            StringEncoding::Latin1 => self.text.chars().map(|c| c as u8).collect(),
+            StringEncoding::Raw => self.raw_contents.clone().unwrap_or_default(),
+        };
+
+        if self.terminated {
+            contents.push(0x00);
+        }
+        contents
+    }
+
+    /// The decoded text of this string.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The encoding this string was decoded from, and will be encoded with.
+    pub fn encoding(&self) -> StringEncoding {
+        self.encoding
+    }
+
+    /// Whether this string ends with a null terminator.
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Whether null bytes are present prior to the terminator.
+    pub fn has_embedded_nul(&self) -> bool {
+        match &self.raw_contents {
+            Some(raw_contents) => raw_contents.contains(&0x00),
+            None => self.text.contains('\0'),
+        }
+    }
+
+    /// Replaces the text of this string, retaining its terminator.
+    ///
+    /// Latin-1 strings remain Latin-1 if all characters are representable,
+    /// and are otherwise promoted to UTF-8. Raw strings become UTF-8.
+    pub fn set_text(&mut self, text: String) {
+        let representable = text.chars().all(|c| (c as u32) <= 0xff);
+        if self.encoding != StringEncoding::Latin1 || !representable {
+            self.encoding = StringEncoding::Utf8;
+        }
+        self.text = text;
+        self.raw_contents = None;
+    }
+}
+
+/// Creates a null-terminated UTF-8 string, as most resources are.
+impl From<String> for SilverString {
+    fn from(text: String) -> Self {
+        SilverString {
+            text,
+            encoding: StringEncoding::Utf8,
+            terminated: true,
+            raw_contents: None,
+        }
+    }
+}
+
+impl From<&str> for SilverString {
+    fn from(text: &str) -> Self {
+        SilverString::from(text.to_string())
+    }
+}
+
+impl fmt::Display for SilverString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+fn default_terminated() -> bool {
+    true
+}
+
+/// The serialized form of a string.
+///
+/// Null-terminated UTF-8 strings are represented as a plain string.
+/// Anything else is represented with its encoding and terminator alongside.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum StringRepresentation {
+    Plain(String),
+    Detailed {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        /// Hex-encoded contents, used for raw strings.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        raw: Option<String>,
+        #[serde(default)]
+        encoding: StringEncoding,
+        #[serde(default = "default_terminated")]
+        terminated: bool,
+    },
+}
+
+impl Serialize for SilverString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let representation = match (&self.raw_contents, self.encoding, self.terminated) {
+            (None, StringEncoding::Utf8, true) => StringRepresentation::Plain(self.text.clone()),
+            (Some(raw_contents), _, terminated) => StringRepresentation::Detailed {
+                text: None,
+                raw: Some(hex::encode(raw_contents)),
+                encoding: StringEncoding::Raw,
+                terminated,
+            },
+            (None, encoding, terminated) => StringRepresentation::Detailed {
+                text: Some(self.text.clone()),
+                raw: None,
+                encoding,
+                terminated,
+            },
+        };
+        representation.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SilverString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let representation: StringRepresentation = Deserialize::deserialize(deserializer)?;
+        let (text, raw, encoding, terminated) = match representation {
+            StringRepresentation::Plain(text) => return Ok(SilverString::from(text)),
+            StringRepresentation::Detailed {
+                text,
+                raw,
+                encoding,
+                terminated,
+            } => (text, raw, encoding, terminated),
+        };
+
+        match (encoding, text, raw) {
+            (StringEncoding::Raw, _, Some(raw)) => {
+                let raw_contents = hex::decode(raw).map_err(Error::custom)?;
+                Ok(SilverString {
+                    text: String::from_utf8_lossy(&raw_contents).into_owned(),
+                    encoding: StringEncoding::Raw,
+                    terminated,
+                    raw_contents: Some(raw_contents),
+                })
+            }
+            (StringEncoding::Raw, _, None) => Err(Error::custom("raw strings require `raw`")),
+            (encoding, Some(text), None) => {
+                if encoding == StringEncoding::Latin1 && text.chars().any(|c| (c as u32) > 0xff) {
+                    return Err(Error::custom("string is not representable in Latin-1"));
+                }
+                Ok(SilverString {
+                    text,
+                    encoding,
+                    terminated,
+                    raw_contents: None,
+                })
+            }
+            _ => Err(Error::custom("expected exactly one of `text` or `raw`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn decodes_edge_cases_losslessly() {
+        let cases: [(&[u8], StringEncoding, bool); 6] = [
+            (b"", StringEncoding::Utf8, false),
+            (b"\0", StringEncoding::Utf8, true),
+            (b"Music", StringEncoding::Utf8, false),
+            (b"Mu\0sic\0", StringEncoding::Utf8, true),
+            (b"Caf\xe9\0", StringEncoding::Latin1, true),
+            (b"\xff\x01\0\xfe", StringEncoding::Raw, false),
+        ];
+        for (raw_data, encoding, terminated) in cases {
+            let string = SilverString::decode(raw_data);
+            assert_eq!(string.encoding(), encoding, "{raw_data:?}");
+            assert_eq!(string.is_terminated(), terminated, "{raw_data:?}");
+            assert_eq!(string.encode(), raw_data);
+        }
+
+        assert!(SilverString::decode(b"Mu\0sic\0").has_embedded_nul());
+        assert!(SilverString::decode(b"\xff\x01\0\xfe").has_embedded_nul());
+        assert_eq!(SilverString::decode(b"Caf\xe9\0").text(), "Caf\u{e9}");
+    }
+
+    proptest! {
+        #[test]
+        fn decoding_round_trips(raw_data in vec(any::<u8>(), 0..64)) {
+            prop_assert_eq!(SilverString::decode(&raw_data).encode(), raw_data);
+        }
+
+        #[test]
+        fn decoding_text_round_trips(text in ".*", terminated in any::<bool>()) {
+            let mut raw_data = text.into_bytes();
+            if terminated {
+                raw_data.push(0x00);
+            }
+            prop_assert_eq!(SilverString::decode(&raw_data).encode(), raw_data);
+        }
+    }
+}
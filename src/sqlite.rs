@@ -0,0 +1,329 @@
+use std::{fs, io::Cursor, path::Path};
+
+use rusqlite::{params, Connection};
+
+use crate::{
+    database::{find_database_files, SilverResourceID},
+    device::DeviceProfile,
+    format::SilverDBFormat,
+    section_content::SectionContent,
+    section_types::SectionType,
+    silver_error::SilverError,
+};
+
+/// The schema of exported SQLite files.
+///
+/// Every table references its parent by row ID, so queries across
+/// many databases can join from `bitmaps` or `strings` up to `databases`.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS databases (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS sections (
+    id INTEGER PRIMARY KEY,
+    database_id INTEGER NOT NULL REFERENCES databases(id),
+    position INTEGER NOT NULL,
+    magic TEXT NOT NULL,
+    name TEXT NOT NULL,
+    is_sequential INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS resources (
+    id INTEGER PRIMARY KEY,
+    section_id INTEGER NOT NULL REFERENCES sections(id),
+    resource_id INTEGER NOT NULL,
+    resource_id_hex TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    hash TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS strings (
+    resource_id INTEGER PRIMARY KEY REFERENCES resources(id),
+    text TEXT NOT NULL,
+    encoding TEXT NOT NULL,
+    terminated INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS bitmaps (
+    resource_id INTEGER PRIMARY KEY REFERENCES resources(id),
+    format TEXT NOT NULL,
+    format_code INTEGER NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    rendered_width INTEGER NOT NULL
+);
+";
+
+/// Writes the contents of databases into an SQLite file for ad-hoc queries.
+///
+/// For example, to find all 29x29 ARGB4444 icons across all databases:
+/// ```sql
+/// SELECT databases.name, resources.resource_id_hex FROM bitmaps
+///     JOIN resources ON resources.id = bitmaps.resource_id
+///     JOIN sections ON sections.id = resources.section_id
+///     JOIN databases ON databases.id = sections.database_id
+///     WHERE width = 29 AND height = 29 AND format = 'Argb4444';
+/// ```
+pub struct SqliteExporter {
+    connection: Connection,
+}
+
+impl SqliteExporter {
+    /// Opens (or creates) the SQLite file at the given path, creating all tables.
+    pub fn open(path: &Path) -> Result<Self, SilverError> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(SqliteExporter { connection })
+    }
+
+    /// Exports the raw contents of a database under the given name.
+    /// Any database previously exported under the same name is replaced.
+    ///
+    /// Raw contents are necessary to record the size of every resource
+    /// as stored on disk, so we parse sections ourselves.
+    pub fn add_database(
+        &mut self,
+        name: &str,
+        file_contents: Vec<u8>,
+        profile: &DeviceProfile,
+    ) -> Result<(), SilverError> {
+        let database_file = SilverDBFormat::read_with_profile(Cursor::new(file_contents), profile)?;
+
+        let transaction = self.connection.transaction()?;
+        remove_database(&transaction, name)?;
+        transaction.execute("INSERT INTO databases (name) VALUES (?1)", params![name])?;
+        let database_id = transaction.last_insert_rowid();
+
+        for (position, raw_section) in database_file.sections.into_iter().enumerate() {
+            let section_type = SectionType::from_magic(raw_section.magic);
+            transaction.execute(
+                "INSERT INTO sections (database_id, position, magic, name, is_sequential)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    database_id,
+                    position as u32,
+                    section_type.to_name(),
+                    section_type.to_string(),
+                    raw_section.is_sequential,
+                ],
+            )?;
+            let section_id = transaction.last_insert_rowid();
+
+            for raw_resource in raw_section.resources {
+                let id = SilverResourceID(raw_resource.id);
+                let contents = SectionContent::parse_section_with_profile(
+                    &section_type,
+                    raw_resource.contents,
+                    profile,
+                )?;
+
+                transaction.execute(
+                    "INSERT INTO resources (section_id, resource_id, resource_id_hex, size, hash)
+                        VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        section_id,
+                        id.0,
+                        id.to_string(),
+                        raw_resource.data_size,
+                        contents.content_hash(),
+                    ],
+                )?;
+                let resource_row_id = transaction.last_insert_rowid();
+
+                match contents {
+                    SectionContent::String(string) => {
+                        transaction.execute(
+                            "INSERT INTO strings (resource_id, text, encoding, terminated)
+                                VALUES (?1, ?2, ?3, ?4)",
+                            params![
+                                resource_row_id,
+                                string.text(),
+                                format!("{:?}", string.encoding()).to_lowercase(),
+                                string.is_terminated(),
+                            ],
+                        )?;
+                    }
+                    SectionContent::Bitmap(Some(bitmap)) => {
+                        transaction.execute(
+                            "INSERT INTO bitmaps (resource_id, format, format_code, width, height, rendered_width)
+                                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                            params![
+                                resource_row_id,
+                                format!("{:?}", bitmap.format_type),
+                                bitmap.format_type.code(),
+                                bitmap.width,
+                                bitmap.height,
+                                bitmap.rendered_width,
+                            ],
+                        )?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Exports every database (`*.bin`) within the given directory and its subdirectories.
+    /// Databases are named by their path relative to the given directory.
+    ///
+    /// Returns the amount of databases exported.
+    pub fn add_directory(
+        &mut self,
+        directory: &Path,
+        profile: &DeviceProfile,
+    ) -> Result<usize, SilverError> {
+        let database_paths = find_database_files(directory)?;
+
+        for database_path in &database_paths {
+            let name = database_path
+                .strip_prefix(directory)
+                .unwrap_or(database_path)
+                .to_string_lossy();
+            self.add_database(&name, fs::read(database_path)?, profile)?;
+        }
+        Ok(database_paths.len())
+    }
+}
+
+/// Removes all rows associated with the database of the given name.
+fn remove_database(connection: &Connection, name: &str) -> Result<(), SilverError> {
+    let database_filter = "SELECT id FROM databases WHERE name = ?1";
+    let section_filter =
+        format!("SELECT id FROM sections WHERE database_id IN ({database_filter})");
+    let resource_filter =
+        format!("SELECT id FROM resources WHERE section_id IN ({section_filter})");
+
+    connection.execute(
+        &format!("DELETE FROM strings WHERE resource_id IN ({resource_filter})"),
+        params![name],
+    )?;
+    connection.execute(
+        &format!("DELETE FROM bitmaps WHERE resource_id IN ({resource_filter})"),
+        params![name],
+    )?;
+    connection.execute(
+        &format!("DELETE FROM resources WHERE section_id IN ({section_filter})"),
+        params![name],
+    )?;
+    connection.execute(
+        &format!("DELETE FROM sections WHERE database_id IN ({database_filter})"),
+        params![name],
+    )?;
+    connection.execute("DELETE FROM databases WHERE name = ?1", params![name])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitmap::{BitmapImage, RawBitmapType},
+        database::SilverDB,
+        test_fixtures::{section, string_section},
+    };
+
+    /// A database holding two strings and a 2x2 bitmap.
+    fn database_contents() -> Vec<u8> {
+        let pixels = image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]));
+        let mut png_writer = Cursor::new(Vec::new());
+        pixels
+            .write_to(&mut png_writer, image::ImageFormat::Png)
+            .unwrap();
+        let bitmap = BitmapImage {
+            width: 2,
+            height: 2,
+            rendered_width: 0,
+            format_type: RawBitmapType::Rgb565,
+            resource_id: 0x0dad0001,
+            contents: png_writer.into_inner(),
+            raw: None,
+        };
+
+        let sections = vec![
+            string_section("Str ", &[(1, "Music"), (2, "Photos")]),
+            section(
+                "BMap",
+                vec![(0x0dad0001, SectionContent::Bitmap(Some(bitmap)))],
+            ),
+        ];
+        SilverDB::write(sections).unwrap()
+    }
+
+    fn count(exporter: &SqliteExporter, table: &str) -> u32 {
+        exporter
+            .connection
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn exports_strings_and_bitmaps() {
+        let path = std::env::temp_dir().join(format!("silverlib-sqlite-{}.db", std::process::id()));
+        // Rows left by an earlier, failed run would otherwise be counted.
+        _ = fs::remove_file(&path);
+        let mut exporter = SqliteExporter::open(&path).unwrap();
+        let profile = DeviceProfile::generic();
+        exporter
+            .add_database("SilverDB.en_US.LE.bin", database_contents(), &profile)
+            .unwrap();
+
+        let mut statement = exporter
+            .connection
+            .prepare(
+                "SELECT databases.name, resources.resource_id, strings.text FROM strings
+                    JOIN resources ON resources.id = strings.resource_id
+                    JOIN sections ON sections.id = resources.section_id
+                    JOIN databases ON databases.id = sections.database_id
+                    ORDER BY resources.resource_id",
+            )
+            .unwrap();
+        let strings: Vec<(String, u32, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        drop(statement);
+        assert_eq!(
+            strings,
+            vec![
+                ("SilverDB.en_US.LE.bin".to_string(), 1, "Music".to_string()),
+                ("SilverDB.en_US.LE.bin".to_string(), 2, "Photos".to_string()),
+            ]
+        );
+
+        let bitmap: (String, String, u32, u32) = exporter
+            .connection
+            .query_row(
+                "SELECT resources.resource_id_hex, format, width, height FROM bitmaps
+                    JOIN resources ON resources.id = bitmaps.resource_id",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            bitmap,
+            ("0x0dad0001".to_string(), "Rgb565".to_string(), 2, 2)
+        );
+
+        // Exporting under the same name replaces rows, while another name adds them.
+        exporter
+            .add_database("SilverDB.en_US.LE.bin", database_contents(), &profile)
+            .unwrap();
+        let counts = ["databases", "sections", "resources", "strings", "bitmaps"]
+            .map(|table| count(&exporter, table));
+        assert_eq!(counts, [1, 2, 3, 2, 1]);
+
+        exporter
+            .add_database("SilverDB.de_DE.LE.bin", database_contents(), &profile)
+            .unwrap();
+        let counts = ["databases", "sections", "resources", "strings", "bitmaps"]
+            .map(|table| count(&exporter, table));
+        assert_eq!(counts, [2, 4, 6, 4, 2]);
+
+        drop(exporter);
+        fs::remove_file(&path).unwrap();
+    }
+}
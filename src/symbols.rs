@@ -0,0 +1,338 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use crate::{
+    database::{SilverDB, SilverResourceID},
+    localization::placeholder_names,
+    section_types::SectionType,
+    silver_error::SilverError,
+};
+
+/// Maps resource IDs to human-readable names, and vice versa.
+///
+/// Symbol files hold one symbol per line, as an ID followed by its name:
+/// ```text
+/// # Comments begin with a hash.
+/// 0x0dad06d8 music_icon
+/// [Str ] 0x00000001 Music_Title
+/// ```
+///
+/// IDs are only unique within their section, so symbols may be limited to one
+/// by a leading section name in brackets. Others name their ID within any section.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    names: BTreeMap<(SilverResourceID, Option<SectionType>), String>,
+    ids: HashMap<String, (SilverResourceID, Option<SectionType>)>,
+}
+
+/// Whether the given name may be used as a symbol.
+/// Names must not be mistaken for an ID, and cannot contain whitespace.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && SilverResourceID::from_str(name).is_err()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_.-:".contains(c))
+}
+
+impl SymbolTable {
+    /// Creates an empty symbol table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a symbol within any section, replacing any existing name for this ID.
+    /// Names must be unique: a name already used by another ID is rejected.
+    pub fn insert(&mut self, id: SilverResourceID, name: String) -> Result<(), SilverError> {
+        self.insert_symbol(id, None, name)
+    }
+
+    /// Adds a symbol limited to the given section, replacing any existing name
+    /// for this ID within it. Names must be unique, as with `SymbolTable::insert`.
+    pub fn insert_in(
+        &mut self,
+        section_type: SectionType,
+        id: SilverResourceID,
+        name: String,
+    ) -> Result<(), SilverError> {
+        self.insert_symbol(id, Some(section_type), name)
+    }
+
+    fn insert_symbol(
+        &mut self,
+        id: SilverResourceID,
+        section_type: Option<SectionType>,
+        name: String,
+    ) -> Result<(), SilverError> {
+        if !is_valid_name(&name) {
+            return Err(SilverError::InvalidSymbol(name));
+        }
+        let key = (id, section_type);
+        if self.ids.get(&name).is_some_and(|existing| *existing != key) {
+            return Err(SilverError::InvalidSymbol(name));
+        }
+
+        if let Some(previous) = self.names.insert(key, name.clone()) {
+            self.ids.remove(&previous);
+        }
+        self.ids.insert(name, key);
+        Ok(())
+    }
+
+    /// The name of the given resource within any section, if known.
+    pub fn name(&self, id: SilverResourceID) -> Option<&str> {
+        self.names.get(&(id, None)).map(String::as_str)
+    }
+
+    /// The name of the given resource within the given section, if known.
+    pub fn name_in(&self, section_type: SectionType, id: SilverResourceID) -> Option<&str> {
+        self.names
+            .get(&(id, Some(section_type)))
+            .or_else(|| self.names.get(&(id, None)))
+            .map(String::as_str)
+    }
+
+    /// Resolves either a name or a raw ID (i.e. `0x0dad06d8`) to its resource ID.
+    pub fn resolve(&self, name_or_id: &str) -> Option<SilverResourceID> {
+        SilverResourceID::from_str(name_or_id)
+            .ok()
+            .or_else(|| self.ids.get(name_or_id).map(|(id, _)| *id))
+    }
+
+    /// Resolves either a name or a raw ID to its resource ID within the given section.
+    /// Names limited to another section are not resolved.
+    pub fn resolve_in(
+        &self,
+        section_type: SectionType,
+        name_or_id: &str,
+    ) -> Option<SilverResourceID> {
+        SilverResourceID::from_str(name_or_id).ok().or_else(|| {
+            self.ids
+                .get(name_or_id)
+                .filter(|(_, scope)| scope.is_none_or(|scope| scope == section_type))
+                .map(|(id, _)| *id)
+        })
+    }
+
+    /// Describes the given ID alongside its name, i.e. `0x0dad06d8 (music_icon)`.
+    pub fn describe(&self, id: SilverResourceID) -> String {
+        describe_name(id, self.name(id))
+    }
+
+    /// Describes the given ID alongside its name within the given section.
+    pub fn describe_in(&self, section_type: SectionType, id: SilverResourceID) -> String {
+        describe_name(id, self.name_in(section_type, id))
+    }
+
+    /// All symbols within this table alongside the section they are limited to,
+    /// in order of their ID.
+    pub fn iter(&self) -> impl Iterator<Item = (SilverResourceID, Option<SectionType>, &str)> {
+        self.names
+            .iter()
+            .map(|((id, section_type), name)| (*id, *section_type, name.as_str()))
+    }
+
+    /// The amount of symbols within this table.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether this table holds no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Parses the contents of a symbol file.
+    pub fn parse(contents: &str) -> Result<Self, SilverError> {
+        let mut table = SymbolTable::new();
+        for (index, raw_line) in contents.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || SilverError::InvalidSymbol(format!("line {}: {line}", index + 1));
+            // Section names may contain spaces (i.e. `Str `), and are thus bracketed.
+            let (section_type, symbol) = match line.strip_prefix('[') {
+                Some(scoped) => {
+                    let (section_name, symbol) = scoped.split_once(']').ok_or_else(invalid)?;
+                    let section_type =
+                        SectionType::from_name(section_name.to_string()).map_err(|_| invalid())?;
+                    (Some(section_type), symbol)
+                }
+                None => (None, line),
+            };
+
+            let mut parts = symbol.split_whitespace();
+            let (Some(raw_id), Some(name), None) = (parts.next(), parts.next(), parts.next())
+            else {
+                return Err(invalid());
+            };
+            let id = SilverResourceID::from_str(raw_id).map_err(|_| invalid())?;
+            table.insert_symbol(id, section_type, name.to_string())?;
+        }
+        Ok(table)
+    }
+
+    /// Writes this table in the format read by `SymbolTable::parse`.
+    pub fn to_file_contents(&self) -> String {
+        self.iter()
+            .map(|(id, section_type, name)| match section_type {
+                Some(section_type) => format!("[{}] {id} {name}\n", section_type.to_name()),
+                None => format!("{id} {name}\n"),
+            })
+            .collect()
+    }
+
+    /// Derives names for `Str ` resources from the `StrT` section of the given database.
+    /// Placeholder names unsuitable as symbols (i.e. containing spaces) are skipped.
+    ///
+    /// As other sections reuse these small IDs, names are limited to `Str `.
+    pub fn from_placeholder_names(database: &SilverDB) -> Self {
+        let mut table = SymbolTable::new();
+        for (id, name) in placeholder_names(database) {
+            // Duplicate or invalid names are simply skipped.
+            _ = table.insert_in(SectionType::String, id, name);
+        }
+        table
+    }
+}
+
+/// Describes an ID alongside its name, if any.
+fn describe_name(id: SilverResourceID, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{id} ({name})"),
+        None => id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::string_section;
+
+    #[test]
+    fn parses_and_resolves_symbols() {
+        let contents = "# Icons\n\n0x0dad06d8 music_icon\n  229441537\tphotos.icon  \n";
+        let table = SymbolTable::parse(contents).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.name(SilverResourceID(0x0dad06d8)), Some("music_icon"));
+
+        assert_eq!(
+            table.resolve("photos.icon"),
+            Some(SilverResourceID(0x0dad0001))
+        );
+        assert_eq!(
+            table.resolve("0x0dad0002"),
+            Some(SilverResourceID(0x0dad0002))
+        );
+        assert_eq!(table.resolve("12"), Some(SilverResourceID(12)));
+        assert_eq!(table.resolve("video_icon"), None);
+
+        assert_eq!(
+            table.describe(SilverResourceID(0x0dad06d8)),
+            "0x0dad06d8 (music_icon)"
+        );
+        assert_eq!(table.describe(SilverResourceID(12)), "0x0000000c");
+
+        let written = table.to_file_contents();
+        assert_eq!(written, "0x0dad0001 photos.icon\n0x0dad06d8 music_icon\n");
+        let reparsed = SymbolTable::parse(&written).unwrap();
+        assert!(reparsed.iter().eq(table.iter()));
+    }
+
+    #[test]
+    fn scopes_symbols_to_sections() {
+        let contents = "[Str ] 1 Music_Title\n[BMap] 0x00000001 music_icon\n0x00000002 shared\n";
+        let table = SymbolTable::parse(contents).unwrap();
+        let id = SilverResourceID(1);
+        assert_eq!(table.name_in(SectionType::String, id), Some("Music_Title"));
+        assert_eq!(table.name_in(SectionType::Bitmap, id), Some("music_icon"));
+        assert_eq!(table.name_in(SectionType::StringTranslation, id), None);
+        assert_eq!(table.name(id), None);
+        assert_eq!(
+            table.describe_in(SectionType::Bitmap, SilverResourceID(2)),
+            "0x00000002 (shared)"
+        );
+
+        assert_eq!(
+            table.resolve_in(SectionType::Bitmap, "music_icon"),
+            Some(id)
+        );
+        assert_eq!(table.resolve_in(SectionType::String, "music_icon"), None);
+        assert_eq!(
+            table.resolve_in(SectionType::String, "shared"),
+            Some(SilverResourceID(2))
+        );
+
+        let written = table.to_file_contents();
+        assert_eq!(
+            written,
+            "[BMap] 0x00000001 music_icon\n[Str ] 0x00000001 Music_Title\n0x00000002 shared\n"
+        );
+        let reparsed = SymbolTable::parse(&written).unwrap();
+        assert!(reparsed.iter().eq(table.iter()));
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        for contents in [
+            "0x0dad06d8",
+            "0x0dad06d8 music icon",
+            "music_icon 0x0dad06d8",
+            "0x0dad06d8 0x0dad0001",
+            "0x0dad06d8 music/icon",
+            "[Str 0x0dad06d8 music_icon",
+            "[Strings] 0x0dad06d8 music_icon",
+        ] {
+            assert!(
+                matches!(
+                    SymbolTable::parse(contents),
+                    Err(SilverError::InvalidSymbol(_))
+                ),
+                "{contents}"
+            );
+        }
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let mut table = SymbolTable::new();
+        table
+            .insert(SilverResourceID(1), "music".to_string())
+            .unwrap();
+        assert!(table
+            .insert(SilverResourceID(2), "music".to_string())
+            .is_err());
+
+        // Renaming an ID frees its previous name.
+        table
+            .insert(SilverResourceID(1), "songs".to_string())
+            .unwrap();
+        assert_eq!(table.resolve("music"), None);
+        assert_eq!(table.resolve("songs"), Some(SilverResourceID(1)));
+        table
+            .insert(SilverResourceID(2), "music".to_string())
+            .unwrap();
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn derives_names_from_placeholders() {
+        let database = SilverDB {
+            sections: vec![string_section(
+                "StrT",
+                &[(1, "Music_Title"), (2, "Not a symbol"), (3, "Music_Title")],
+            )],
+        };
+
+        let table = SymbolTable::from_placeholder_names(&database);
+        let symbols: Vec<_> = table
+            .iter()
+            .map(|(id, section_type, name)| (id.0, section_type, name))
+            .collect();
+        assert_eq!(symbols, vec![(1, Some(SectionType::String), "Music_Title")]);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{
+    database::{SilverDB, SilverResource, SilverResourceID, SilverSection},
+    localization::TranslationUnit,
+    section_content::SectionContent,
+    section_types::SectionType,
+};
+
+/// A section of the given name (i.e. `Str `) holding the given resources.
+pub(crate) fn section(name: &str, resources: Vec<(u32, SectionContent)>) -> SilverSection {
+    SilverSection {
+        section_type: SectionType::from_name(name.to_string()).unwrap(),
+        is_sequential: 1,
+        resources: resources
+            .into_iter()
+            .map(|(id, contents)| SilverResource {
+                id: SilverResourceID(id),
+                contents,
+            })
+            .collect(),
+    }
+}
+
+/// A section of the given name holding the given strings.
+pub(crate) fn string_section(name: &str, strings: &[(u32, &str)]) -> SilverSection {
+    let resources = strings
+        .iter()
+        .map(|(id, text)| (*id, SectionContent::String((*text).into())))
+        .collect();
+    section(name, resources)
+}
+
+/// A database holding a single `Str ` section with the given strings.
+pub(crate) fn string_database(strings: &[(u32, &str)]) -> SilverDB {
+    SilverDB {
+        sections: vec![string_section("Str ", strings)],
+    }
+}
+
+/// A translation unit of the given string.
+pub(crate) fn unit(id: u32, source: &str, target: Option<&str>) -> TranslationUnit {
+    TranslationUnit {
+        id: SilverResourceID(id),
+        source: source.to_string(),
+        target: target.map(str::to_string),
+    }
+}
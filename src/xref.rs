@@ -0,0 +1,284 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    database::{SilverDB, SilverResourceID},
+    section_content::SectionContent,
+    section_types::SectionType,
+};
+
+/// Resource IDs assigned within firmware are 0x0DAD_0000 or greater.
+/// (`RawBitmapData::parse` relies on this same assumption.)
+///
+/// Sequential sections such as `Str ` and `StrT` use small IDs starting at 1.
+/// Matching those everywhere would flag nearly every small integer, so they are
+/// only matched when requested, and only within `STRING_REFERENCING_SECTIONS`.
+const MINIMUM_RESOURCE_ID: u32 = 0x0DAD_0000;
+
+/// Sections describing UI elements, whose contents are believed to hold
+/// the IDs of the `Str ` resources they display.
+///
+/// This is unconfirmed: the layout of these sections is not yet understood.
+const STRING_REFERENCING_SECTIONS: [&str; 7] =
+    ["View", "VLyt", "SCRN", "ITEM", "SLst", "TVCL", "TVCS"];
+
+/// Identifies a resource within a database by its section and ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceLocation {
+    /// The section this resource is present within.
+    pub section_type: SectionType,
+    /// The ID of this resource.
+    pub id: SilverResourceID,
+}
+
+/// How likely an inferred reference is to be genuine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceConfidence {
+    /// The value matched a resource ID assigned within firmware,
+    /// which is unlikely to occur by coincidence.
+    High,
+    /// The value matched the small, sequential ID of a `Str ` resource.
+    /// Small integers are common within any structure, so many are coincidental.
+    Low,
+}
+
+/// A reference from one resource's contents to another resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResourceReference {
+    /// The resource whose contents hold the referenced ID.
+    pub source: ResourceLocation,
+    /// The offset within the source's raw contents where the ID was found.
+    pub offset: u32,
+    /// The resource being referenced.
+    pub target: ResourceLocation,
+    /// How likely this reference is to be genuine.
+    pub confidence: ReferenceConfidence,
+}
+
+/// A graph of all resources referencing other resources within the same database.
+///
+/// References are inferred: we scan raw contents for little-endian u32 values
+/// matching the ID of another resource. As such, false positives are possible.
+pub struct ReferenceGraph {
+    /// All references found, in the order of their source resource.
+    pub references: Vec<ResourceReference>,
+}
+
+impl ReferenceGraph {
+    /// Scans every resource within the given database for embedded resource IDs.
+    ///
+    /// Only IDs assigned within firmware are matched, so references to
+    /// sequential sections such as `Str ` are not found.
+    pub fn build(database: &SilverDB) -> Self {
+        ReferenceGraph::scan(database, false)
+    }
+
+    /// Scans every resource as with `ReferenceGraph::build`, additionally matching
+    /// `Str ` IDs within sections believed to reference strings.
+    ///
+    /// As such IDs are small integers, these references are of low confidence.
+    pub fn build_including_strings(database: &SilverDB) -> Self {
+        ReferenceGraph::scan(database, true)
+    }
+
+    fn scan(database: &SilverDB, include_strings: bool) -> Self {
+        // First, determine where every possible resource ID lives.
+        // IDs are not guaranteed to be unique across sections.
+        let mut known_ids: HashMap<u32, Vec<SectionType>> = HashMap::new();
+        let mut string_ids: HashSet<u32> = HashSet::new();
+        for section in &database.sections {
+            for resource in &section.resources {
+                if resource.id.0 >= MINIMUM_RESOURCE_ID {
+                    known_ids
+                        .entry(resource.id.0)
+                        .or_default()
+                        .push(section.section_type);
+                } else if section.section_type == SectionType::String {
+                    string_ids.insert(resource.id.0);
+                }
+            }
+        }
+
+        // Next, scan the raw contents of every resource.
+        let mut references: Vec<ResourceReference> = Vec::new();
+        for section in &database.sections {
+            let references_strings = include_strings
+                && STRING_REFERENCING_SECTIONS.contains(&section.section_type.to_name().as_str());
+
+            for resource in &section.resources {
+                // Strings and bitmaps are not known to reference other resources.
+                // (Bitmaps do contain an ID, but it is their own.)
+                let raw_contents = match &resource.contents {
+                    SectionContent::Unknown(raw_contents)
+                    | SectionContent::DateTimeLocale(raw_contents) => raw_contents,
+                    _ => continue,
+                };
+
+                let source = ResourceLocation {
+                    section_type: section.section_type,
+                    id: resource.id,
+                };
+
+                // Many structures begin with a u16 element count, so IDs
+                // are not always aligned to 4 bytes. We check every 2 bytes.
+                let mut offset = 0;
+                while offset + 4 <= raw_contents.len() {
+                    let value = u32::from_le_bytes([
+                        raw_contents[offset],
+                        raw_contents[offset + 1],
+                        raw_contents[offset + 2],
+                        raw_contents[offset + 3],
+                    ]);
+
+                    let (target_sections, confidence) = match known_ids.get(&value) {
+                        Some(target_sections) => {
+                            (target_sections.as_slice(), ReferenceConfidence::High)
+                        }
+                        None if references_strings && string_ids.contains(&value) => {
+                            (&[SectionType::String][..], ReferenceConfidence::Low)
+                        }
+                        None => (&[][..], ReferenceConfidence::High),
+                    };
+                    if value != resource.id.0 {
+                        for target_section in target_sections {
+                            references.push(ResourceReference {
+                                source,
+                                offset: offset as u32,
+                                target: ResourceLocation {
+                                    section_type: *target_section,
+                                    id: SilverResourceID(value),
+                                },
+                                confidence,
+                            });
+                        }
+                    }
+
+                    offset += 2;
+                }
+            }
+        }
+
+        ReferenceGraph { references }
+    }
+
+    /// References from the given resource to others.
+    ///
+    /// IDs are only unique within their section (i.e. `Str ` and `StrT` share IDs).
+    pub fn outgoing(&self, location: ResourceLocation) -> impl Iterator<Item = &ResourceReference> {
+        self.references
+            .iter()
+            .filter(move |reference| reference.source == location)
+    }
+
+    /// References to the given resource from others.
+    pub fn incoming(&self, location: ResourceLocation) -> impl Iterator<Item = &ResourceReference> {
+        self.references
+            .iter()
+            .filter(move |reference| reference.target == location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{silver_string::SilverString, test_fixtures::section};
+
+    fn string(text: &str) -> SectionContent {
+        SectionContent::String(SilverString::decode(format!("{text}\0").as_bytes()))
+    }
+
+    fn targets(graph: &ReferenceGraph, name: &str) -> Vec<(String, u32)> {
+        graph
+            .references
+            .iter()
+            .filter(|reference| reference.source.section_type.to_name() == name)
+            .map(|reference| {
+                (
+                    reference.target.section_type.to_name(),
+                    reference.target.id.0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_string_references_within_views_only() {
+        // A view holding a string ID, preceded by a u16 element count and
+        // followed by a color's ID. A color holding the same small value.
+        let mut view = vec![0x01, 0x00, 0x02, 0x00, 0x00, 0x00];
+        view.extend(0x0dad0010u32.to_le_bytes());
+        let database = SilverDB {
+            sections: vec![
+                section("Str ", vec![(1, string("Music")), (2, string("Photos"))]),
+                section("StrT", vec![(2, string("Photos_Title"))]),
+                section(
+                    "COLR",
+                    vec![(0x0dad0010, SectionContent::Unknown(vec![2, 0, 0, 0]))],
+                ),
+                section("View", vec![(0x0dad0020, SectionContent::Unknown(view))]),
+            ],
+        };
+
+        let graph = ReferenceGraph::build_including_strings(&database);
+        assert_eq!(
+            targets(&graph, "View"),
+            [("Str ".to_string(), 2), ("COLR".to_string(), 0x0dad0010)]
+        );
+        assert!(targets(&graph, "COLR").is_empty());
+
+        // Only the string reference is of low confidence.
+        let confidences: Vec<_> = graph.references.iter().map(|r| r.confidence).collect();
+        assert_eq!(
+            confidences,
+            [ReferenceConfidence::Low, ReferenceConfidence::High]
+        );
+
+        // Lookups are keyed by section, as `StrT` shares IDs with `Str `.
+        let location = |name: &str, id| ResourceLocation {
+            section_type: SectionType::from_name(name.to_string()).unwrap(),
+            id: SilverResourceID(id),
+        };
+        assert_eq!(graph.incoming(location("Str ", 2)).count(), 1);
+        assert_eq!(graph.incoming(location("StrT", 2)).count(), 0);
+        assert_eq!(graph.outgoing(location("View", 0x0dad0020)).count(), 2);
+        assert_eq!(graph.outgoing(location("COLR", 0x0dad0020)).count(), 0);
+
+        // Without opting in, small IDs are never matched.
+        let graph = ReferenceGraph::build(&database);
+        assert_eq!(targets(&graph, "View"), [("COLR".to_string(), 0x0dad0010)]);
+    }
+
+    #[test]
+    fn arbitrary_view_contents_hold_no_string_references() {
+        // Small integers are everywhere: counts, coordinates, flags.
+        let view: Vec<u8> = (0u8..=255).flat_map(|value| [value, 0, 0, 0]).collect();
+        let database = SilverDB {
+            sections: vec![
+                section("Str ", (1..=64).map(|id| (id, string("Text"))).collect()),
+                section("View", vec![(0x0dad0020, SectionContent::Unknown(view))]),
+            ],
+        };
+        assert!(ReferenceGraph::build(&database).references.is_empty());
+
+        // When requested, any that are found are marked as such.
+        let graph = ReferenceGraph::build_including_strings(&database);
+        assert!(!graph.references.is_empty());
+        assert!(graph
+            .references
+            .iter()
+            .all(|reference| reference.confidence == ReferenceConfidence::Low));
+    }
+
+    #[test]
+    fn ignores_own_resource_id() {
+        let database = SilverDB {
+            sections: vec![section(
+                "View",
+                vec![(
+                    0x0dad0020,
+                    SectionContent::Unknown(0x0dad0020u32.to_le_bytes().to_vec()),
+                )],
+            )],
+        };
+        assert!(ReferenceGraph::build(&database).references.is_empty());
+    }
+}